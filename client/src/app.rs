@@ -0,0 +1,161 @@
+use macroquad::prelude::*;
+
+use crate::audio::Audio;
+use crate::gamepad::Gamepad;
+use crate::minimap::Minimap;
+use crate::network::NetClient;
+use crate::rendering::{WorldRenderer, draw_announcement};
+use crate::scene::{self, Scene, connect::ConnectForm, lobby::LevelPicker};
+use crate::session::Session;
+use crate::settings::{Settings, SettingsMenu};
+use crate::textures;
+
+// How long announcements stay on screen (seconds)
+const ANNOUNCEMENT_SECS: f32 = 3.0;
+
+// ---------- App ----------
+/// State that outlives a connection (window, preferences, the connect form)
+/// plus the current scene and, while online, its `NetClient` and `Session`.
+pub struct App {
+    pub scene: Scene,                                        // Which screen we're on
+    pub net: Option<NetClient>,                              // Network client (None until connected)
+    pub session: Session,                                    // The game we're in, rebuilt on disconnect
+    pub form: ConnectForm,                                   // Server address, name and team
+    pub picker: LevelPicker,                                 // Level and skin selection
+
+    // --- Rendering and Devices ---
+    pub renderer: WorldRenderer,                             // Framebuffer for the 3D view
+    pub settings: Settings,                                  // FOV, sensitivity, bindings... from the config dir
+    pub gamepad: Gamepad,                                    // Connected pads, if built with the feature
+    pub audio: Audio,                                        // Synthesized sound effects
+    pub minimap: Minimap,                                    // Explored cells and view options
+
+    // --- In-Game Overlays ---
+    pub mouse_captured: bool,                                // Whether mouse is captured for FPS controls
+    pub map_change_mode: bool,                               // Whether we're in map change mode during gameplay
+    pub settings_open: bool,                                 // Whether the settings menu is showing
+    pub settings_menu: SettingsMenu,                         // Its cursor and rebinding state
+    pub kill_cam_enabled: bool,                              // Show the killer's view for a moment after dying
+}
+
+impl App {
+    pub async fn new() -> Self {
+        let atlas = textures::TextureAtlas::load(textures::ATLAS_PATH).await;  // Wall textures
+        show_mouse(true);                                    // Show mouse cursor initially
+        Self {
+            scene: Scene::Connect,                           // Start in connection state
+            net: None,
+            session: Session::new(),
+            form: ConnectForm::default(),
+            picker: LevelPicker::default(),
+            renderer: WorldRenderer::new(atlas),
+            settings: Settings::load(),
+            gamepad: Gamepad::new(),
+            audio: Audio::load().await,
+            minimap: Minimap::default(),
+            mouse_captured: false,
+            map_change_mode: false,
+            settings_open: false,
+            settings_menu: SettingsMenu::default(),
+            kill_cam_enabled: true,
+        }
+    }
+
+    /// One frame: apply settings, then let the current scene update and draw.
+    pub fn frame(&mut self) {
+        let dt = macroquad::time::get_frame_time();           // Get time since last frame (delta time)
+        let pad = self.gamepad.poll(&self.settings.gamepad, dt);  // Read the pad once, before anything uses it
+
+        self.renderer.set_fov(self.settings.fov_deg);
+        self.audio.set_volume(self.settings.volume);
+        let radar = self.session.match_state.as_ref().map(|m| m.enemy_radar).unwrap_or_default();
+        self.minimap.set_view(self.settings.minimap_rotate, self.settings.minimap_zoom, self.settings.fog_of_war, radar);
+        self.renderer.set_scale(self.settings.render_scale);
+
+        // Clear screen to black background
+        clear_background(BLACK);
+
+        match self.scene {
+            Scene::Connect => scene::connect::update(self),
+            Scene::Connecting { .. } => scene::connecting::update(self),
+            Scene::Lobby => scene::lobby::update(self),
+            Scene::Playing => scene::playing::update(self, &pad, dt),
+            Scene::Disconnected { .. } => scene::disconnected::update(self),
+        }
+
+        // Draw whatever scene we ended up in
+        match self.scene {
+            Scene::Connect => scene::connect::draw(self),
+            Scene::Connecting { .. } => scene::connecting::draw(self),
+            Scene::Lobby => scene::lobby::draw(self),
+            Scene::Playing => scene::playing::draw(self, &pad),
+            Scene::Disconnected { .. } => scene::disconnected::draw(self),
+        }
+
+        // Flag events and server complaints, on top of any scene
+        if let Some((ref text, at)) = self.session.announcement {
+            draw_announcement(text, (macroquad::time::get_time() - at) as f32, ANNOUNCEMENT_SECS);
+        }
+    }
+
+    /// Opens the socket and sends the Join with what's in the form.
+    pub fn connect(&mut self) {
+        let (addr, name) = (self.form.server_addr.trim(), self.form.username.trim());
+        (self.scene, self.net) = scene::connect(addr, name, self.form.team_choice, macroquad::time::get_time());
+    }
+
+    /// Reads what the server sent and runs the scene's timeouts. Every online
+    /// scene calls this first in its `update`.
+    pub fn poll_server(&mut self) {
+        let Some(net) = &mut self.net else { return };
+        let now = macroquad::time::get_time();
+        for msg in net.receive(now) {
+            match msg {
+                // --- Only fatal server errors end the session ---
+                protocol::ServerToClient::Error { message, fatal } => {
+                    println!("❌ CLIENT: Server error: {}", message);
+                    if !self.scene.server_error(&message, fatal) {
+                        break;                                // Ignore whatever else was queued
+                    }
+                    self.session.announce(format!("Server: {}", message));
+                }
+                msg => {
+                    if let protocol::ServerToClient::Accept(_) = msg {
+                        self.scene.accepted();                // Connecting -> Lobby
+                    }
+                    self.session.handle(msg, self.form.username.trim(), &mut self.audio, &mut self.renderer);
+                }
+            }
+        }
+
+        // Resend the Join if it's taking too long; give up on a silent server
+        if self.scene.tick(now, net.last_heard, net.last_error.as_deref()) {
+            println!("⚠️ CLIENT: No answer yet, resending Join");
+            net.rejoin();
+        }
+
+        if let Scene::Disconnected { reason } = &self.scene {
+            println!("❌ CLIENT: Disconnected: {}", reason);
+            self.end_session();
+        }
+    }
+
+    /// Drops the connection and everything we knew about the game, so the next connect starts clean.
+    pub fn end_session(&mut self) {
+        if let (Some(net), Some(player_id)) = (&self.net, self.session.my_player_id) {
+            let _ = net.tx_outgoing.send(protocol::ClientToServer::Leave(protocol::LeaveNotice { player_id }));
+        }
+        self.net = None;                                     // Network thread exits once the Leave is out
+        self.session = Session::new();
+        self.map_change_mode = false;
+        self.settings_open = false;
+        self.capture_mouse(false);                           // Free the mouse for the menu
+    }
+
+    /// Grabs and hides the cursor for mouse look, or gives it back.
+    pub fn capture_mouse(&mut self, captured: bool) {
+        set_cursor_grab(captured);
+        show_mouse(!captured);
+        self.mouse_captured = captured;
+    }
+}
@@ -0,0 +1,296 @@
+use macroquad::audio::{load_sound_from_bytes, Sound};
+use macroquad::prelude::*;
+use std::f32::consts::{FRAC_PI_4, TAU};
+use crate::level::Level;
+use crate::player::Player;
+use crate::raycast;
+
+// Sound effects are synthesized at startup (like the fallback textures) and
+// played through macroquad's mixer, behind the `audio` cargo feature. It's on
+// by default, but on Linux it links against ALSA (`libasound2-dev` /
+// `alsa-lib-devel`), so without those headers build the client silent:
+//
+//     cargo run --bin client --no-default-features
+//
+// Without the feature the clips are still built and placed, just never played.
+// The mixer has no panning, so every clip is pre-mixed at a few pan positions,
+// clear and muffled, and each play picks the closest one.
+
+// ---------- Config ----------
+const SAMPLE_RATE: u32 = 44_100;  // the mixer's rate, so clips aren't resampled
+const PAN_STEPS: usize = 5;       // pan positions each clip is pre-mixed at, left to right
+const REF_DISTANCE: f32 = 2.0;    // cells at which a sound is at half volume
+const MAX_DISTANCE: f32 = 20.0;   // cells beyond which nothing is heard
+const BEHIND_GAIN: f32 = 0.75;    // sounds straight behind are a bit quieter
+const OCCLUDED_GAIN: f32 = 0.35;  // through a wall: quieter and muffled
+const MUFFLE: f32 = 0.12;         // one-pole low-pass coefficient for muffled clips
+const STEP_LENGTH: f32 = 0.3;     // cells per footstep of our own, half a walk cycle
+const OWN_STEP_GAIN: f32 = 0.5;   // our own footsteps stay in the background
+
+// ---------- Sounds ----------
+#[derive(Clone, Copy, Debug, PartialEq, Eq)]
+pub enum Sfx {
+    Gunshot,
+    Impact,   // a bullet hitting a wall
+    Hit,      // we took damage
+    Death,
+    Footstep,
+}
+
+impl Sfx {
+    const ALL: [Sfx; 5] = [Sfx::Gunshot, Sfx::Impact, Sfx::Hit, Sfx::Death, Sfx::Footstep];
+
+    // Loudness relative to the others
+    fn gain(self) -> f32 {
+        match self {
+            Sfx::Gunshot => 0.9,
+            Sfx::Impact => 0.5,
+            Sfx::Hit => 0.8,
+            Sfx::Death => 0.8,
+            Sfx::Footstep => 0.4,
+        }
+    }
+}
+
+// ---------- Spatialization ----------
+/// How a sound reaches the listener.
+#[derive(Clone, Copy, Debug, PartialEq)]
+pub struct Voice {
+    pub gain: f32,     // 0..1
+    pub pan: f32,      // -1 left .. 1 right
+    pub muffled: bool, // a wall is in the way
+}
+
+impl Voice {
+    const CENTER: Voice = Voice { gain: 1.0, pan: 0.0, muffled: false };
+}
+
+/// How a sound at `source` sounds to a listener at `listener` facing `facing`;
+/// None if it's too far away to hear.
+pub fn spatialize(level: &Level, listener: Vec2, facing: f32, source: Vec2) -> Option<Voice> {
+    let to = source - listener;
+    let dist = to.length();
+    if dist >= MAX_DISTANCE {
+        return None;
+    }
+    let mut gain = REF_DISTANCE / (REF_DISTANCE + dist) * (1.0 - dist / MAX_DISTANCE);
+    let mut pan = 0.0;
+    if dist > 1e-3 {
+        let forward = vec2(facing.cos(), facing.sin());
+        let right = vec2(-forward.y, forward.x);
+        let dir = to / dist;
+        pan = dir.dot(right);
+        gain *= BEHIND_GAIN + (1.0 - BEHIND_GAIN) * (dir.dot(forward) + 1.0) * 0.5;
+    }
+    let muffled = raycast::occluded(level, listener, source);
+    if muffled {
+        gain *= OCCLUDED_GAIN;
+    }
+    Some(Voice { gain, pan, muffled })
+}
+
+// Which pre-mixed pan position is closest to `pan`.
+fn pan_slot(pan: f32) -> usize {
+    ((pan.clamp(-1.0, 1.0) + 1.0) * 0.5 * (PAN_STEPS - 1) as f32).round() as usize
+}
+
+fn slot_pan(slot: usize) -> f32 {
+    slot as f32 / (PAN_STEPS - 1) as f32 * 2.0 - 1.0
+}
+
+// ---------- Playback ----------
+pub struct Audio {
+    clips: Vec<Sound>, // per Sfx, per pan slot: clear, muffled
+    volume: f32,
+    own_walk: f32,     // cells since our last footstep
+}
+
+impl Audio {
+    /// Synthesizes and loads every clip. Silent if the mixer won't take them.
+    pub async fn load() -> Self {
+        #[cfg(not(feature = "audio"))]
+        println!("🔇 CLIENT: Built without the `audio` feature, sound is off");
+        let mut clips = Vec::new();
+        for sfx in Sfx::ALL {
+            let mono = synthesize(sfx);
+            for slot in 0..PAN_STEPS {
+                for muffled in [false, true] {
+                    match load_sound_from_bytes(&encode_wav(&mono, slot_pan(slot), muffled)).await {
+                        Ok(clip) => clips.push(clip),
+                        Err(e) => {
+                            println!("⚠️ CLIENT: Could not load sounds: {}", e);
+                            return Self { clips: Vec::new(), volume: 0.0, own_walk: 0.0 };
+                        }
+                    }
+                }
+            }
+        }
+        Self { clips, volume: 1.0, own_walk: 0.0 }
+    }
+
+    /// Master volume, 0..1 (from the settings).
+    pub fn set_volume(&mut self, volume: f32) {
+        self.volume = volume.clamp(0.0, 1.0);
+    }
+
+    /// Plays `sfx` where it happened, as heard by `player`.
+    pub fn play_at(&self, sfx: Sfx, level: &Level, player: &Player, source: Vec2) {
+        if let Some(voice) = spatialize(level, player.pos, player.dir, source) {
+            self.play(sfx, voice);
+        }
+    }
+
+    /// Plays `sfx` centered and at full level: something happening to us.
+    pub fn play_local(&self, sfx: Sfx) {
+        self.play(sfx, Voice::CENTER);
+    }
+
+    /// Our own footsteps, one every `STEP_LENGTH` cells walked.
+    pub fn walked(&mut self, distance: f32) {
+        self.own_walk += distance;
+        if self.own_walk >= STEP_LENGTH {
+            self.own_walk %= STEP_LENGTH;
+            self.play(Sfx::Footstep, Voice { gain: OWN_STEP_GAIN, ..Voice::CENTER });
+        }
+    }
+
+    fn play(&self, sfx: Sfx, voice: Voice) {
+        let index = (sfx as usize * PAN_STEPS + pan_slot(voice.pan)) * 2 + voice.muffled as usize;
+        let volume = self.volume * sfx.gain() * voice.gain;
+        let Some(clip) = self.clips.get(index) else {
+            return;
+        };
+        if volume < 0.005 {
+            return;
+        }
+        #[cfg(feature = "audio")]
+        macroquad::audio::play_sound(clip, macroquad::audio::PlaySoundParams { looped: false, volume });
+        #[cfg(not(feature = "audio"))]
+        let _ = clip;
+    }
+}
+
+// ---------- Procedural sounds ----------
+// Mono samples in -1..1 at SAMPLE_RATE.
+fn synthesize(sfx: Sfx) -> Vec<f32> {
+    let secs = match sfx {
+        Sfx::Gunshot => 0.3,
+        Sfx::Impact => 0.15,
+        Sfx::Hit => 0.25,
+        Sfx::Death => 0.7,
+        Sfx::Footstep => 0.09,
+    };
+    let len = (secs * SAMPLE_RATE as f32) as usize;
+    let mut seed = 0x9e37_79b9_u32 ^ sfx as u32;
+    let mut phase = 0.0f32;
+    let mut low = 0.0f32; // low-passed noise for the thuds
+    (0..len)
+        .map(|i| {
+            let t = i as f32 / SAMPLE_RATE as f32;
+            seed ^= seed << 13;
+            seed ^= seed >> 17;
+            seed ^= seed << 5;
+            let noise = seed as f32 / u32::MAX as f32 * 2.0 - 1.0;
+            low += (noise - low) * 0.08;
+            // Pitch sweeps accumulate phase so the tone glides smoothly
+            let mut tone = |hz: f32| {
+                phase = (phase + hz / SAMPLE_RATE as f32 * TAU) % TAU;
+                phase.sin()
+            };
+            let s = match sfx {
+                // Sharp crack over a falling thump
+                Sfx::Gunshot => noise * (-t * 25.0).exp() * 0.6 + tone(160.0 - 300.0 * t) * (-t * 18.0).exp() * 0.5,
+                // Click and a short ricochet ping
+                Sfx::Impact => noise * (-t * 60.0).exp() * 0.5 + tone(2200.0 - 4000.0 * t) * (-t * 30.0).exp() * 0.3,
+                // Low punch
+                Sfx::Hit => tone(90.0 - 120.0 * t) * (-t * 14.0).exp() * 0.8 + low * 3.0 * (-t * 40.0).exp(),
+                // Buzzy groan sliding down
+                Sfx::Death => tone(320.0 - 350.0 * t).signum() * 0.3 * (1.0 - t / secs),
+                // Soft scuff
+                Sfx::Footstep => low * 4.0 * (-t * 50.0).exp(),
+            };
+            // Fade the last few milliseconds so clips don't end on a click
+            let tail = ((secs - t) / 0.005).min(1.0);
+            (s * tail).clamp(-1.0, 1.0)
+        })
+        .collect()
+}
+
+// 16-bit stereo PCM WAV of `mono` panned to `pan`, low-passed if `muffled`.
+// Constant-power pan law, scaled so the nearer ear is at full level.
+fn encode_wav(mono: &[f32], pan: f32, muffled: bool) -> Vec<u8> {
+    let angle = (pan.clamp(-1.0, 1.0) + 1.0) * FRAC_PI_4;
+    let (left, right) = (angle.cos(), angle.sin());
+    let (left, right) = (left / left.max(right), right / left.max(right));
+
+    let data_len = (mono.len() * 4) as u32;
+    let mut wav = Vec::with_capacity(44 + data_len as usize);
+    wav.extend_from_slice(b"RIFF");
+    wav.extend_from_slice(&(36 + data_len).to_le_bytes());
+    wav.extend_from_slice(b"WAVEfmt ");
+    wav.extend_from_slice(&16u32.to_le_bytes());              // fmt chunk size
+    wav.extend_from_slice(&1u16.to_le_bytes());               // PCM
+    wav.extend_from_slice(&2u16.to_le_bytes());               // channels
+    wav.extend_from_slice(&SAMPLE_RATE.to_le_bytes());
+    wav.extend_from_slice(&(SAMPLE_RATE * 4).to_le_bytes());  // bytes per second
+    wav.extend_from_slice(&4u16.to_le_bytes());               // bytes per frame
+    wav.extend_from_slice(&16u16.to_le_bytes());              // bits per sample
+    wav.extend_from_slice(b"data");
+    wav.extend_from_slice(&data_len.to_le_bytes());
+
+    let mut filtered = 0.0f32;
+    for &s in mono {
+        filtered += (s - filtered) * MUFFLE;
+        let s = if muffled { filtered } else { s };
+        for gain in [left, right] {
+            wav.extend_from_slice(&((s * gain * i16::MAX as f32) as i16).to_le_bytes());
+        }
+    }
+    wav
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn sounds_pan_fade_and_muffle() {
+        let mut tiles = vec![0u8; 100];
+        tiles[5 * 10 + 5] = 1; // pillar
+        let level = Level::new(10, 10, tiles, "pillar".to_string());
+        let listener = vec2(2.5, 2.5);
+
+        // Facing +x, +y is to the right
+        let right = spatialize(&level, listener, 0.0, vec2(2.5, 4.5)).unwrap();
+        let left = spatialize(&level, listener, 0.0, vec2(2.5, 0.5)).unwrap();
+        let ahead = spatialize(&level, listener, 0.0, vec2(4.5, 2.5)).unwrap();
+        let behind = spatialize(&level, listener, 0.0, vec2(0.5, 2.5)).unwrap();
+        assert!(right.pan > 0.99 && left.pan < -0.99 && ahead.pan.abs() < 1e-5);
+        assert!(behind.gain < ahead.gain);
+        assert_eq!(pan_slot(right.pan), PAN_STEPS - 1);
+        assert_eq!(pan_slot(ahead.pan), PAN_STEPS / 2);
+
+        let far = spatialize(&level, listener, 0.0, vec2(8.5, 2.5)).unwrap();
+        assert!(far.gain < ahead.gain && !far.muffled);
+        assert!(spatialize(&level, listener, 0.0, listener + vec2(MAX_DISTANCE, 0.0)).is_none());
+
+        // Same distance, but the pillar is in the way
+        let clear = spatialize(&level, vec2(3.5, 3.5), 0.0, vec2(7.5, 3.5)).unwrap();
+        let hidden = spatialize(&level, vec2(3.5, 5.5), 0.0, vec2(7.5, 5.5)).unwrap();
+        assert!(hidden.muffled && !clear.muffled && hidden.gain < clear.gain);
+    }
+
+    #[test]
+    fn clips_are_well_formed_wavs() {
+        for sfx in Sfx::ALL {
+            let mono = synthesize(sfx);
+            assert!(!mono.is_empty() && mono.iter().all(|s| s.abs() <= 1.0));
+            let wav = encode_wav(&mono, -1.0, true);
+            assert_eq!(&wav[0..4], b"RIFF");
+            assert_eq!(wav.len(), 44 + mono.len() * 4);
+            assert_eq!(u32::from_le_bytes(wav[4..8].try_into().unwrap()) as usize, wav.len() - 8);
+            // Hard left: the right channel is silent
+            assert!(wav[44..].chunks(4).all(|frame| frame[2..4] == [0, 0]));
+        }
+    }
+}
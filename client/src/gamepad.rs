@@ -0,0 +1,302 @@
+use macroquad::prelude::*;
+use serde::{Deserialize, Deserializer, Serialize};
+use std::collections::HashMap;
+use crate::settings::Action;
+
+// Gamepads are read through gilrs, behind the `gamepad` cargo feature because
+// on Linux it links against libudev (`libudev-dev` / `systemd-devel`):
+//
+//     cargo run --bin client --features gamepad
+//
+// Without the feature everything here still compiles and `Gamepad::poll`
+// reports no pad, so keyboard and mouse work as before.
+
+// ---------- Config ----------
+const LOOK_ACCEL_THRESHOLD: f32 = 0.9; // stick tilt past which look speed ramps up
+const LOOK_ACCEL_SECS: f32 = 0.6;      // time to reach full boost
+const LOOK_ACCEL_BOOST: f32 = 1.8;     // look speed multiplier at full boost
+#[cfg(feature = "gamepad")]
+const TRIGGER_PRESSED: f32 = 0.5;      // analog trigger travel that counts as a press
+
+// ---------- Buttons ----------
+/// Gamepad buttons, named after their position (South is A on Xbox, Cross on PlayStation).
+#[derive(Clone, Copy, Debug, PartialEq, Eq, Serialize, Deserialize)]
+pub enum PadButton {
+    South,
+    East,
+    North,
+    West,
+    LeftBumper,
+    RightBumper,
+    LeftTrigger,
+    RightTrigger,
+    Select,
+    Start,
+    LeftStick,
+    RightStick,
+    DPadUp,
+    DPadDown,
+    DPadLeft,
+    DPadRight,
+}
+
+/// Buttons for the actions the sticks don't cover.
+#[derive(Clone, Debug, PartialEq, Serialize)]
+pub struct PadBindings {
+    pub shoot: PadButton,
+    pub reload: PadButton,
+    pub next_weapon: PadButton,
+    pub prev_weapon: PadButton,
+    pub scoreboard: PadButton,
+    pub kill_cam: PadButton,
+}
+
+impl Default for PadBindings {
+    fn default() -> Self {
+        Self {
+            shoot: PadButton::RightTrigger,
+            reload: PadButton::West,
+            next_weapon: PadButton::RightBumper,
+            prev_weapon: PadButton::LeftBumper,
+            scoreboard: PadButton::Select,
+            kill_cam: PadButton::North,
+        }
+    }
+}
+
+// Like `KeyBindings`: an unknown button name only resets that binding.
+impl<'de> Deserialize<'de> for PadBindings {
+    fn deserialize<D: Deserializer<'de>>(deserializer: D) -> Result<Self, D::Error> {
+        let saved = HashMap::<String, serde_json::Value>::deserialize(deserializer)?;
+        let mut bindings = PadBindings::default();
+        for action in Action::ALL {
+            let Some(value) = saved.get(action.field()) else {
+                continue;
+            };
+            match PadButton::deserialize(value) {
+                Ok(button) => bindings.set(action, button),
+                Err(e) => println!("⚠️ CLIENT: Default pad binding for {}: {}", action.label(), e),
+            }
+        }
+        Ok(bindings)
+    }
+}
+
+impl PadBindings {
+    /// The button for `action`; None for actions the sticks handle.
+    pub fn get(&self, action: Action) -> Option<PadButton> {
+        match action {
+            Action::Shoot => Some(self.shoot),
+            Action::Reload => Some(self.reload),
+            Action::NextWeapon => Some(self.next_weapon),
+            Action::PrevWeapon => Some(self.prev_weapon),
+            Action::Scoreboard => Some(self.scoreboard),
+            Action::KillCam => Some(self.kill_cam),
+            Action::Forward | Action::Back | Action::StrafeLeft | Action::StrafeRight => None,
+        }
+    }
+
+    pub fn set(&mut self, action: Action, button: PadButton) {
+        let slot = match action {
+            Action::Shoot => &mut self.shoot,
+            Action::Reload => &mut self.reload,
+            Action::NextWeapon => &mut self.next_weapon,
+            Action::PrevWeapon => &mut self.prev_weapon,
+            Action::Scoreboard => &mut self.scoreboard,
+            Action::KillCam => &mut self.kill_cam,
+            Action::Forward | Action::Back | Action::StrafeLeft | Action::StrafeRight => return,
+        };
+        *slot = button;
+    }
+}
+
+// ---------- Settings ----------
+#[derive(Clone, Debug, PartialEq, Serialize, Deserialize)]
+#[serde(default)]
+pub struct GamepadSettings {
+    pub index: usize,     // which connected pad to read, so several clients can share a machine
+    pub dead_zone: f32,   // stick tilt ignored, 0..1
+    pub curve: f32,       // response exponent: 1 linear, higher = finer aim near the center
+    pub look_speed: f32,  // radians per second at full tilt
+    pub bindings: PadBindings,
+}
+
+impl Default for GamepadSettings {
+    fn default() -> Self {
+        Self { index: 0, dead_zone: 0.15, curve: 2.0, look_speed: 3.0, bindings: PadBindings::default() }
+    }
+}
+
+// ---------- Stick shaping ----------
+/// Radial dead zone and response curve: tilt inside `dead_zone` reads as zero,
+/// the rest is rescaled to 0..1 and raised to `curve`, keeping the direction.
+pub fn shape_stick(raw: Vec2, dead_zone: f32, curve: f32) -> Vec2 {
+    let len = raw.length();
+    if len <= dead_zone || len < 1e-6 {
+        return Vec2::ZERO;
+    }
+    let t = ((len - dead_zone) / (1.0 - dead_zone)).min(1.0);
+    raw / len * t.powf(curve)
+}
+
+/// Ramps look speed up while the look stick is held near full tilt, so small
+/// corrections stay precise but full turns don't take forever.
+#[derive(Default)]
+pub struct LookAccel {
+    held: f32, // seconds at full tilt
+}
+
+impl LookAccel {
+    pub fn update(&mut self, tilt: f32, dt: f32) -> f32 {
+        if tilt >= LOOK_ACCEL_THRESHOLD {
+            self.held = (self.held + dt).min(LOOK_ACCEL_SECS);
+        } else {
+            self.held = 0.0;
+        }
+        1.0 + (LOOK_ACCEL_BOOST - 1.0) * self.held / LOOK_ACCEL_SECS
+    }
+}
+
+// ---------- Polling ----------
+/// One frame of gamepad state, already shaped by the settings.
+#[derive(Default)]
+pub struct PadInput {
+    pub connected: bool,
+    pub forward: f32,
+    pub strafe: f32,
+    pub rot: f32,                 // radians this frame
+    pub down: Vec<PadButton>,     // held
+    pub pressed: Vec<PadButton>,  // went down this frame
+}
+
+impl PadInput {
+    pub fn is_down(&self, button: PadButton) -> bool {
+        self.down.contains(&button)
+    }
+
+    pub fn is_pressed(&self, button: PadButton) -> bool {
+        self.pressed.contains(&button)
+    }
+}
+
+pub struct Gamepad {
+    #[cfg(feature = "gamepad")]
+    gilrs: Option<gilrs::Gilrs>,
+    was_down: Vec<PadButton>,
+    look_accel: LookAccel,
+}
+
+impl Gamepad {
+    pub fn new() -> Self {
+        Self {
+            #[cfg(feature = "gamepad")]
+            gilrs: match gilrs::GilrsBuilder::new().with_default_filters(false).build() {
+                Ok(g) => Some(g),
+                Err(e) => {
+                    println!("⚠️ CLIENT: Gamepads unavailable: {}", e);
+                    None
+                }
+            },
+            was_down: Vec::new(),
+            look_accel: LookAccel::default(),
+        }
+    }
+
+    /// Reads the configured pad once per frame.
+    pub fn poll(&mut self, settings: &GamepadSettings, dt: f32) -> PadInput {
+        let Some((left, right, down)) = self.read(settings.index) else {
+            self.was_down.clear();
+            return PadInput::default();
+        };
+        let pressed = down.iter().filter(|b| !self.was_down.contains(b)).copied().collect();
+        self.was_down = down.clone();
+
+        let mov = shape_stick(left, settings.dead_zone, settings.curve);
+        let look = shape_stick(right, settings.dead_zone, settings.curve);
+        let boost = self.look_accel.update(right.length(), dt);
+        PadInput {
+            connected: true,
+            forward: mov.y,
+            strafe: mov.x,
+            rot: -look.x * settings.look_speed * boost * dt, // right on the stick turns like the mouse
+            down,
+            pressed,
+        }
+    }
+
+    // Raw sticks (up = +y) and held buttons of the `index`-th connected pad.
+    #[cfg(feature = "gamepad")]
+    fn read(&mut self, index: usize) -> Option<(Vec2, Vec2, Vec<PadButton>)> {
+        use gilrs::{Axis, Button};
+        let gilrs = self.gilrs.as_mut()?;
+        while gilrs.next_event().is_some() {} // updates the cached state
+        let (_, pad) = gilrs.gamepads().nth(index)?;
+        let left = vec2(pad.value(Axis::LeftStickX), pad.value(Axis::LeftStickY));
+        let right = vec2(pad.value(Axis::RightStickX), pad.value(Axis::RightStickY));
+        let trigger = |axis: Axis, button: Button| {
+            pad.is_pressed(button) || pad.button_data(button).is_some_and(|d| d.value() > TRIGGER_PRESSED) || pad.value(axis) > TRIGGER_PRESSED
+        };
+        // gilrs calls the bumpers LeftTrigger/RightTrigger and the triggers *Trigger2
+        let down = [
+            (PadButton::South, pad.is_pressed(Button::South)),
+            (PadButton::East, pad.is_pressed(Button::East)),
+            (PadButton::North, pad.is_pressed(Button::North)),
+            (PadButton::West, pad.is_pressed(Button::West)),
+            (PadButton::LeftBumper, pad.is_pressed(Button::LeftTrigger)),
+            (PadButton::RightBumper, pad.is_pressed(Button::RightTrigger)),
+            (PadButton::LeftTrigger, trigger(Axis::LeftZ, Button::LeftTrigger2)),
+            (PadButton::RightTrigger, trigger(Axis::RightZ, Button::RightTrigger2)),
+            (PadButton::Select, pad.is_pressed(Button::Select)),
+            (PadButton::Start, pad.is_pressed(Button::Start)),
+            (PadButton::LeftStick, pad.is_pressed(Button::LeftThumb)),
+            (PadButton::RightStick, pad.is_pressed(Button::RightThumb)),
+            (PadButton::DPadUp, pad.is_pressed(Button::DPadUp)),
+            (PadButton::DPadDown, pad.is_pressed(Button::DPadDown)),
+            (PadButton::DPadLeft, pad.is_pressed(Button::DPadLeft)),
+            (PadButton::DPadRight, pad.is_pressed(Button::DPadRight)),
+        ]
+        .into_iter()
+        .filter_map(|(button, down)| down.then_some(button))
+        .collect();
+        Some((left, right, down))
+    }
+
+    #[cfg(not(feature = "gamepad"))]
+    fn read(&mut self, _index: usize) -> Option<(Vec2, Vec2, Vec<PadButton>)> {
+        None
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn dead_zone_swallows_small_tilt() {
+        assert_eq!(shape_stick(vec2(0.1, 0.05), 0.15, 1.0), Vec2::ZERO);
+        // Just past the dead zone starts from zero, full tilt stays full
+        assert!(shape_stick(vec2(0.16, 0.0), 0.15, 1.0).x < 0.02);
+        assert!((shape_stick(vec2(0.0, -1.0), 0.15, 2.0).y + 1.0).abs() < 1e-6);
+    }
+
+    #[test]
+    fn curve_keeps_direction_and_softens_the_middle() {
+        let linear = shape_stick(vec2(0.6, 0.0), 0.0, 1.0);
+        let curved = shape_stick(vec2(0.6, 0.0), 0.0, 2.0);
+        assert!((linear.x - 0.6).abs() < 1e-6);
+        assert!((curved.x - 0.36).abs() < 1e-6);
+        let diag = shape_stick(vec2(0.5, 0.5), 0.1, 2.0);
+        assert!((diag.x - diag.y).abs() < 1e-6);
+    }
+
+    #[test]
+    fn look_speeds_up_only_at_full_tilt() {
+        let mut accel = LookAccel::default();
+        assert_eq!(accel.update(0.5, 0.1), 1.0);
+        for _ in 0..20 {
+            accel.update(1.0, 0.1);
+        }
+        assert!((accel.update(1.0, 0.1) - LOOK_ACCEL_BOOST).abs() < 1e-6);
+        assert_eq!(accel.update(0.2, 0.1), 1.0); // letting go resets it
+    }
+}
@@ -1,4 +1,6 @@
 use macroquad::prelude::*;
+use crate::gamepad::PadInput;
+use crate::settings::{Action, Settings};
 
 // ---------- Input ----------
 #[derive(Default)]
@@ -7,33 +9,76 @@ pub struct InputState {
     pub strafe: f32,
     pub rot: f32,
     pub shoot: bool,
+    pub reload: bool,
+    pub weapon_slot: Option<usize>, // number key pressed this frame (0-based)
+    pub weapon_cycle: i32,          // +1 next / -1 previous weapon this frame
+    pub toggle_kill_cam: bool,
+    pub scoreboard: bool, // held
+    pub render_scale_step: i32, // +1 sharper / -1 faster this frame
+    pub minimap_zoom_step: i32, // +1 closer / -1 further this frame
+    pub toggle_minimap_rotation: bool,
 }
 
-pub fn gather_input(mouse_captured: bool) -> InputState {
+/// Keyboard, mouse and the gamepad (if one is connected) folded into one state.
+pub fn gather_input(mouse_captured: bool, settings: &Settings, pad: &PadInput) -> InputState {
     let mut s = InputState::default();
+    let keys = &settings.bindings;
 
     // Movement keys (should work regardless of mouse capture)
-    if is_key_down(KeyCode::W) {
+    if keys.forward.is_down() {
         s.forward += 1.0;
     }
-    if is_key_down(KeyCode::S) {
+    if keys.back.is_down() {
         s.forward -= 1.0;
     }
-    if is_key_down(KeyCode::D) {
+    if keys.strafe_right.is_down() {
         s.strafe += 1.0;
     }
-    if is_key_down(KeyCode::A) {
+    if keys.strafe_left.is_down() {
         s.strafe -= 1.0;
     }
 
+    // Left stick adds to the keys; analog tilt gives analog speed
+    s.forward = (s.forward + pad.forward).clamp(-1.0, 1.0);
+    s.strafe = (s.strafe + pad.strafe).clamp(-1.0, 1.0);
+    let pad_down = |a: Action| settings.gamepad.bindings.get(a).is_some_and(|b| pad.is_down(b));
+    let pad_pressed = |a: Action| settings.gamepad.bindings.get(a).is_some_and(|b| pad.is_pressed(b));
+
     // Shooting
-    s.shoot = is_mouse_button_down(MouseButton::Left);
+    s.shoot = keys.shoot.is_down() || pad_down(Action::Shoot);
+    s.reload = keys.reload.is_pressed() || pad_pressed(Action::Reload);
+
+    // Weapon switching: number keys pick a slot, wheel or Q/E cycle
+    let slot_keys = [KeyCode::Key1, KeyCode::Key2, KeyCode::Key3, KeyCode::Key4];
+    s.weapon_slot = slot_keys.iter().position(|k| is_key_pressed(*k));
+    let (_, wheel_y) = mouse_wheel();
+    if wheel_y < 0.0 || keys.next_weapon.is_pressed() || pad_pressed(Action::NextWeapon) {
+        s.weapon_cycle = 1;
+    } else if wheel_y > 0.0 || keys.prev_weapon.is_pressed() || pad_pressed(Action::PrevWeapon) {
+        s.weapon_cycle = -1;
+    }
+
+    s.toggle_kill_cam = keys.kill_cam.is_pressed() || pad_pressed(Action::KillCam);
+    s.scoreboard = keys.scoreboard.is_down() || pad_down(Action::Scoreboard);
+    if is_key_pressed(KeyCode::RightBracket) {
+        s.render_scale_step = 1;
+    } else if is_key_pressed(KeyCode::LeftBracket) {
+        s.render_scale_step = -1;
+    }
+    if is_key_pressed(KeyCode::Equal) {
+        s.minimap_zoom_step = 1;
+    } else if is_key_pressed(KeyCode::Minus) {
+        s.minimap_zoom_step = -1;
+    }
+    s.toggle_minimap_rotation = is_key_pressed(KeyCode::M);
 
-    // Mouse rotation (only when captured)
+    // Mouse rotation (only when captured); the right stick turns either way
+    let invert = if settings.invert_look { -1.0 } else { 1.0 };
     if mouse_captured {
         let mouse_delta = mouse_delta_position();
-        s.rot = -mouse_delta.x * 0.3; // MOUSE_SENSITIVITY
+        s.rot = -mouse_delta.x * settings.sensitivity * invert;
     }
+    s.rot += pad.rot * invert;
 
     s
 }
@@ -1,5 +1,4 @@
 use macroquad::prelude::*;
-use protocol;
 
 // ---------- Server-provided Level definition ----------
 #[derive(Clone, Debug)]
@@ -61,9 +60,17 @@ pub fn level_from_maze_level(wire: &protocol::MazeLevel) -> Level {
     for y in 0..h {
         for x in 0..w {
             let c = &wire.cells[y * w + x];
-            // Consider any wall flag as a wall; otherwise floor
+            // Consider any wall flag as a wall; otherwise floor, marked by cell kind
             let is_wall = c.wall_north || c.wall_south || c.wall_east || c.wall_west;
-            tiles[y * w + x] = if is_wall { 1 } else { 0 };
+            tiles[y * w + x] = if is_wall {
+                1
+            } else {
+                match c.kind {
+                    protocol::CellKind::SpawnPoint => 2,
+                    protocol::CellKind::Cover => 3,
+                    _ => 0,
+                }
+            };
         }
     }
 
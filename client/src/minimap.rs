@@ -0,0 +1,186 @@
+use macroquad::prelude::*;
+use std::f32::consts::FRAC_PI_2;
+use crate::level::Level;
+use crate::player::{team_color, Player, RemotePlayer};
+use crate::raycast;
+use crate::rendering::{pickup_color, set_clip, Bullet, Flag, Pickup};
+
+// ---------- Config ----------
+const SIZE: f32 = 160.0;        // side of the minimap square (pixels)
+const MARGIN: f32 = 8.0;
+const REVEAL_RAYS: usize = 48;  // rays cast across the view each frame to uncover cells
+const REVEAL_STEPS: usize = 32; // cells each of them may cross
+// Zoom is in pixels per cell; defaults for the settings menu
+pub const DEFAULT_ZOOM: f32 = 4.0;
+pub const MIN_ZOOM: f32 = 2.0;
+pub const MAX_ZOOM: f32 = 16.0;
+pub const ZOOM_STEP: f32 = 1.0;
+
+// ---------- Minimap ----------
+/// The corner map: which cells we've seen on the current level, and how to show them.
+#[derive(Default)]
+pub struct Minimap {
+    explored: Vec<bool>,
+    level: (String, usize, usize), // name and size the exploration belongs to
+    rotate: bool,                  // player-up instead of north-up
+    zoom: f32,
+    fog: bool,                     // hide cells we haven't seen
+    radar: protocol::RadarMode,    // which enemies to show (server rule)
+}
+
+impl Minimap {
+    pub fn set_view(&mut self, rotate: bool, zoom: f32, fog: bool, radar: protocol::RadarMode) {
+        self.rotate = rotate;
+        self.zoom = zoom.clamp(MIN_ZOOM, MAX_ZOOM);
+        self.fog = fog;
+        self.radar = radar;
+    }
+
+    /// Uncovers what `player` can see: the cells rays across the field of view
+    /// cross and the walls they stop at, plus everything right next to us.
+    pub fn reveal(&mut self, level: &Level, player: &Player, fov_deg: f32) {
+        let key = (level.name.clone(), level.w, level.h);
+        if self.level != key {
+            self.level = key;
+            self.explored = vec![false; level.w * level.h];
+        }
+        let (w, h) = (level.w as i32, level.h as i32);
+        let explored = &mut self.explored;
+        let mut mark = |x: i32, y: i32| {
+            if x >= 0 && y >= 0 && x < w && y < h {
+                explored[(y * w + x) as usize] = true;
+            }
+        };
+
+        let (cx, cy) = (player.pos.x.floor() as i32, player.pos.y.floor() as i32);
+        for dy in -1..=1 {
+            for dx in -1..=1 {
+                mark(cx + dx, cy + dy);
+            }
+        }
+        let half_fov = fov_deg.to_radians() * 0.5;
+        for i in 0..REVEAL_RAYS {
+            let dir = raycast::ray_dir(player.dir, half_fov, raycast::camera_x(i, REVEAL_RAYS));
+            if let Some(hit) = raycast::cast_through(level, player.pos, dir, REVEAL_STEPS, &mut mark) {
+                mark(hit.cell.0, hit.cell.1);
+            }
+        }
+    }
+
+    fn seen(&self, level: &Level, p: Vec2) -> bool {
+        if !self.fog {
+            return true;
+        }
+        let (x, y) = (p.x.floor(), p.y.floor());
+        x >= 0.0 && y >= 0.0 && (x as usize) < level.w && (y as usize) < level.h
+            && self.explored.get(y as usize * level.w + x as usize).copied().unwrap_or(false)
+    }
+
+    // Whether the server lets us see this enemy (teammates always show).
+    fn on_radar(&self, level: &Level, player: &Player, other: &RemotePlayer) -> bool {
+        let enemy = player.team_id.is_none() || other.team_id != player.team_id;
+        if !enemy {
+            return true;
+        }
+        match self.radar {
+            protocol::RadarMode::All => true,
+            protocol::RadarMode::Visible => !raycast::occluded(level, player.pos, other.pos),
+            protocol::RadarMode::Off => false,
+        }
+    }
+
+    /// Draws the map centered on `player` in the top-left corner.
+    pub fn draw(&self, level: &Level, player: &Player, others: &[RemotePlayer], bullets: &[Bullet], flags: &[Flag], pickups: &[Pickup]) {
+        let zoom = self.zoom.clamp(MIN_ZOOM, MAX_ZOOM);
+        let center = vec2(MARGIN + SIZE * 0.5, MARGIN + SIZE * 0.5);
+        // Rotated, the way we face points up the screen
+        let rot = if self.rotate { Vec2::from_angle(-FRAC_PI_2 - player.dir) } else { Vec2::X };
+        let to_screen = |p: Vec2| center + rot.rotate(p - player.pos) * zoom;
+
+        draw_rectangle(MARGIN - 2.0, MARGIN - 2.0, SIZE + 4.0, SIZE + 4.0, Color::from_rgba(0, 0, 0, 160));
+        set_clip(Some(Rect::new(MARGIN, MARGIN, SIZE, SIZE)));
+
+        // Cells within reach of the corners: PATH = WHITE, WALL = DARKGREEN, SPAWN = BLUE, COVER = BROWN
+        let reach = SIZE * 0.5 * std::f32::consts::SQRT_2 / zoom + 1.0;
+        let x0 = (player.pos.x - reach).floor().max(0.0) as usize;
+        let y0 = (player.pos.y - reach).floor().max(0.0) as usize;
+        let x1 = ((player.pos.x + reach).ceil() as usize).min(level.w);
+        let y1 = ((player.pos.y + reach).ceil() as usize).min(level.h);
+        for y in y0..y1 {
+            for x in x0..x1 {
+                if self.fog && !self.explored.get(y * level.w + x).copied().unwrap_or(false) {
+                    continue;
+                }
+                let c = match level.tiles[y * level.w + x] {
+                    1 => DARKGREEN, // Wall
+                    2 => BLUE,      // Spawn point
+                    3 => BROWN,     // Cover
+                    _ => WHITE,     // Path
+                };
+                let [a, b, d, e] = cell_corners(x as f32, y as f32).map(to_screen);
+                draw_triangle(a, b, d, c);
+                draw_triangle(a, d, e, c);
+            }
+        }
+
+        // stocked item spots
+        for item in pickups.iter().filter(|i| i.available && self.seen(level, i.pos)) {
+            let p = to_screen(item.pos);
+            draw_rectangle(p.x - 1.5, p.y - 1.5, 3.0, 3.0, pickup_color(item.kind));
+        }
+
+        // flag bases (outlined) and flags (filled)
+        for flag in flags {
+            let color = team_color(flag.team_id);
+            if self.seen(level, flag.base) {
+                let corners = cell_corners(flag.base.x - 0.5, flag.base.y - 0.5).map(to_screen);
+                for i in 0..4 {
+                    let (a, b) = (corners[i], corners[(i + 1) % 4]);
+                    draw_line(a.x, a.y, b.x, b.y, 1.0, color);
+                }
+            }
+            if self.seen(level, flag.pos) {
+                let f = to_screen(flag.pos);
+                draw_triangle(vec2(f.x, f.y - 4.0), f, vec2(f.x + 4.0, f.y - 2.0), color);
+                draw_line(f.x, f.y - 4.0, f.x, f.y + 2.0, 1.0, WHITE);
+            }
+        }
+
+        // bullets we can see (or all of them, if enemies are on radar anyway)
+        for bullet in bullets {
+            let pos = vec2(bullet.x, bullet.y);
+            if self.radar == protocol::RadarMode::All || !raycast::occluded(level, player.pos, pos) {
+                let p = to_screen(pos);
+                draw_circle(p.x, p.y, 1.0, ORANGE);
+            }
+        }
+
+        // other players + facing arrows
+        for rp in others.iter().filter(|rp| self.on_radar(level, player, rp)) {
+            let p = to_screen(rp.pos);
+            let dot = rp.team_id.map(team_color).unwrap_or(RED); // Team color in team modes
+            draw_circle(p.x, p.y, 1.5, dot);
+            let tip = to_screen(rp.pos + Vec2::from_angle(rp.angle) * (4.0 / zoom));
+            draw_line(p.x, p.y, tip.x, tip.y, 1.0, ORANGE);
+        }
+
+        // player + facing line
+        let tip = to_screen(player.pos + Vec2::from_angle(player.dir) * (6.0 / zoom));
+        draw_circle(center.x, center.y, 1.5, YELLOW);
+        draw_line(center.x, center.y, tip.x, tip.y, 1.0, ORANGE);
+
+        set_clip(None);
+        draw_rectangle_lines(MARGIN - 2.0, MARGIN - 2.0, SIZE + 4.0, SIZE + 4.0, 1.0, GRAY);
+
+        // Rotated, mark which way north is
+        if self.rotate {
+            let n = center + rot.rotate(vec2(0.0, -1.0)) * (SIZE * 0.5 - 8.0);
+            draw_text("N", n.x - 4.0, n.y + 5.0, 16.0, YELLOW);
+        }
+    }
+}
+
+// Corners of the cell whose top-left corner is (x, y), going around it.
+fn cell_corners(x: f32, y: f32) -> [Vec2; 4] {
+    [vec2(x, y), vec2(x + 1.0, y), vec2(x + 1.0, y + 1.0), vec2(x, y + 1.0)]
+}
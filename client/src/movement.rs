@@ -61,8 +61,9 @@ pub fn move_player(level: &Level, player: &mut crate::player::Player, input: &In
     let wish = f * input.forward + r * input.strafe;
 
     if wish.length_squared() > 1e-6 {
-        // normalize + scale, clamp step to avoid tunneling on very low FPS
-        let mut step = wish.normalize() * MOVE_SPEED * dt;
+        // normalize + scale (a half-tilted stick walks at half speed), clamp step to avoid tunneling on very low FPS
+        let speed = if player.speed_boost_ms > 0 { MOVE_SPEED * protocol::SPEED_BOOST_FACTOR } else { MOVE_SPEED };
+        let mut step = wish.normalize() * wish.length().min(1.0) * speed * dt;
         let max_step = 0.35; // fraction of a cell per frame
         let len = step.length();
         if len > max_step {
@@ -8,22 +8,28 @@ use protocol::{self, ClientToServer, ServerToClient};
 pub struct NetClient {
     pub tx_outgoing: Sender<ClientToServer>,
     pub rx_incoming: Receiver<ServerToClient>,
+    pub rx_errors: Receiver<String>, // socket errors, e.g. "Connection refused" when nothing listens
+    pub last_heard: f64,             // when `receive` last got anything from the server
+    pub last_error: Option<String>,  // latest socket error since then
+    join: ClientToServer,
 }
 
 impl NetClient {
-    pub fn start(server_addr: String, username: String) -> std::io::Result<Self> {
+    pub fn start(server_addr: String, username: String, team: Option<u8>) -> std::io::Result<Self> {
         let socket = UdpSocket::bind("0.0.0.0:0")?;
         socket.connect(&server_addr)?;
         socket.set_nonblocking(true)?;
 
         let (tx_outgoing, rx_outgoing) = channel::<ClientToServer>();
         let (tx_incoming, rx_incoming) = channel::<ServerToClient>();
+        let (tx_errors, rx_errors) = channel::<String>();
+        let join = ClientToServer::Join(protocol::JoinRequest { username, team });
+        let first_join = join.clone();
 
         // Spawn network thread
         thread::spawn(move || {
             // Send initial Join
-            let join = ClientToServer::Join(protocol::JoinRequest { username });
-            if let Ok(bytes) = protocol::encode_client(&join) {
+            if let Ok(bytes) = protocol::encode_client(&first_join) {
                 let _ = socket.send(&bytes);
             }
 
@@ -55,14 +61,33 @@ impl NetClient {
                     Err(ref e) if e.kind() == std::io::ErrorKind::WouldBlock => {
                         thread::sleep(Duration::from_millis(5));
                     }
-                    Err(_) => {
+                    Err(e) => {
+                        let _ = tx_errors.send(e.to_string());
                         thread::sleep(Duration::from_millis(50));
                     }
                 }
             }
         });
 
-        Ok(Self { tx_outgoing, rx_incoming })
+        Ok(Self { tx_outgoing, rx_incoming, rx_errors, last_heard: 0.0, last_error: None, join })
+    }
+
+    /// Sends the Join again, for when the first one (or its Accept) got lost.
+    pub fn rejoin(&self) {
+        let _ = self.tx_outgoing.send(self.join.clone());
+    }
+
+    /// Everything the server sent since the last call; `now` becomes `last_heard` if that's anything.
+    pub fn receive(&mut self, now: f64) -> Vec<ServerToClient> {
+        while let Ok(e) = self.rx_errors.try_recv() {
+            self.last_error = Some(e);                        // Shown if the server never answers
+        }
+        let msgs: Vec<ServerToClient> = self.rx_incoming.try_iter().collect();
+        if !msgs.is_empty() {
+            self.last_heard = now;                            // The server is alive
+            self.last_error = None;
+        }
+        msgs
     }
 }
 
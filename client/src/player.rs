@@ -69,6 +69,21 @@ impl PlayerSkin {
     }
 }
 
+// ---------- Teams ----------
+pub fn team_color(team_id: u8) -> Color {
+    match team_id {
+        0 => Color::from_rgba(220, 40, 40, 255),   // Red team
+        _ => Color::from_rgba(40, 120, 255, 255),  // Blue team
+    }
+}
+
+pub fn team_name(team_id: u8) -> &'static str {
+    match team_id {
+        0 => "Red",
+        _ => "Blue",
+    }
+}
+
 // ---------- Player ----------
 #[derive(Clone, Copy)]
 pub struct Player {
@@ -78,6 +93,16 @@ pub struct Player {
     pub ammo: u8,
     pub kills: u32,
     pub deaths: u32,
+    pub spawn_protected: bool,
+    pub team_id: Option<u8>, // None outside team modes
+    pub carrying_flag: Option<u8>, // team id of the flag we're carrying (CTF)
+    pub weapon: protocol::Weapon,  // equipped weapon according to the server
+    pub reserve_ammo: u16,         // spare rounds for the equipped weapon
+    pub reloading: bool,
+    pub armor: u8,
+    pub speed_boost_ms: u32,       // power-up time left, 0 if none
+    pub damage_boost_ms: u32,
+    pub life: protocol::LifeState, // dead players wait for the server to respawn them
     pub skin: PlayerSkin,
 }
 
@@ -90,6 +115,16 @@ impl Player {
             ammo: 30,
             kills: 0,
             deaths: 0,
+            spawn_protected: false,
+            team_id: None,
+            carrying_flag: None,
+            weapon: protocol::Weapon::default(),
+            reserve_ammo: 0,
+            reloading: false,
+            armor: 0,
+            speed_boost_ms: 0,
+            damage_boost_ms: 0,
+            life: protocol::LifeState::Alive,
             skin: PlayerSkin::Soldier, // Default skin
         }
     }
@@ -98,6 +133,7 @@ impl Player {
 // ---------- Remote players ----------
 #[derive(Clone, Debug)]
 pub struct RemotePlayer {
+    pub player_id: u64,
     pub pos: Vec2,
     pub angle: f32,
     pub name: String,
@@ -105,131 +141,214 @@ pub struct RemotePlayer {
     // pub ammo: u8,
     // pub kills: u32,
     // pub deaths: u32,
+    pub team_id: Option<u8>,
     pub skin: PlayerSkin,
+    pub anim: PlayerAnim,
+}
+
+// ---------- Animation ----------
+const STRIDE: f32 = 0.6;       // cells covered by one full walk cycle
+const WALK_FRAMES: f32 = 4.0;
+const MOVE_HOLD_SECS: f64 = 0.15; // keep walking between snapshots
+const SHOOT_SECS: f64 = 0.15;
+const FLASH_SECS: f64 = 0.06;     // muzzle flash, the first part of the shoot pose
+const DYING_SECS: f64 = 0.45;
+const DYING_FRAMES: f64 = 3.0;
+
+/// Pose a remote player is drawn in.
+#[derive(Clone, Copy, Debug, PartialEq)]
+pub enum Frame {
+    Idle,
+    Walk(u8),  // step of the walk cycle, 0..4
+    Shoot(u8), // 0 with a muzzle flash, 1 recoiling
+    Dying(u8), // 0..3, falling over
+    Dead,      // lying on the floor
+}
+
+/// Per-player animation timers, advanced from snapshot deltas and events.
+#[derive(Clone, Copy, Debug)]
+pub struct PlayerAnim {
+    pub pos: Vec2,            // position in the last snapshot
+    walk_distance: f32,       // drives the walk cycle, so steps match speed
+    moving_until: f64,
+    shot_at: f64,
+    died_at: Option<f64>,
+}
+
+impl PlayerAnim {
+    pub fn new(pos: Vec2) -> Self {
+        Self { pos, walk_distance: 0.0, moving_until: f64::NEG_INFINITY, shot_at: f64::NEG_INFINITY, died_at: None }
+    }
+
+    /// Takes in a snapshot: movement since the last one walks, dying starts the fall.
+    pub fn update(&mut self, pos: Vec2, alive: bool, now: f64) {
+        let moved = pos.distance(self.pos);
+        // Respawns teleport; don't count them as a step
+        if alive && moved > 0.001 && moved < 2.0 {
+            self.walk_distance += moved;
+            self.moving_until = now + MOVE_HOLD_SECS;
+        }
+        self.pos = pos;
+        if alive {
+            self.died_at = None;
+        } else if self.died_at.is_none() {
+            self.died_at = Some(now);
+        }
+    }
+
+    /// Footfalls so far, two per walk cycle.
+    pub fn steps(&self) -> u32 {
+        (self.walk_distance / (STRIDE * 0.5)) as u32
+    }
+
+    pub fn shot(&mut self, now: f64) {
+        self.shot_at = now;
+    }
+
+    pub fn frame(&self, now: f64) -> Frame {
+        if let Some(died_at) = self.died_at {
+            let t = now - died_at;
+            return if t < DYING_SECS { Frame::Dying((t / DYING_SECS * DYING_FRAMES) as u8) } else { Frame::Dead };
+        }
+        if now - self.shot_at < SHOOT_SECS {
+            Frame::Shoot(if now - self.shot_at < FLASH_SECS { 0 } else { 1 })
+        } else if now < self.moving_until {
+            Frame::Walk(((self.walk_distance / STRIDE * WALK_FRAMES) as u32 % WALK_FRAMES as u32) as u8)
+        } else {
+            Frame::Idle
+        }
+    }
 }
 
 // ---------- Player Rendering with Skins ----------
-pub fn draw_player_with_skin(x: f32, y: f32, width: f32, height: f32, skin: PlayerSkin, angle: f32, screen_height: f32, depth: f32) {
+/// Draws a remote player standing at `x` (center) with the top of the sprite at `y`.
+/// `view` is the octant from `raycast::view_octant`: 0 faces the viewer, 4 faces away.
+#[allow(clippy::too_many_arguments)]
+pub fn draw_player_with_skin(x: f32, y: f32, width: f32, height: f32, skin: PlayerSkin, team_id: Option<u8>, view: u8, frame: Frame) {
+    // In team modes the uniform (body, arms, legs) shows the team color
+    let body_color = team_id.map(team_color).unwrap_or(skin.get_body_color());
+
+    // How much of the front we see, and which way the player faces on screen (+1 right)
+    let rel = view as f32 * std::f32::consts::FRAC_PI_4;
+    let toward = rel.cos();
+    let side = -rel.sin();
+
+    // Falling over: squash toward the feet, which stay put
+    let feet_y = y + height * 1.4;
+    let (y, height, width) = match frame {
+        Frame::Dead => {
+            draw_corpse(x, feet_y, width, height, skin, body_color, side);
+            return;
+        }
+        Frame::Dying(step) => {
+            let squash = 1.0 - 0.22 * (step as f32 + 1.0);
+            (feet_y - height * 1.4 * squash, height * squash, width * (1.0 + (1.0 - squash) * 0.6))
+        }
+        _ => (y, height, width),
+    };
+
+    // Seen from the side the body is narrower
+    let body_w = width * (0.5 + 0.3 * toward.abs());
+
+    // Walk cycle: legs lift in turn and arms swing the other way
+    let swing = match frame {
+        Frame::Walk(step) => [0.0, 1.0, 0.0, -1.0][step as usize % 4] * height * 0.06,
+        _ => 0.0,
+    };
+
+    // Legs
+    let leg_width = width * 0.2;
+    let leg_height = height * 0.5;
+    let leg_y = y + height * 0.9;
+    let leg_spread = width * 0.25 * (0.4 + 0.6 * toward.abs());
+    draw_rectangle(x - leg_spread - leg_width * 0.5, leg_y, leg_width, leg_height - swing.max(0.0), body_color);
+    draw_rectangle(x + leg_spread - leg_width * 0.5, leg_y, leg_width, leg_height + swing.min(0.0), body_color);
+
     // Body (torso)
     let body_y = y + height * 0.4;
-    let body_height = height * 0.6;
-    draw_rectangle(
-        x - width * 0.4,
-        body_y,
-        width * 0.8,
-        body_height,
-        skin.get_body_color(),
-    );
-    
-    // Armor vest (chest piece)
+    draw_rectangle(x - body_w * 0.5, body_y, body_w, height * 0.6, body_color);
+
+    // Armor vest on the chest, backpack on the back
     let vest_y = y + height * 0.45;
     let vest_height = height * 0.3;
-    draw_rectangle(
-        x - width * 0.3,
-        vest_y,
-        width * 0.6,
-        vest_height,
-        skin.get_armor_color(),
-    );
-    
-    // Head
+    if toward > -0.1 {
+        let vest_w = body_w * 0.75;
+        draw_rectangle(x - vest_w * 0.5 + side * body_w * 0.1, vest_y, vest_w, vest_height, skin.get_armor_color());
+    } else {
+        let pack_w = body_w * 0.6;
+        draw_rectangle(x - pack_w * 0.5 - side * body_w * 0.1, vest_y, pack_w, vest_height * 1.2, skin.get_helmet_color());
+    }
+
+    // Arms; raised to the gun while shooting
+    let arm_width = width * 0.15;
+    let arm_height = height * 0.4;
+    let shooting = matches!(frame, Frame::Shoot(_));
+    let arm_y = if shooting { y + height * 0.4 } else { y + height * 0.45 };
+    let arm_x = body_w * 0.5 + arm_width * 0.25;
+    if toward.abs() > 0.3 {
+        draw_rectangle(x - arm_x - arm_width * 0.5, arm_y + swing, arm_width, arm_height, body_color);
+        draw_rectangle(x + arm_x - arm_width * 0.5, arm_y - swing, arm_width, arm_height, body_color);
+    } else {
+        // Profile: the near arm swings over the torso
+        draw_rectangle(x - arm_width * 0.5 + swing * side, arm_y, arm_width, arm_height, body_color);
+    }
+
+    // Head, helmet and, when the face is toward us, eyes looking the way the player faces
     let head_y = y + height * 0.15;
     let head_r = width * 0.25;
     draw_circle(x, head_y, head_r, skin.get_head_color());
-    
-    // Helmet
-    let helmet_y = y + height * 0.1;
-    let helmet_r = width * 0.28;
-    draw_circle(x, helmet_y, helmet_r, skin.get_helmet_color());
-    
-    // Eyes (small white dots)
-    let eye_y = head_y - head_r * 0.3;
-    let eye_r = head_r * 0.15;
-    draw_circle(x - head_r * 0.3, eye_y, eye_r, WHITE);
-    draw_circle(x + head_r * 0.3, eye_y, eye_r, WHITE);
-    
-    // Arms
-    let arm_width = width * 0.15;
-    let arm_height = height * 0.4;
-    let arm_y = y + height * 0.45;
-    
-    // Left arm
-    draw_rectangle(
-        x - width * 0.6,
-        arm_y,
-        arm_width,
-        arm_height,
-        skin.get_body_color(),
-    );
-    
-    // Right arm
-    draw_rectangle(
-        x + width * 0.45,
-        arm_y,
-        arm_width,
-        arm_height,
-        skin.get_body_color(),
-    );
-    
-    // Legs
-    let leg_width = width * 0.2;
-    let leg_height = height * 0.5;
-    let leg_y = y + height * 0.9;
-    
-    // Left leg
-    draw_rectangle(
-        x - width * 0.35,
-        leg_y,
-        leg_width,
-        leg_height,
-        skin.get_body_color(),
-    );
-    
-    // Right leg
-    draw_rectangle(
-        x + width * 0.15,
-        leg_y,
-        leg_width,
-        leg_height,
-        skin.get_body_color(),
-    );
-    
-    // Weapon (gun)
-    let weapon_length = width * 0.8;
+    draw_circle(x - side * head_r * 0.15, y + height * 0.1, width * 0.28, skin.get_helmet_color());
+    if toward > 0.1 {
+        let eye_y = head_y - head_r * 0.3;
+        let eye_r = head_r * 0.15;
+        let look = side * head_r * 0.45;
+        let gap = head_r * 0.3 * toward;
+        draw_circle(x + look - gap, eye_y, eye_r, WHITE);
+        draw_circle(x + look + gap, eye_y, eye_r, WHITE);
+    }
+
+    // Weapon (gun), held toward where the player faces
+    let gun_color = Color::from_rgba(64, 64, 64, 255); // Dark gray
     let weapon_width = width * 0.08;
-    let weapon_y = y + height * 0.5;
-    let weapon_x = x + width * 0.5;
-    
-    // Gun barrel
-    draw_rectangle(
-        weapon_x,
-        weapon_y - weapon_width * 0.5,
-        weapon_length,
-        weapon_width,
-        Color::from_rgba(64, 64, 64, 255), // Dark gray
-    );
-    
-    // Gun handle
-    draw_rectangle(
-        weapon_x + weapon_length * 0.7,
-        weapon_y + weapon_width * 0.5,
-        weapon_width * 1.5,
-        weapon_width * 2.0,
-        Color::from_rgba(139, 69, 19, 255), // Brown
-    );
-    
-    // Facing direction indicator (small arrow)
-    let arrow_length = (screen_height / depth) * 0.1;
-    let ax = angle.cos();
-    let ay = angle.sin();
-    let arrow_x = x;
-    let arrow_y = y + height * 0.7;
-    draw_line(
-        arrow_x,
-        arrow_y,
-        arrow_x + ax as f32 * arrow_length,
-        arrow_y - ay as f32 * arrow_length,
-        2.0,
-        YELLOW,
-    );
+    let weapon_y = if shooting { y + height * 0.42 } else { y + height * 0.5 };
+    let weapon_length = width * 0.8 * side.abs();
+    let mut muzzle = x + side * body_w * 0.3;
+    if weapon_length > width * 0.1 {
+        let start = x + side.signum() * body_w * 0.3;
+        let end = start + side.signum() * weapon_length;
+        muzzle = end;
+        draw_rectangle(start.min(end), weapon_y - weapon_width * 0.5, (end - start).abs(), weapon_width, gun_color);
+        // Gun handle
+        draw_rectangle(
+            start + (end - start) * 0.2 - weapon_width * 0.75,
+            weapon_y + weapon_width * 0.5,
+            weapon_width * 1.5,
+            weapon_width * 2.0,
+            Color::from_rgba(139, 69, 19, 255), // Brown
+        );
+    }
+    if toward > 0.5 {
+        // Pointed at us: just the muzzle in front of the chest
+        draw_circle(muzzle, weapon_y, weapon_width * 1.5, gun_color);
+    }
+
+    // Muzzle flash; only a glow past the body when the shooter faces away
+    if frame == Frame::Shoot(0) {
+        let r = weapon_width * if toward < -0.5 { 2.0 } else { 3.5 };
+        draw_circle(muzzle, weapon_y, r, Color::new(1.0, 0.55, 0.1, 0.7));
+        draw_circle(muzzle, weapon_y, r * 0.5, Color::new(1.0, 0.95, 0.6, 1.0));
+    }
+}
+
+// Lying on the floor, head toward the side the player faced.
+fn draw_corpse(x: f32, feet_y: f32, width: f32, height: f32, skin: PlayerSkin, body_color: Color, side: f32) {
+    let len = height * 1.1;
+    let thick = width * 0.45;
+    let dir = if side < 0.0 { -1.0 } else { 1.0 };
+    let top = feet_y - thick;
+    draw_rectangle(x - len * 0.5, top, len, thick, body_color);
+    draw_rectangle(x - len * 0.15, top + thick * 0.15, len * 0.3, thick * 0.7, skin.get_armor_color());
+    let head_x = x + dir * (len * 0.5 + width * 0.2);
+    draw_circle(head_x, feet_y - thick * 0.5, width * 0.25, skin.get_head_color());
+    draw_circle(head_x + dir * width * 0.05, feet_y - thick * 0.55, width * 0.2, skin.get_helmet_color());
 }
@@ -0,0 +1,370 @@
+use macroquad::math::{vec2, Vec2};
+use crate::level::Level;
+
+// Pure raycasting math: no drawing and no window access, so it can be tested
+// headless. Angles are in radians, positions in cells.
+
+// ---------- Camera ----------
+/// Horizontal camera-plane coordinate of a screen column's center: -1 at the
+/// left edge, +1 at the right edge.
+pub fn camera_x(col: usize, cols: usize) -> f32 {
+    2.0 * (col as f32 + 0.5) / cols as f32 - 1.0
+}
+
+/// Direction of the ray through camera-plane coordinate `cam_x` for a viewer
+/// facing `facing` with half field of view `half_fov`.
+pub fn ray_dir(facing: f32, half_fov: f32, cam_x: f32) -> Vec2 {
+    let dir = vec2(facing.cos(), facing.sin());
+    let plane = vec2(-dir.y, dir.x) * half_fov.tan();
+    dir + plane * cam_x
+}
+
+// ---------- Wall hits ----------
+/// Which kind of grid line a ray crossed last.
+#[derive(Clone, Copy, Debug, PartialEq, Eq)]
+pub enum Side {
+    X, // a vertical grid line (east/west face)
+    Y, // a horizontal grid line (north/south face)
+}
+
+#[derive(Clone, Copy, Debug, PartialEq)]
+pub struct Hit {
+    pub dist: f32,        // perpendicular distance (no fisheye)
+    pub side: Side,
+    pub cell: (i32, i32), // the solid cell that was hit
+    pub tile: u8,
+    pub u: f32,           // 0..1 along the wall face, mirrored so every face reads left to right
+}
+
+// Tiles rays stop at. Spawn points and cover are floor markings, not walls.
+fn blocks_ray(tile: u8) -> bool {
+    tile == 1
+}
+
+/// Walks the grid (DDA) from `origin` along `dir` until it reaches a solid
+/// tile. Gives up after `max_steps` cells.
+pub fn cast(level: &Level, origin: Vec2, dir: Vec2, max_steps: usize) -> Option<Hit> {
+    cast_through(level, origin, dir, max_steps, |_, _| {})
+}
+
+/// `cast`, also calling `passed` with every open cell the ray crosses,
+/// starting with the one `origin` is in.
+pub fn cast_through(level: &Level, origin: Vec2, dir: Vec2, max_steps: usize, mut passed: impl FnMut(i32, i32)) -> Option<Hit> {
+    let mut map_x = origin.x.floor() as i32;
+    let mut map_y = origin.y.floor() as i32;
+    passed(map_x, map_y);
+
+    let delta = vec2(
+        if dir.x.abs() < 1e-6 { 1e30 } else { (1.0 / dir.x).abs() },
+        if dir.y.abs() < 1e-6 { 1e30 } else { (1.0 / dir.y).abs() },
+    );
+    let step_x = if dir.x < 0.0 { -1 } else { 1 };
+    let step_y = if dir.y < 0.0 { -1 } else { 1 };
+    let mut side_dist = vec2(
+        if dir.x < 0.0 { (origin.x - map_x as f32) * delta.x } else { (map_x as f32 + 1.0 - origin.x) * delta.x },
+        if dir.y < 0.0 { (origin.y - map_y as f32) * delta.y } else { (map_y as f32 + 1.0 - origin.y) * delta.y },
+    );
+
+    for _ in 0..max_steps {
+        let side = if side_dist.x < side_dist.y {
+            side_dist.x += delta.x;
+            map_x += step_x;
+            Side::X
+        } else {
+            side_dist.y += delta.y;
+            map_y += step_y;
+            Side::Y
+        };
+
+        let tile = level.at(map_x, map_y);
+        if !blocks_ray(tile) {
+            passed(map_x, map_y);
+            continue;
+        }
+
+        let dist = match side {
+            Side::X => (map_x as f32 - origin.x + (1 - step_x) as f32 / 2.0) / dir.x,
+            Side::Y => (map_y as f32 - origin.y + (1 - step_y) as f32 / 2.0) / dir.y,
+        }
+        .abs()
+        .max(0.0001);
+
+        // Where along the face we hit
+        let along = match side {
+            Side::X => origin.y + dist * dir.y,
+            Side::Y => origin.x + dist * dir.x,
+        };
+        let mut u = along - along.floor();
+        if (side == Side::X && dir.x > 0.0) || (side == Side::Y && dir.y < 0.0) {
+            u = 1.0 - u;
+        }
+        return Some(Hit { dist, side, cell: (map_x, map_y), tile, u });
+    }
+    None
+}
+
+/// Whether a wall stands between `from` and `to`.
+pub fn occluded(level: &Level, from: Vec2, to: Vec2) -> bool {
+    let d = to - from;
+    let len = d.length();
+    if len < 1e-4 {
+        return false;
+    }
+    // With a unit direction the hit distance is the distance along the segment
+    let steps = (d.x.abs() + d.y.abs()) as usize + 2;
+    cast(level, from, d / len, steps).is_some_and(|hit| hit.dist < len)
+}
+
+// ---------- Floor & ceiling ----------
+/// The strip of floor one framebuffer row below the horizon sees. The ceiling
+/// row mirrored above the horizon sees the same cells.
+#[derive(Clone, Copy, Debug, PartialEq)]
+pub struct FloorRow {
+    pub start: Vec2, // floor point under the row's first column
+    pub step: Vec2,  // world offset from one column to the next
+    pub dist: f32,   // distance along the view direction
+}
+
+/// Casts framebuffer row `row` (of `rows`, `cols` wide) onto the floor. None
+/// for rows at or above the horizon.
+pub fn floor_row(origin: Vec2, facing: f32, half_fov: f32, row: usize, rows: usize, cols: usize) -> Option<FloorRow> {
+    // Rows from the horizon, matching the wall projection (a wall at distance d is rows/d tall)
+    let p = row as f32 + 0.5 - rows as f32 * 0.5;
+    if p <= 0.0 {
+        return None;
+    }
+    let dist = rows as f32 * 0.5 / p;
+    let left = ray_dir(facing, half_fov, -1.0);
+    let right = ray_dir(facing, half_fov, 1.0);
+    let step = (right - left) * dist / cols as f32;
+    let start = origin + ray_dir(facing, half_fov, camera_x(0, cols)) * dist;
+    Some(FloorRow { start, step, dist })
+}
+
+// ---------- Sprites ----------
+/// Projects both ends of the segment `a`-`b`, cutting off any part behind the
+/// viewer. None when all of it is behind.
+pub fn project_segment(origin: Vec2, facing: f32, half_fov: f32, a: Vec2, b: Vec2) -> Option<(Projection, Projection)> {
+    let dir = vec2(facing.cos(), facing.sin());
+    let (da, db) = ((a - origin).dot(dir), (b - origin).dot(dir));
+    // Cut a little in front of the near plane so the cut end still projects
+    let cut = NEAR * 2.0;
+    if da < cut && db < cut {
+        return None;
+    }
+    let clip = |p: Vec2, dp: f32, q: Vec2, dq: f32| if dp >= cut { p } else { p + (q - p) * ((cut - dp) / (dq - dp)) };
+    let a2 = clip(a, da, b, db);
+    let b2 = clip(b, db, a, da);
+    Some((project(origin, facing, half_fov, a2)?, project(origin, facing, half_fov, b2)?))
+}
+
+/// Runs of columns in `first..last` where something at `depth` is nearer than
+/// the wall in `zbuffer`, as half-open `(start, end)` ranges.
+pub fn visible_spans(zbuffer: &[f32], depth: f32, first: usize, last: usize) -> Vec<(usize, usize)> {
+    let mut spans = Vec::new();
+    let last = last.min(zbuffer.len());
+    let mut start = None;
+    for (col, &wall) in zbuffer.iter().enumerate().take(last).skip(first) {
+        let visible = depth < wall;
+        match (visible, start) {
+            (true, None) => start = Some(col),
+            (false, Some(s)) => {
+                spans.push((s, col));
+                start = None;
+            }
+            _ => {}
+        }
+    }
+    if let Some(s) = start {
+        spans.push((s, last));
+    }
+    spans
+}
+
+/// Where a world point lands on screen.
+#[derive(Clone, Copy, Debug, PartialEq)]
+pub struct Projection {
+    pub x: f32,     // 0..1 across the screen (may fall outside for off-screen points)
+    pub depth: f32, // distance along the view direction
+}
+
+// Nearest depth anything is projected at.
+const NEAR: f32 = 0.05;
+
+/// Projects `point` for a viewer at `origin` facing `facing`. None when the
+/// point is behind (or practically on top of) the viewer.
+pub fn project(origin: Vec2, facing: f32, half_fov: f32, point: Vec2) -> Option<Projection> {
+    let dir = vec2(facing.cos(), facing.sin());
+    let right = vec2(-dir.y, dir.x);
+    let to = point - origin;
+    let depth = to.dot(dir);
+    if depth <= NEAR {
+        return None;
+    }
+    let lateral = to.dot(right);
+    Some(Projection { x: 0.5 + (lateral / depth) / (2.0 * half_fov.tan()), depth })
+}
+
+/// Which of 8 sprite directions a player at `pos` facing `facing` shows a
+/// viewer at `viewer`: 0 faces the viewer, counting in 45 degree steps
+/// (2 faces the viewer's left, 4 faces away, 6 faces the viewer's right).
+pub fn view_octant(viewer: Vec2, pos: Vec2, facing: f32) -> u8 {
+    let to_viewer = viewer - pos;
+    let rel = facing - to_viewer.y.atan2(to_viewer.x);
+    (rel / std::f32::consts::FRAC_PI_4).round().rem_euclid(8.0) as u8 % 8
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    // 5x5 room: walls all around, open 3x3 inside
+    fn room() -> Level {
+        let mut tiles = vec![1u8; 25];
+        for y in 1..4 {
+            for x in 1..4 {
+                tiles[y * 5 + x] = 0;
+            }
+        }
+        Level::new(5, 5, tiles, "room".to_string())
+    }
+
+    #[test]
+    fn camera_x_spans_the_screen() {
+        assert!((camera_x(0, 4) + 0.75).abs() < 1e-6);
+        assert!((camera_x(3, 4) - 0.75).abs() < 1e-6);
+        assert!(camera_x(2, 5).abs() < 1e-6);
+    }
+
+    #[test]
+    fn straight_ray_hits_facing_wall() {
+        let hit = cast(&room(), vec2(2.5, 2.5), vec2(1.0, 0.0), 64).unwrap();
+        assert!((hit.dist - 1.5).abs() < 1e-5);
+        assert_eq!(hit.side, Side::X);
+        assert_eq!(hit.cell, (4, 2));
+        assert!((hit.u - 0.5).abs() < 1e-5);
+
+        let hit = cast(&room(), vec2(2.5, 2.5), vec2(0.0, -1.0), 64).unwrap();
+        assert_eq!(hit.side, Side::Y);
+        assert_eq!(hit.cell, (2, 0));
+    }
+
+    #[test]
+    fn distance_is_perpendicular() {
+        // A slanted ray from the same camera plane reports the same depth to a flat wall
+        let dir = ray_dir(0.0, 35f32.to_radians(), 0.8);
+        let hit = cast(&room(), vec2(2.5, 2.5), dir, 64).unwrap();
+        assert!((hit.dist - 1.5).abs() < 1e-5);
+        assert!(hit.u >= 0.0 && hit.u < 1.0);
+    }
+
+    #[test]
+    fn opposite_faces_are_mirrored() {
+        // The same world point seen from either side maps to mirrored u
+        let mut tiles = vec![0u8; 25];
+        tiles[2 * 5 + 2] = 1; // single pillar
+        let level = Level::new(5, 5, tiles, "pillar".to_string());
+        let east = cast(&level, vec2(0.5, 2.3), vec2(1.0, 0.0), 64).unwrap();
+        let west = cast(&level, vec2(4.5, 2.3), vec2(-1.0, 0.0), 64).unwrap();
+        assert!((east.u + west.u - 1.0).abs() < 1e-5);
+    }
+
+    #[test]
+    fn ray_gives_up_without_walls() {
+        let level = Level::new(3, 3, vec![0u8; 9], "open".to_string());
+        // Out of bounds counts as wall, so a short step limit is what stops it
+        assert!(cast(&level, vec2(1.5, 1.5), vec2(1.0, 0.0), 1).is_none());
+        assert!(cast(&level, vec2(1.5, 1.5), vec2(1.0, 0.0), 8).is_some());
+    }
+
+    #[test]
+    fn walls_occlude_only_what_is_behind_them() {
+        let mut tiles = vec![0u8; 25];
+        tiles[2 * 5 + 2] = 1; // pillar in the middle
+        let level = Level::new(5, 5, tiles, "pillar".to_string());
+        assert!(occluded(&level, vec2(0.5, 2.5), vec2(4.5, 2.5)));
+        assert!(occluded(&level, vec2(1.5, 1.5), vec2(3.5, 3.5)));
+        assert!(!occluded(&level, vec2(0.5, 0.5), vec2(4.5, 0.5)));
+        assert!(!occluded(&level, vec2(0.5, 2.5), vec2(1.5, 2.5))); // stops short of it
+    }
+
+    #[test]
+    fn rays_report_the_cells_they_cross() {
+        let mut cells = Vec::new();
+        let hit = cast_through(&room(), vec2(1.5, 1.5), vec2(1.0, 0.0), 64, |x, y| cells.push((x, y)));
+        assert_eq!(hit.unwrap().cell, (4, 1));
+        assert_eq!(cells, vec![(1, 1), (2, 1), (3, 1)]);
+    }
+
+    #[test]
+    fn markings_do_not_block_rays() {
+        let mut tiles = vec![0u8; 25];
+        tiles[2 * 5 + 2] = 2; // spawn point
+        tiles[2 * 5 + 3] = 3; // cover
+        let level = Level::new(5, 5, tiles, "marks".to_string());
+        let hit = cast(&level, vec2(0.5, 2.5), vec2(1.0, 0.0), 64).unwrap();
+        assert_eq!(hit.cell, (5, 2)); // out of bounds past the markings
+    }
+
+    #[test]
+    fn floor_rows_meet_the_walls() {
+        let (rows, cols) = (100, 5);
+        assert!(floor_row(vec2(2.5, 2.5), 0.0, 35f32.to_radians(), 49, rows, cols).is_none());
+        // The bottom row is just under one cell away, where a wall at distance 1 ends
+        let bottom = floor_row(vec2(2.5, 2.5), 0.0, 35f32.to_radians(), rows - 1, rows, cols).unwrap();
+        assert!((bottom.dist - 50.0 / 49.5).abs() < 1e-5);
+        // The middle column looks straight ahead
+        let mid = bottom.start + bottom.step * 2.0;
+        assert!((mid - vec2(2.5 + bottom.dist, 2.5)).length() < 1e-5);
+        // Rows closer to the horizon are further away
+        let far = floor_row(vec2(2.5, 2.5), 0.0, 35f32.to_radians(), 60, rows, cols).unwrap();
+        assert!(far.dist > bottom.dist);
+    }
+
+    #[test]
+    fn spans_clip_against_walls() {
+        // A corner at depth 2 over columns 2..4, open elsewhere
+        let zbuffer = [9.0, 9.0, 2.0, 2.0, 9.0, 9.0];
+        assert_eq!(visible_spans(&zbuffer, 3.0, 0, 6), vec![(0, 2), (4, 6)]);
+        assert_eq!(visible_spans(&zbuffer, 1.0, 1, 5), vec![(1, 5)]);
+        assert_eq!(visible_spans(&zbuffer, 3.0, 2, 4), vec![]);
+        // Ranges past the buffer are cut off
+        assert_eq!(visible_spans(&zbuffer, 3.0, 5, 10), vec![(5, 6)]);
+    }
+
+    #[test]
+    fn segments_are_cut_at_the_viewer() {
+        let fov = 35f32.to_radians();
+        let (a, b) = project_segment(vec2(0.0, 0.0), 0.0, fov, vec2(2.0, 0.0), vec2(4.0, 0.0)).unwrap();
+        assert!((a.depth - 2.0).abs() < 1e-6 && (b.depth - 4.0).abs() < 1e-6);
+        // Passing the viewer: the end behind is pulled forward, the other end stays
+        let (a, b) = project_segment(vec2(0.0, 0.0), 0.0, fov, vec2(-1.0, 1.0), vec2(3.0, 1.0)).unwrap();
+        assert!(a.depth > 0.0 && a.depth < 0.2);
+        assert!((b.depth - 3.0).abs() < 1e-6);
+        assert!(project_segment(vec2(0.0, 0.0), 0.0, fov, vec2(-1.0, 0.0), vec2(-3.0, 0.0)).is_none());
+    }
+
+    #[test]
+    fn octants_follow_the_facing() {
+        use std::f32::consts::{FRAC_PI_2, PI};
+        // Viewer at the origin looking along +x at a player 3 cells ahead
+        let (eye, pos) = (vec2(0.0, 0.0), vec2(3.0, 0.0));
+        assert_eq!(view_octant(eye, pos, PI), 0); // looking back at us
+        assert_eq!(view_octant(eye, pos, 0.0), 4); // walking away
+        assert_eq!(view_octant(eye, pos, FRAC_PI_2), 6); // +y, the viewer's right
+        assert_eq!(view_octant(eye, pos, -FRAC_PI_2), 2);
+        assert_eq!(view_octant(eye, pos, PI - 0.2), 0); // snaps to the nearest octant
+        assert_eq!(view_octant(eye, pos, 3.0 * PI), 0); // any winding
+    }
+
+    #[test]
+    fn projection_centers_points_ahead() {
+        let p = project(vec2(0.0, 0.0), 0.0, 35f32.to_radians(), vec2(3.0, 0.0)).unwrap();
+        assert!((p.x - 0.5).abs() < 1e-6);
+        assert!((p.depth - 3.0).abs() < 1e-6);
+        // +y is to the right when facing +x
+        let right = project(vec2(0.0, 0.0), 0.0, 35f32.to_radians(), vec2(3.0, 1.0)).unwrap();
+        assert!(right.x > 0.5);
+        assert!(project(vec2(0.0, 0.0), 0.0, 35f32.to_radians(), vec2(-1.0, 0.0)).is_none());
+    }
+}
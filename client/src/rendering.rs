@@ -1,293 +1,694 @@
 use macroquad::prelude::*;
 use crate::level::Level;
-use crate::player::{Player, RemotePlayer, draw_player_with_skin};
+use crate::player::{Player, RemotePlayer, draw_player_with_skin, team_color, team_name};
+use crate::raycast;
+use crate::settings::{Binding, KeyBindings};
+use crate::textures::{Surface, TextureAtlas};
 
 // ---------- Config ----------
-const FOV_DEG: f32 = 70.0;
-const RENDER_SCALE: f32 = 1.0;
+// Defaults for the settings menu
+pub const DEFAULT_FOV_DEG: f32 = 70.0;
+// Internal resolution as a fraction of the window, adjustable at runtime
+pub const DEFAULT_RENDER_SCALE: f32 = 1.0;
+pub const MIN_RENDER_SCALE: f32 = 0.25;
+pub const RENDER_SCALE_STEP: f32 = 0.25;
+// Walls fade into the fog color between these distances (cells)
+const FOG_START: f32 = 3.0;
+const FOG_END: f32 = 18.0;
+const FOG_COLOR: Color = Color::new(0.09, 0.09, 0.14, 1.0);
+const FLOOR_LIGHT: f32 = 0.8;
+const CEILING_LIGHT: f32 = 0.6;
+// Bullet effects
+const TRACER_SECS: f32 = 0.03;   // tracer length as the distance covered in this long
+const MAX_TRACER_LEN: f32 = 1.5; // cells
+const SPARK_SECS: f64 = 0.3;
 
 // ---------- Bullet ----------
 #[derive(Clone, Copy)]
 pub struct Bullet {
+    pub id: u64,
     pub x: f32,
     pub y: f32,
-    // pub angle: f32,
-    // pub lifetime: f32,
+    pub angle: f32,
+    pub speed: f32, // cells per second
 }
 
-// ---------- Minimap ----------
-pub fn draw_minimap(level: &Level, player: &Player, others: &[RemotePlayer], bullets: &[Bullet]) {
-    let map_scale = 4.0;
-    let pad = 8.0;
-    let w = level.w as f32 * map_scale;
-    let h = level.h as f32 * map_scale;
+// Sparks where a bullet struck a wall.
+#[derive(Clone, Copy)]
+struct Impact {
+    pos: Vec2,
+    at: f64,
+}
 
-    draw_rectangle(
-        pad - 2.0,
-        pad - 2.0,
-        w + 4.0,
-        h + 4.0,
-        Color::from_rgba(0, 0, 0, 160),
-    );
+// ---------- Flag ----------
+/// A capture-the-flag flag; `pos` follows the carrier while carried.
+#[derive(Clone, Copy)]
+pub struct Flag {
+    pub pos: Vec2,
+    pub base: Vec2,
+    pub team_id: u8,
+    pub carrier: Option<u64>,
+}
 
-    // ✅ PATH (t==0) = WHITE, WALL (t==1) = DARKGREEN, SPAWN (t==2) = BLUE, COVER (t==3) = BROWN
-    for y in 0..level.h {
-        for x in 0..level.w {
-            let t = level.tiles[y * level.w + x];
-            let c = match t {
-                1 => DARKGREEN, // Wall
-                2 => BLUE,      // Spawn point
-                3 => BROWN,     // Cover
-                _ => WHITE,     // Path
-            };
-            draw_rectangle(
-                pad + x as f32 * map_scale,
-                pad + y as f32 * map_scale,
-                map_scale,
-                map_scale,
-                c,
-            );
+// ---------- Pickup ----------
+/// An item spot; unavailable spots are waiting to respawn.
+#[derive(Clone, Copy)]
+pub struct Pickup {
+    pub pos: Vec2,
+    pub kind: protocol::PickupKind,
+    pub available: bool,
+}
+
+pub fn pickup_color(kind: protocol::PickupKind) -> Color {
+    match kind {
+        protocol::PickupKind::Ammo => Color::from_rgba(230, 190, 40, 255),        // Brass
+        protocol::PickupKind::Health => Color::from_rgba(240, 240, 240, 255),     // White box, red cross
+        protocol::PickupKind::Armor => Color::from_rgba(70, 160, 255, 255),       // Blue
+        protocol::PickupKind::SpeedBoost => Color::from_rgba(60, 230, 120, 255),  // Green
+        protocol::PickupKind::DamageBoost => Color::from_rgba(200, 60, 255, 255), // Purple
+    }
+}
+
+// ---------- World renderer ----------
+/// Owns the CPU framebuffer the raycaster draws into, at `scale` times the
+/// window resolution, and the texture it is uploaded to once per frame.
+pub struct WorldRenderer {
+    atlas: TextureAtlas,
+    scale: f32,
+    fov_deg: f32,
+    frame: Image,
+    texture: Texture2D,
+    zbuffer: Vec<f32>, // wall distance per framebuffer column
+    impacts: Vec<Impact>,
+}
+
+impl WorldRenderer {
+    pub fn new(atlas: TextureAtlas) -> Self {
+        let frame = Image::gen_image_color(1, 1, BLACK);
+        let texture = Texture2D::from_image(&frame);
+        Self { atlas, scale: DEFAULT_RENDER_SCALE, fov_deg: DEFAULT_FOV_DEG, frame, texture, zbuffer: Vec::new(), impacts: Vec::new() }
+    }
+
+    /// Sparks on the wall ahead of a bullet that just disappeared, if it was
+    /// close enough to have hit it since the last snapshot. Returns where.
+    pub fn add_impact(&mut self, level: &Level, bullet: &Bullet, now: f64) -> Option<Vec2> {
+        let dir = vec2(bullet.angle.cos(), bullet.angle.sin());
+        let origin = vec2(bullet.x, bullet.y);
+        let hit = raycast::cast(level, origin, dir, 8)?;
+        // How far it could have flown between two snapshots, plus some slack
+        if hit.dist > bullet.speed / protocol::SNAPSHOT_HZ as f32 + 0.5 {
+            return None;
         }
+        // Just in front of the wall, so the wall doesn't hide it
+        let pos = origin + dir * (hit.dist - 0.05).max(0.0);
+        self.impacts.retain(|i| now - i.at < SPARK_SECS);
+        self.impacts.push(Impact { pos, at: now });
+        Some(pos)
     }
 
-    // player
-    let px = pad + player.pos.x * map_scale;
-    let py = pad + player.pos.y * map_scale;
-    draw_circle(px, py, 1.5, YELLOW); // Smaller player dot
+    pub fn scale(&self) -> f32 {
+        self.scale
+    }
 
-    // facing line
-    let p2 = vec2(px, py) + vec2(player.dir.cos(), player.dir.sin()) * 6.0; // Shorter facing line
-    draw_line(px, py, p2.x, p2.y, 1.0, ORANGE);
+    /// Raises or lowers the internal resolution by `steps` increments.
+    pub fn adjust_scale(&mut self, steps: i32) {
+        self.set_scale(self.scale + steps as f32 * RENDER_SCALE_STEP);
+    }
 
-    // other players + facing arrows
-    for rp in others.iter() {
-        let ox = pad + rp.pos.x * map_scale;
-        let oy = pad + rp.pos.y * map_scale;
-        draw_circle(ox, oy, 1.5, RED); // Smaller other player dots
-        // facing arrow
-        let ax = rp.angle.cos() as f32;
-        let ay = rp.angle.sin() as f32;
-        let tip = vec2(ox, oy) + vec2(ax, ay) * (4.0); // Shorter facing arrows
-        draw_line(ox, oy, tip.x, tip.y, 1.0, ORANGE);
+    pub fn set_scale(&mut self, scale: f32) {
+        self.scale = scale.clamp(MIN_RENDER_SCALE, 1.0);
     }
 
-    // bullets
-    for bullet in bullets {
-        let bx = pad + bullet.x * map_scale;
-        let by = pad + bullet.y * map_scale;
-        draw_circle(bx, by, 1.0, ORANGE);
+    /// Horizontal field of view in degrees.
+    pub fn set_fov(&mut self, fov_deg: f32) {
+        self.fov_deg = fov_deg;
     }
+
+    // Matches the framebuffer to the window size and scale; returns its size.
+    fn prepare(&mut self) -> (usize, usize) {
+        let w = ((screen_width() * self.scale) as usize).max(1);
+        let h = ((screen_height() * self.scale) as usize).max(1);
+        if self.frame.width as usize != w || self.frame.height as usize != h {
+            self.frame = Image::gen_image_color(w as u16, h as u16, BLACK);
+            self.texture = Texture2D::from_image(&self.frame);
+            self.texture.set_filter(FilterMode::Nearest); // chunky pixels when scaled down
+        }
+        self.zbuffer.clear();
+        self.zbuffer.resize(w, f32::INFINITY);
+        (w, h)
+    }
+
+    // Restricts drawing to framebuffer columns `c0..c1` of the window; None lifts it.
+    fn clip_columns(&self, cols: Option<(usize, usize)>) {
+        let col_w = screen_width() / self.zbuffer.len() as f32;
+        set_clip(cols.map(|(c0, c1)| Rect::new(c0 as f32 * col_w, 0.0, (c1 - c0) as f32 * col_w, screen_height())));
+    }
+}
+
+/// Restricts drawing to `rect` (in screen coordinates); None lifts it.
+/// Each change starts a new draw call. The clip outlives the frame, so every
+/// caller lifts it before returning: `draw_world` after its sprite loop and
+/// `Minimap::draw` at its end, with no early return in between.
+pub fn set_clip(rect: Option<Rect>) {
+    let dpi = screen_dpi_scale();
+    let clip = rect.map(|r| {
+        let (x, y) = ((r.x * dpi) as i32, (r.y * dpi) as i32);
+        (x, y, (r.right() * dpi).ceil() as i32 - x, (r.bottom() * dpi).ceil() as i32 - y)
+    });
+    // SAFETY: `get_internal_gl` hands out `&mut` borrows of macroquad's global
+    // context with an unchecked lifetime, so it's unsound to hold them across
+    // other macroquad calls. Here the borrow lives for this one statement on the
+    // main thread, and `scissor` only records the clip in the batcher's state
+    // (no raw GL), which macroquad then applies to the draw calls that follow.
+    unsafe { get_internal_gl() }.quad_gl.scissor(clip);
+}
+
+// Texel lit by `light` and blended toward the fog color by `fog` (0..1).
+fn shade_texel(texel: [u8; 4], light: f32, fog: f32) -> [u8; 4] {
+    let fog_rgb = [FOG_COLOR.r, FOG_COLOR.g, FOG_COLOR.b];
+    let mut out = [0, 0, 0, 255];
+    for i in 0..3 {
+        let lit = texel[i] as f32 / 255.0 * light;
+        out[i] = ((lit * (1.0 - fog) + fog_rgb[i] * fog) * 255.0) as u8;
+    }
+    out
 }
 
 // ---------- Raycasting (DDA) ----------
-pub fn draw_world(level: &Level, player: &Player, others: &[RemotePlayer], bullets: &[Bullet]) {
-    let (sw, sh) = (
-        screen_width() * RENDER_SCALE,
-        screen_height() * RENDER_SCALE,
-    );
-    let fov = FOV_DEG.to_radians();
-    let half_fov = fov * 0.5;
-    let num_cols = sw as i32;
+pub fn draw_world(renderer: &mut WorldRenderer, level: &Level, player: &Player, others: &[RemotePlayer], bullets: &[Bullet], flags: &[Flag], pickups: &[Pickup]) {
+    let half_fov = renderer.fov_deg.to_radians() * 0.5;
+    let (fw, fh) = renderer.prepare();
+
+    // Floor, ceiling and walls go into the framebuffer
+    {
+        let WorldRenderer { atlas, frame, zbuffer, .. } = renderer;
+        let pixels = frame.get_image_data_mut();
+
+        // Floor & ceiling: each row below the horizon and its mirror above see the same cells
+        for y in fh / 2..fh {
+            let Some(row) = raycast::floor_row(player.pos, player.dir, half_fov, y, fh, fw) else {
+                continue;
+            };
+            let fog = ((row.dist - FOG_START) / (FOG_END - FOG_START)).clamp(0.0, 1.0);
+            let ceiling_y = fh - 1 - y;
+            for col in 0..fw {
+                let p = row.start + row.step * col as f32;
+                let (u, v) = (p.x - p.x.floor(), p.y - p.y.floor());
+                let surface = Surface::for_floor(level.at(p.x.floor() as i32, p.y.floor() as i32));
+                pixels[y * fw + col] = shade_texel(atlas.sample(surface, u, v), FLOOR_LIGHT, fog);
+                pixels[ceiling_y * fw + col] = shade_texel(atlas.sample(Surface::Ceiling, u, v), CEILING_LIGHT, fog);
+            }
+        }
 
-    // Sky & floor
-    draw_rectangle(0.0, 0.0, sw, sh * 0.5, Color::from_rgba(30, 30, 50, 255));
-    draw_rectangle(
+        for (col, depth) in zbuffer.iter_mut().enumerate() {
+            let dir = raycast::ray_dir(player.dir, half_fov, raycast::camera_x(col, fw));
+            let Some(hit) = raycast::cast(level, player.pos, dir, 1024) else {
+                continue;
+            };
+            *depth = hit.dist;
+
+            // Full wall slice, then the rows of it that are on screen
+            let full_h = fh as f32 / hit.dist;
+            let top = fh as f32 * 0.5 - full_h * 0.5;
+            let y0 = top.max(0.0) as usize;
+            let y1 = ((top + full_h).ceil().max(0.0) as usize).min(fh);
+
+            // Y-side faces are darker; distance fog
+            let light = if hit.side == raycast::Side::X { 1.0 } else { 0.7 };
+            let fog = ((hit.dist - FOG_START) / (FOG_END - FOG_START)).clamp(0.0, 1.0);
+            for y in y0..y1 {
+                let v = (y as f32 + 0.5 - top) / full_h;
+                pixels[y * fw + col] = shade_texel(atlas.sample(Surface::Wall, hit.u, v), light, fog);
+            }
+        }
+    }
+
+    // One upload and one draw for the whole 3D view
+    renderer.texture.update(&renderer.frame);
+    let (sw, sh) = (screen_width(), screen_height());
+    draw_texture_ex(
+        &renderer.texture,
+        0.0,
         0.0,
-        sh * 0.5,
-        sw,
-        sh * 0.5,
-        Color::from_rgba(25, 35, 25, 255),
+        WHITE,
+        DrawTextureParams { dest_size: Some(vec2(sw, sh)), ..Default::default() },
     );
 
-    // Depth buffer per column for occlusion (z-buffer)
-    let mut zbuffer = vec![f32::INFINITY; num_cols as usize];
-
-    for col in 0..num_cols {
-        let colf = col as f32;
-        let cam_x = (2.0 * colf / sw - 1.0) * (half_fov).tan();
-        let ray_dir = vec2(player.dir.cos(), player.dir.sin())
-            + vec2(-player.dir.sin(), player.dir.cos()) * cam_x;
-
-        // DDA setup
-        let mut map_x = player.pos.x.floor() as i32;
-        let mut map_y = player.pos.y.floor() as i32;
-
-        let delta_dist = vec2(
-            if ray_dir.x.abs() < 1e-6 {
-                1e30
-            } else {
-                (1.0 / ray_dir.x).abs()
-            },
-            if ray_dir.y.abs() < 1e-6 {
-                1e30
-            } else {
-                (1.0 / ray_dir.y).abs()
-            },
-        );
+    // Billboards, far to near so nearer sprites paint over farther ones
+    let now = get_time();
+    let mut sprites: Vec<Billboard> = others
+        .iter()
+        .map(|rp| (Sprite::Player(rp), rp.pos))
+        .chain(flags.iter().map(|flag| (Sprite::Flag(flag), flag.pos))) // the flag we're carrying projects behind us
+        .chain(pickups.iter().filter(|i| i.available).map(|item| (Sprite::Pickup(item), item.pos)))
+        .filter_map(|(sprite, pos)| {
+            let proj = raycast::project(player.pos, player.dir, half_fov, pos)?;
+            Some(Billboard { sprite, screen_x: proj.x * sw, depth: proj.depth })
+        })
+        .collect();
+    // Tracers trail behind each bullet along its flight path, longer for faster rounds
+    sprites.extend(bullets.iter().filter_map(|bullet| {
+        let head = vec2(bullet.x, bullet.y);
+        let len = (bullet.speed * TRACER_SECS).min(MAX_TRACER_LEN);
+        let tail = head - vec2(bullet.angle.cos(), bullet.angle.sin()) * len;
+        let (head, tail) = raycast::project_segment(player.pos, player.dir, half_fov, head, tail)?;
+        let sprite = Sprite::Tracer { tail_x: tail.x * sw, tail_depth: tail.depth };
+        Some(Billboard { sprite, screen_x: head.x * sw, depth: head.depth })
+    }));
+    renderer.impacts.retain(|i| now - i.at < SPARK_SECS);
+    sprites.extend(renderer.impacts.iter().filter_map(|impact| {
+        let proj = raycast::project(player.pos, player.dir, half_fov, impact.pos)?;
+        Some(Billboard { sprite: Sprite::Spark((now - impact.at) as f32), screen_x: proj.x * sw, depth: proj.depth })
+    }));
+    sprites.sort_by(|a, b| b.depth.total_cmp(&a.depth));
+
+    // Each sprite is clipped to the framebuffer columns where it is in front of the wall
+    let col_w = sw / fw as f32;
+    for b in &sprites {
+        let half_w = b.half_width(sh);
+        let first = ((b.screen_x - half_w) / col_w).floor().max(0.0) as usize;
+        let last = (((b.screen_x + half_w) / col_w).ceil().max(0.0) as usize).min(fw);
+        for span in raycast::visible_spans(&renderer.zbuffer, b.depth, first, last) {
+            renderer.clip_columns(Some(span));
+            b.draw(sh, player.pos, now);
+        }
+    }
+    renderer.clip_columns(None);
+}
 
-        let step_x = if ray_dir.x < 0.0 { -1 } else { 1 };
-        let step_y = if ray_dir.y < 0.0 { -1 } else { 1 };
-
-        let mut side_dist = vec2(
-            if ray_dir.x < 0.0 {
-                (player.pos.x - map_x as f32) * delta_dist.x
-            } else {
-                ((map_x as f32 + 1.0) - player.pos.x) * delta_dist.x
-            },
-            if ray_dir.y < 0.0 {
-                (player.pos.y - map_y as f32) * delta_dist.y
-            } else {
-                ((map_y as f32 + 1.0) - player.pos.y) * delta_dist.y
-            },
-        );
+// ---------- Billboards ----------
+enum Sprite<'a> {
+    Player(&'a RemotePlayer),
+    Flag(&'a Flag),
+    Pickup(&'a Pickup),
+    Tracer { tail_x: f32, tail_depth: f32 }, // the billboard's position is the bullet
+    Spark(f32),                              // seconds since the impact
+}
 
-        // DDA loop
-        let mut side = 0; // 0: x hit, 1: y hit
-        let mut hit = false;
-        for _ in 0..1024 {
-            if side_dist.x < side_dist.y {
-                side_dist.x += delta_dist.x;
-                map_x += step_x;
-                side = 0;
-            } else {
-                side_dist.y += delta_dist.y;
-                map_y += step_y;
-                side = 1;
-            }
+struct Billboard<'a> {
+    sprite: Sprite<'a>,
+    screen_x: f32, // window pixels
+    depth: f32,
+}
 
-            let tile = level.at(map_x, map_y);
-            if tile == 1 || tile == 2 || tile == 3 {
-                // Wall, spawn point, or cover
-                hit = true;
-                break;
+impl Billboard<'_> {
+    // Half the on-screen width around `screen_x` the sprite may cover, name tags included.
+    fn half_width(&self, sh: f32) -> f32 {
+        let perp = self.depth;
+        match self.sprite {
+            Sprite::Player(rp) => {
+                let sprite_h = (sh / perp).clamp(12.0, sh * 0.8);
+                let name_w = measure_text(&rp.name, None, 14, 1.0).width;
+                (sprite_h * 1.5).max(name_w) * 0.5 + 2.0 // wide enough for a body lying down
             }
+            Sprite::Flag(_) => (sh / perp * 0.8).min(sh) * 0.35 + 2.0, // cloth hangs right of the pole
+            Sprite::Pickup(_) => (sh / perp * 0.25).min(sh * 0.5) * 0.5 + 2.0,
+            Sprite::Tracer { tail_x, .. } => (tail_x - self.screen_x).abs() + tracer_width(sh, perp),
+            Sprite::Spark(_) => spark_size(sh, perp) + 1.0,
         }
-        if !hit {
-            continue;
-        }
+    }
 
-        // Perp distance to avoid fisheye
-        let perp_dist = if side == 0 {
-            (map_x as f32 - player.pos.x + (1 - step_x) as f32 / 2.0) / ray_dir.x
-        } else {
-            (map_y as f32 - player.pos.y + (1 - step_y) as f32 / 2.0) / ray_dir.y
-        }
-        .abs()
-        .max(0.0001);
-
-        let line_h = (sh / perp_dist).min(sh);
-        let y0 = (sh * 0.5 - line_h * 0.5).max(0.0);
-        let y1 = (y0 + line_h).min(sh);
-
-        // Different colors for different tile types
-        let tile = level.at(map_x, map_y);
-        let base = match tile {
-            2 => { // Spawn point - blue
-                if side == 0 {
-                    Color::from_rgba(100, 100, 255, 255)
-                } else {
-                    Color::from_rgba(80, 80, 200, 255)
-                }
+    fn draw(&self, sh: f32, viewer: Vec2, now: f64) {
+        let screen_x = self.screen_x;
+        let perp = self.depth;
+        match self.sprite {
+            Sprite::Player(rp) => {
+                let sprite_h = (sh / perp).clamp(12.0, sh * 0.8);
+                let sprite_w = sprite_h * 0.35; // aspect ratio of a person
+                let y0 = sh * 0.5 - sprite_h * 0.5;
+                let view = raycast::view_octant(viewer, rp.pos, rp.angle);
+                draw_player_with_skin(screen_x, y0, sprite_w, sprite_h, rp.skin, rp.team_id, view, rp.anim.frame(now));
+
+                // name tag above
+                let name_y = (y0 - 12.0).max(0.0);
+                let tw = measure_text(&rp.name, None, 14, 1.0);
+                draw_text(
+                    &rp.name,
+                    (screen_x - tw.width * 0.5).max(0.0),
+                    name_y,
+                    14.0,
+                    rp.team_id.map(team_color).unwrap_or(WHITE),
+                );
+            }
+            // A pole with a team-colored cloth
+            Sprite::Flag(flag) => {
+                let pole_h = (sh / perp * 0.8).min(sh);
+                // Carried flags ride above the carrier's head
+                let lift = if flag.carrier.is_some() { (sh / perp) * 0.6 } else { 0.0 };
+                let floor_y = sh * 0.5 + (sh / perp) * 0.5 - lift;
+                let top_y = floor_y - pole_h;
+                let width = (pole_h * 0.04).max(1.0);
+                draw_line(screen_x, top_y, screen_x, floor_y, width, Color::from_rgba(200, 200, 200, 255));
+                let cloth_w = pole_h * 0.35;
+                let cloth_h = pole_h * 0.25;
+                draw_rectangle(screen_x, top_y, cloth_w, cloth_h, team_color(flag.team_id));
             }
-            3 => { // Cover - brown
-                if side == 0 {
-                    Color::from_rgba(139, 69, 19, 255)
-                } else {
-                    Color::from_rgba(101, 67, 33, 255)
+            // A small bobbing box sitting on the floor
+            Sprite::Pickup(item) => {
+                let size = (sh / perp * 0.25).min(sh * 0.5);
+                let bob = (now as f32 * 3.0 + item.pos.x).sin() * size * 0.1;
+                let floor_y = sh * 0.5 + (sh / perp) * 0.5;
+                let top_y = floor_y - size * 1.3 + bob;
+                let x0 = screen_x - size * 0.5;
+                draw_rectangle(x0, top_y, size, size, pickup_color(item.kind));
+                draw_rectangle_lines(x0, top_y, size, size, (size * 0.06).max(1.0), BLACK);
+                if item.kind == protocol::PickupKind::Health {
+                    let bar = size * 0.2;
+                    draw_rectangle(screen_x - bar * 0.5, top_y + size * 0.2, bar, size * 0.6, RED);
+                    draw_rectangle(x0 + size * 0.2, top_y + size * 0.5 - bar * 0.5, size * 0.6, bar, RED);
                 }
             }
-            _ => { // Wall - gray with side-based shading
-                if side == 0 {
-                    Color::from_rgba(190, 190, 200, 255)
-                } else {
-                    Color::from_rgba(120, 120, 140, 255)
+            // A streak at eye level, fading toward the tail, with a hot tip
+            Sprite::Tracer { tail_x, tail_depth } => {
+                let y = sh * 0.5;
+                let width = tracer_width(sh, perp);
+                let tail_width = tracer_width(sh, tail_depth);
+                draw_line(tail_x, y, screen_x, y, (width + tail_width) * 0.5, Color::new(1.0, 0.6, 0.1, 0.45));
+                let mid_x = (tail_x + screen_x) * 0.5;
+                draw_line(mid_x, y, screen_x, y, width * 0.6, Color::new(1.0, 0.9, 0.4, 0.9));
+                draw_circle(screen_x, y, width * 0.6, Color::from_rgba(255, 255, 200, 255));
+            }
+            // Bright bits flying out from the wall, fading as they spread
+            Sprite::Spark(age) => {
+                let t = (age / SPARK_SECS as f32).clamp(0.0, 1.0);
+                let size = spark_size(sh, perp);
+                let y = sh * 0.5;
+                let color = Color::new(1.0, 0.8 - 0.4 * t, 0.3 * (1.0 - t), 1.0 - t);
+                if t < 0.3 {
+                    draw_circle(screen_x, y, size * 0.35 * (1.0 - t), Color::new(1.0, 1.0, 0.8, 1.0 - t));
+                }
+                for i in 0..6 {
+                    let a = i as f32 * std::f32::consts::TAU / 6.0 + 0.4;
+                    let (dx, dy) = (a.cos(), a.sin());
+                    let r0 = size * 0.3 * t;
+                    let r1 = size * (0.3 + 0.7 * t);
+                    // Gravity pulls them down a little as they fly
+                    let drop = size * 0.5 * t * t;
+                    draw_line(screen_x + dx * r0, y + dy * r0 + drop, screen_x + dx * r1, y + dy * r1 + drop, (size * 0.08).max(1.0), color);
                 }
             }
+        }
+    }
+}
+
+fn tracer_width(sh: f32, depth: f32) -> f32 {
+    (sh / depth * 0.012).clamp(1.0, 6.0)
+}
+
+fn spark_size(sh: f32, depth: f32) -> f32 {
+    (sh / depth * 0.12).min(sh * 0.15)
+}
+
+// ---------- Screen Flash ----------
+pub fn draw_screen_flash(flash_timer: f32) {
+    if flash_timer > 0.0 {
+        let alpha = (flash_timer / 0.1).min(1.0); // Flash duration: 0.1 seconds
+        let flash_color = Color::from_rgba(255, 255, 255, (alpha * 100.0) as u8); // Semi-transparent white
+        draw_rectangle(0.0, 0.0, screen_width(), screen_height(), flash_color);
+    }
+}
+
+// ---------- Match Status ----------
+/// Top-center banner with mode, phase and timer; a winner banner once the match is over.
+/// `since_update` is how long ago `state` arrived, so the timer keeps counting between updates.
+pub fn draw_match_status(
+    state: &protocol::MatchStateUpdate,
+    since_update: f32,
+    result: Option<&protocol::MatchResult>,
+    eliminated: bool,
+) {
+    let mode = match state.mode {
+        protocol::GameMode::Deathmatch => "Deathmatch",
+        protocol::GameMode::TeamDeathmatch => "Team Deathmatch",
+        protocol::GameMode::LastManStanding => "Last Man Standing",
+        protocol::GameMode::CaptureTheFlag => "Capture the Flag",
+    };
+    let phase = match state.phase {
+        protocol::MatchPhase::Warmup => "WARMUP",
+        protocol::MatchPhase::Active => "LIVE",
+        protocol::MatchPhase::Ended => "MATCH OVER",
+    };
+    let mut line = format!("{mode} - {phase}");
+    if let Some(ms) = state.time_remaining_ms {
+        let secs = (ms as f32 / 1000.0 - since_update).max(0.0).ceil() as u32;
+        line.push_str(&format!("   {}:{:02}", secs / 60, secs % 60));
+    }
+    if let Some(limit) = state.capture_limit {
+        line.push_str(&format!("   Capture limit: {limit}"));
+    } else if let Some(limit) = state.frag_limit {
+        if state.mode != protocol::GameMode::LastManStanding {
+            line.push_str(&format!("   Frag limit: {limit}"));
+        }
+    }
+    let tw = measure_text(&line, None, 22, 1.0);
+    draw_text(&line, (screen_width() - tw.width) * 0.5, 30.0, 22.0, WHITE);
+
+    // Team scores, in team colors
+    if !state.team_scores.is_empty() {
+        let mut x = screen_width() * 0.5 - 80.0;
+        for (i, score) in state.team_scores.iter().enumerate() {
+            let team = i as u8;
+            draw_text(&format!("{} {score}", team_name(team).to_uppercase()), x, 55.0, 22.0, team_color(team));
+            x += 100.0;
+        }
+    }
+
+    if eliminated && state.phase == protocol::MatchPhase::Active {
+        let txt = "ELIMINATED - waiting for the round to end";
+        let tw = measure_text(txt, None, 28, 1.0);
+        draw_text(txt, (screen_width() - tw.width) * 0.5, screen_height() * 0.35, 28.0, RED);
+    }
+
+    if let Some(result) = result {
+        let winner = match &result.winner {
+            protocol::MatchWinner::Player { username, .. } => format!("{username} wins!"),
+            protocol::MatchWinner::Team { team_id } => format!("{} team wins!", team_name(*team_id)),
+            protocol::MatchWinner::Draw => "Draw!".to_string(),
         };
+        let tw = measure_text(&winner, None, 40, 1.0);
+        draw_text(&winner, (screen_width() - tw.width) * 0.5, screen_height() * 0.3, 40.0, GOLD);
+        let tw = measure_text(&result.reason, None, 20, 1.0);
+        draw_text(&result.reason, (screen_width() - tw.width) * 0.5, screen_height() * 0.3 + 30.0, 20.0, WHITE);
+    }
+}
 
-        draw_line(colf, y0, colf, y1, 1.0, base);
+// ---------- Scoreboard ----------
+/// Centered table of every player sorted by score, grouped by team in team
+/// modes. Our own row is highlighted.
+pub fn draw_scoreboard(players: &[protocol::PlayerState], my_id: Option<u64>, match_state: Option<&protocol::MatchStateUpdate>) {
+    let (sw, sh) = (screen_width(), screen_height());
+    let width = 560.0f32.min(sw - 40.0);
+    let x0 = (sw - width) * 0.5;
+    let row_h = 24.0;
+
+    // One group per team (sorted by team id), or a single group outside team modes
+    let mut teams: Vec<Option<u8>> = players.iter().map(|p| p.team_id).collect();
+    teams.sort();
+    teams.dedup();
+    let groups: Vec<(Option<u8>, Vec<&protocol::PlayerState>)> = teams
+        .into_iter()
+        .map(|team| {
+            let mut rows: Vec<&protocol::PlayerState> = players.iter().filter(|p| p.team_id == team).collect();
+            rows.sort_by(|a, b| b.score.cmp(&a.score).then(b.kills.cmp(&a.kills)).then(a.deaths.cmp(&b.deaths)));
+            (team, rows)
+        })
+        .collect();
+
+    let height = 60.0 + players.len() as f32 * row_h + groups.len() as f32 * (row_h + 16.0);
+    let y0 = ((sh - height) * 0.5).max(10.0);
+    draw_rectangle(x0, y0, width, height, Color::from_rgba(0, 0, 0, 200));
+    draw_rectangle_lines(x0, y0, width, height, 2.0, GRAY);
+
+    let title = "SCOREBOARD";
+    let tw = measure_text(title, None, 28, 1.0);
+    draw_text(title, (sw - tw.width) * 0.5, y0 + 30.0, 28.0, WHITE);
+
+    // Column positions
+    let cols = [x0 + 16.0, x0 + width - 300.0, x0 + width - 230.0, x0 + width - 170.0, x0 + width - 110.0, x0 + width - 50.0];
+    let mut y = y0 + 60.0;
+    for (team, rows) in groups {
+        // Group header doubles as the column header
+        let (label, color) = match team {
+            Some(t) => {
+                let score = match_state.and_then(|m| m.team_scores.get(t as usize)).copied();
+                let label = match score {
+                    Some(s) => format!("{} TEAM  {}", team_name(t).to_uppercase(), s),
+                    None => format!("{} TEAM", team_name(t).to_uppercase()),
+                };
+                (label, team_color(t))
+            }
+            None => ("PLAYER".to_string(), LIGHTGRAY),
+        };
+        draw_text(&label, cols[0], y, 18.0, color);
+        for (i, h) in ["Score", "K", "D", "K/D", "Ping"].iter().enumerate() {
+            draw_text(h, cols[i + 1], y, 18.0, LIGHTGRAY);
+        }
+        y += 8.0;
+        draw_line(x0 + 10.0, y, x0 + width - 10.0, y, 1.0, GRAY);
+        y += row_h;
 
-        // store depth
-        zbuffer[col as usize] = perp_dist;
+        for p in rows {
+            if Some(p.player_id) == my_id {
+                draw_rectangle(x0 + 6.0, y - row_h + 6.0, width - 12.0, row_h, Color::from_rgba(255, 255, 255, 40));
+            }
+            let dead = p.life != protocol::LifeState::Alive;
+            let name_color = if dead { GRAY } else { p.team_id.map(team_color).unwrap_or(WHITE) };
+            let kd = if p.deaths == 0 { p.kills as f32 } else { p.kills as f32 / p.deaths as f32 };
+            let ping = match p.ping_ms {
+                Some(ms) => ms.to_string(),
+                None if p.is_bot => "BOT".to_string(),
+                None => "-".to_string(),
+            };
+            draw_text(&p.username, cols[0], y, 20.0, name_color);
+            draw_text(&p.score.to_string(), cols[1], y, 20.0, WHITE);
+            draw_text(&p.kills.to_string(), cols[2], y, 20.0, WHITE);
+            draw_text(&p.deaths.to_string(), cols[3], y, 20.0, WHITE);
+            draw_text(&format!("{:.2}", kd), cols[4], y, 20.0, WHITE);
+            draw_text(&ping, cols[5], y, 20.0, WHITE);
+            y += row_h;
+        }
+        y += 8.0;
     }
+}
 
-    // Enhanced billboard rendering for other players with skins
-    for rp in others.iter() {
-        let to = rp.pos - player.pos;
-        let dir = vec2(player.dir.cos(), player.dir.sin());
-        let right = vec2(-dir.y, dir.x);
-        let depth = to.dot(dir);
-        if depth <= 0.05 {
+// ---------- Kill Feed ----------
+/// One line of the kill feed.
+#[derive(Clone)]
+pub struct KillFeedEntry {
+    pub killer: String,
+    pub killer_team: Option<u8>,
+    pub victim: String,
+    pub victim_team: Option<u8>,
+    pub weapon: protocol::Weapon,
+    pub involves_me: bool, // highlighted
+    pub at: f64,           // get_time() when it happened
+}
+
+/// Recent kills in the top-right corner, newest at the bottom, fading out after `ttl` seconds.
+pub fn draw_kill_feed(entries: &[KillFeedEntry], now: f64, ttl: f64) {
+    let size = 18.0;
+    let mut y = 30.0;
+    for e in entries {
+        let age = now - e.at;
+        if age >= ttl {
             continue;
         }
-        let lateral = to.dot(right);
-        let fov = FOV_DEG.to_radians();
-        let half = (fov * 0.5).tan();
-        let screen_x = (0.5 + (lateral / depth) / (2.0 * half)) * sw;
-        let perp = depth.max(0.0001);
-        let sprite_h = (sh / perp).clamp(12.0, sh * 0.8);
-        let sprite_w = sprite_h * 0.35; // aspect ratio of a person
-        let y0 = sh * 0.5 - sprite_h * 0.5;
-        let y1 = y0 + sprite_h;
-        let x0 = (screen_x - sprite_w * 0.5).max(0.0);
-        let x1 = (screen_x + sprite_w * 0.5).min(sw);
-        // occlusion test using center column under the sprite
-        let col = (screen_x.round() as i32).clamp(0, num_cols - 1) as usize;
-        let occluded = perp >= zbuffer[col] - 0.001;
-        if x1 > 0.0 && x0 < sw && !occluded {
-            // Draw player with skin
-            draw_player_with_skin(screen_x, y0, sprite_w, sprite_h, rp.skin, rp.angle, sh, perp);
-            
-            // name tag above
-            let name_y = (y0 - 12.0).max(0.0);
-            let tw = measure_text(&rp.name, None, 14, 1.0);
-            draw_text(
-                &rp.name,
-                (screen_x - tw.width * 0.5).max(0.0),
-                name_y,
-                14.0,
-                WHITE,
-            );
-        }
+        let alpha = (ttl - age).min(1.0) as f32; // fade during the last second
+        let name_color = |team: Option<u8>| {
+            let c = team.map(team_color).unwrap_or(WHITE);
+            Color::new(c.r, c.g, c.b, alpha)
+        };
+        let weapon = format!(" [{}] ", e.weapon);
+        let kw = measure_text(&e.killer, None, size as u16, 1.0).width;
+        let ww = measure_text(&weapon, None, size as u16, 1.0).width;
+        let vw = measure_text(&e.victim, None, size as u16, 1.0).width;
+        let x = screen_width() - 10.0 - (kw + ww + vw);
+        let bg = if e.involves_me { Color::new(0.6, 0.1, 0.1, 0.5 * alpha) } else { Color::new(0.0, 0.0, 0.0, 0.4 * alpha) };
+        draw_rectangle(x - 4.0, y - size + 2.0, kw + ww + vw + 8.0, size + 4.0, bg);
+        draw_text(&e.killer, x, y, size, name_color(e.killer_team));
+        draw_text(&weapon, x + kw, y, size, Color::new(0.8, 0.8, 0.8, alpha));
+        draw_text(&e.victim, x + kw + ww, y, size, name_color(e.victim_team));
+        y += size + 6.0;
+    }
+}
+
+// ---------- Hit Feedback ----------
+/// Where a hit on us came from: unit vector (world space) from us to the shooter.
+#[derive(Clone, Copy)]
+pub struct DamageIndicator {
+    pub dir: Vec2,
+    pub damage: u8,
+    pub at: f64,
+}
+
+/// X-shaped marker around the crosshair when our shot lands; red for the killing blow.
+pub fn draw_hit_marker(age: f32, kill: bool) {
+    let ttl = 0.25;
+    if age >= ttl {
+        return;
+    }
+    let alpha = 1.0 - age / ttl;
+    let color = if kill { Color::new(1.0, 0.2, 0.2, alpha) } else { Color::new(1.0, 1.0, 1.0, alpha) };
+    let (cx, cy) = (screen_width() * 0.5, screen_height() * 0.5);
+    let (inner, outer) = (6.0, 14.0);
+    for (sx, sy) in [(-1.0, -1.0), (1.0, -1.0), (-1.0, 1.0), (1.0, 1.0)] {
+        draw_line(cx + sx * inner, cy + sy * inner, cx + sx * outer, cy + sy * outer, 2.0, color);
     }
+}
 
-    // Render bullets as tiny dots
-    for bullet in bullets {
-        let to = vec2(bullet.x, bullet.y) - player.pos;
-        let dir = vec2(player.dir.cos(), player.dir.sin());
-        let right = vec2(-dir.y, dir.x);
-        let depth = to.dot(dir);
-        if depth <= 0.05 {
+/// Red wedges around the screen center pointing toward whoever hit us. Directions
+/// are kept in world space, so the wedges swing around as we turn.
+pub fn draw_damage_indicators(indicators: &[DamageIndicator], player: &Player, now: f64, ttl: f64) {
+    let (cx, cy) = (screen_width() * 0.5, screen_height() * 0.5);
+    let radius = screen_height().min(screen_width()) * 0.25;
+    let dir = vec2(player.dir.cos(), player.dir.sin());
+    let right = vec2(-dir.y, dir.x);
+    for ind in indicators {
+        let age = now - ind.at;
+        if age >= ttl {
             continue;
         }
-        let lateral = to.dot(right);
-        let fov = FOV_DEG.to_radians();
-        let half = (fov * 0.5).tan();
-        let screen_x = (0.5 + (lateral / depth) / (2.0 * half)) * sw;
-        let perp = depth.max(0.0001);
-        let sprite_size = (sh / perp).clamp(1.0, sh * 0.02); // Much smaller size - tiny dots
-        let y0 = sh * 0.5 - sprite_size * 0.5;
-        let x0 = screen_x - sprite_size * 0.5;
-        let col = (screen_x.round() as i32).clamp(0, num_cols - 1) as usize;
-        let occluded = perp >= zbuffer[col] - 0.001;
-        if x0 > 0.0 && x0 < sw && y0 > 0.0 && y0 < sh && !occluded {
-            draw_circle(
-                screen_x,
-                sh * 0.5,
-                sprite_size * 0.5,
-                Color::from_rgba(255, 255, 0, 255), // Bright yellow for better visibility
-            );
+        let alpha = (1.0 - age / ttl) as f32;
+        // Screen direction: forward is up, our right is right
+        let screen = vec2(ind.dir.dot(right), -ind.dir.dot(dir)).normalize_or_zero();
+        if screen == Vec2::ZERO {
+            continue;
         }
+        let side = vec2(-screen.y, screen.x);
+        let width = 14.0 + ind.damage as f32 * 0.3; // bigger hits, wider wedge
+        let tip = vec2(cx, cy) + screen * (radius + 22.0);
+        let base = vec2(cx, cy) + screen * radius;
+        draw_triangle(tip, base + side * width, base - side * width, Color::new(0.9, 0.05, 0.05, 0.8 * alpha));
     }
 }
 
-// ---------- Screen Flash ----------
-pub fn draw_screen_flash(flash_timer: f32) {
-    if flash_timer > 0.0 {
-        let alpha = (flash_timer / 0.1).min(1.0); // Flash duration: 0.1 seconds
-        let flash_color = Color::from_rgba(255, 255, 255, (alpha * 100.0) as u8); // Semi-transparent white
-        draw_rectangle(0.0, 0.0, screen_width(), screen_height(), flash_color);
+// ---------- Death Screen ----------
+/// Red-tinted overlay while dead: the killer's name and the respawn countdown
+/// (`respawn_in` is None when we're out until the round ends).
+pub fn draw_death_screen(killer: Option<&str>, respawn_in: Option<f32>, kill_cam: bool, kill_cam_enabled: bool, kill_cam_key: Binding) {
+    let (sw, sh) = (screen_width(), screen_height());
+    let tint = if kill_cam { 40 } else { 110 }; // lighter while showing the kill-cam
+    draw_rectangle(0.0, 0.0, sw, sh, Color::from_rgba(90, 0, 0, tint));
+
+    let mut y = sh * 0.45;
+    let title = if kill_cam { "KILL-CAM" } else { "YOU DIED" };
+    let tw = measure_text(title, None, 48, 1.0);
+    draw_text(title, (sw - tw.width) * 0.5, y, 48.0, RED);
+
+    if let Some(name) = killer {
+        y += 40.0;
+        let txt = format!("Killed by {name}");
+        let tw = measure_text(&txt, None, 26, 1.0);
+        draw_text(&txt, (sw - tw.width) * 0.5, y, 26.0, WHITE);
+    }
+
+    if let Some(secs) = respawn_in {
+        y += 32.0;
+        let txt = format!("Respawning in {:.1}", secs);
+        let tw = measure_text(&txt, None, 22, 1.0);
+        draw_text(&txt, (sw - tw.width) * 0.5, y, 22.0, YELLOW);
+    }
+
+    let hint = format!("{}: kill-cam {}", kill_cam_key.name(), if kill_cam_enabled { "off" } else { "on" });
+    let tw = measure_text(&hint, None, 16, 1.0);
+    draw_text(&hint, (sw - tw.width) * 0.5, sh - 140.0, 16.0, GRAY);
+}
+
+// ---------- Announcements ----------
+/// Short-lived centered message (flag pickups, captures, ...), fading out over `ttl` seconds.
+pub fn draw_announcement(text: &str, age: f32, ttl: f32) {
+    if age >= ttl {
+        return;
     }
+    let alpha = (1.0 - age / ttl).clamp(0.0, 1.0);
+    let tw = measure_text(text, None, 26, 1.0);
+    draw_text(
+        text,
+        (screen_width() - tw.width) * 0.5,
+        screen_height() * 0.15,
+        26.0,
+        Color::new(1.0, 1.0, 1.0, alpha),
+    );
 }
 
 // ---------- Crosshair ----------
@@ -321,6 +722,7 @@ pub fn draw_crosshair() {
 }
 
 // ---------- HUD ----------
+#[allow(clippy::too_many_arguments)]
 pub fn draw_hud(
     level: &Level,
     rtt_ms: Option<u64>,
@@ -330,15 +732,27 @@ pub fn draw_hud(
     player: &Player,
     // has_moved_locally: bool,
     map_change_mode: bool,
+    bindings: &KeyBindings,
 ) {
     let fps = macroquad::time::get_fps();
     let ping_txt = match rtt_ms {
         Some(v) => format!("{} ms", v),
         None => "--".to_string(),
     };
+    // Help line from the current bindings, so it stays right after rebinding
+    let keys = |bs: &[Binding]| {
+        let names: Vec<String> = bs.iter().map(|b| b.name()).collect();
+        if names.iter().all(|n| n.len() == 1) { names.concat() } else { names.join("/") }
+    };
+    let b = bindings;
     let txt = format!(
-        "Ping: {ping_txt}   Players: {player_count}\nUser: {username}   Map: {}\nWASD move, Mouse look, Left Click shoot, F1 change map",
-        level.name
+        "Ping: {ping_txt}   Players: {player_count}\nUser: {username}   Map: {}\n{} move, Mouse look, {} shoot, {} reload, 1-4/Wheel/{} weapon, {} scores, M/-/= minimap, F1 change map, F2 settings",
+        level.name,
+        keys(&[b.forward, b.strafe_left, b.back, b.strafe_right]),
+        b.shoot.name(),
+        b.reload.name(),
+        keys(&[b.prev_weapon, b.next_weapon]),
+        b.scoreboard.name(),
     );
     let fpstxt = format!("FPS: {fps}");
     draw_text(&txt, 10.0, screen_height() - 60.0, 20.0, WHITE);
@@ -348,22 +762,83 @@ pub fn draw_hud(
     let health_color = if player.health > 50 { GREEN } else if player.health > 25 { YELLOW } else { RED };
     let health_txt = format!("Health: {}", player.health);
     draw_text(&health_txt, 10.0, screen_height() - 80.0, 20.0, health_color);
+    if player.armor > 0 {
+        let armor_txt = format!("Armor: {}", player.armor);
+        draw_text(&armor_txt, 130.0, screen_height() - 80.0, 20.0, pickup_color(protocol::PickupKind::Armor));
+    }
+
+    // Active power-ups with seconds left, on the health row after armor
+    let mut boost_x = 250.0;
+    for (name, ms, kind) in [
+        ("SPEED", player.speed_boost_ms, protocol::PickupKind::SpeedBoost),
+        ("DAMAGE", player.damage_boost_ms, protocol::PickupKind::DamageBoost),
+    ] {
+        if ms > 0 {
+            let txt = format!("{} {}s", name, ms.div_ceil(1000));
+            draw_text(&txt, boost_x, screen_height() - 80.0, 20.0, pickup_color(kind));
+            boost_x += measure_text(&txt, None, 20, 1.0).width + 15.0;
+        }
+    }
     
-    let ammo_txt = format!("Ammo: {}", player.ammo);
-    draw_text(&ammo_txt, 10.0, screen_height() - 100.0, 20.0, WHITE);
+    let ammo_color = if player.ammo == 0 { RED } else { WHITE };
+    let ammo_txt = format!("{}  Ammo: {} / {}", player.weapon, player.ammo, player.reserve_ammo);
+    draw_text(&ammo_txt, 10.0, screen_height() - 100.0, 20.0, ammo_color);
+
+    // Reload state under the crosshair
+    let reload_txt = if player.reloading {
+        Some(("RELOADING...".to_string(), YELLOW))
+    } else if player.ammo == 0 && player.reserve_ammo > 0 {
+        Some((format!("Press {} to reload", bindings.reload.name()), WHITE))
+    } else if player.ammo == 0 {
+        Some(("OUT OF AMMO - find an ammo pickup".to_string(), RED))
+    } else {
+        None
+    };
+    if let Some((txt, color)) = reload_txt {
+        let tw = measure_text(&txt, None, 20, 1.0);
+        draw_text(&txt, (screen_width() - tw.width) * 0.5, screen_height() * 0.5 + 40.0, 20.0, color);
+    }
+
+    // Weapon slots, current one highlighted
+    let mut slot_x = screen_width() - 360.0;
+    for (i, weapon) in protocol::Weapon::ALL.iter().enumerate() {
+        let txt = format!("{} {}", i + 1, weapon);
+        let color = if *weapon == player.weapon { YELLOW } else { GRAY };
+        draw_text(&txt, slot_x, screen_height() - 20.0, 18.0, color);
+        slot_x += measure_text(&txt, None, 18, 1.0).width + 16.0;
+    }
     
     let stats_txt = format!("Kills: {} | Deaths: {}", player.kills, player.deaths);
     draw_text(&stats_txt, 10.0, screen_height() - 120.0, 16.0, WHITE);
 
+    if let Some(team) = player.team_id {
+        let team_txt = format!("Team: {}", team_name(team));
+        draw_text(&team_txt, 200.0, screen_height() - 120.0, 16.0, team_color(team));
+    }
+
+    // Flag carrier banner
+    if let Some(team) = player.carrying_flag {
+        let txt = format!("YOU HAVE THE {} FLAG - BRING IT HOME", team_name(team).to_uppercase());
+        let tw = measure_text(&txt, None, 24, 1.0);
+        draw_text(&txt, (screen_width() - tw.width) * 0.5, screen_height() * 0.2, 24.0, team_color(team));
+    }
+
+    // Spawn protection banner
+    if player.spawn_protected {
+        let txt = "SPAWN PROTECTED";
+        let tw = measure_text(txt, None, 24, 1.0);
+        draw_text(txt, (screen_width() - tw.width) * 0.5, screen_height() * 0.25, 24.0, SKYBLUE);
+    }
+
     // Debug: Show key states
-    let w_pressed = if is_key_down(KeyCode::W) { "W" } else { " " };
-    let a_pressed = if is_key_down(KeyCode::A) { "A" } else { " " };
-    let s_pressed = if is_key_down(KeyCode::S) { "S" } else { " " };
-    let d_pressed = if is_key_down(KeyCode::D) { "D" } else { " " };
-    let shoot_pressed = if is_mouse_button_down(MouseButton::Left) { "SHOOT" } else { "     " };
+    let shown = |b: Binding| {
+        let name = b.name();
+        if b.is_down() { name } else { " ".repeat(name.len()) }
+    };
+    let shoot_pressed = if bindings.shoot.is_down() { "SHOOT" } else { "     " };
     let debug_txt = format!(
         "Keys: [{}][{}][{}][{}] [{}]",
-        w_pressed, a_pressed, s_pressed, d_pressed, shoot_pressed
+        shown(bindings.forward), shown(bindings.strafe_left), shown(bindings.back), shown(bindings.strafe_right), shoot_pressed
     );
     draw_text(&debug_txt, 10.0, screen_height() - 140.0, 16.0, YELLOW);
 
@@ -0,0 +1,125 @@
+use crate::network::NetClient;
+
+// One module per scene, each with an `update` (input, network) and a `draw`
+pub mod connect;
+pub mod connecting;
+pub mod lobby;
+pub mod playing;
+pub mod disconnected;
+
+// ---------- Config ----------
+pub const CONNECT_TIMEOUT: f64 = 3.0; // seconds to wait for Accept before resending Join
+pub const CONNECT_ATTEMPTS: u32 = 3;  // Joins sent before giving up
+pub const SERVER_SILENCE: f64 = 5.0;  // seconds without any message before we call the server gone
+
+// ---------- Scenes ----------
+/// Which screen the client is on. Everything past `Connect` owns a `NetClient`,
+/// and any of them can drop to `Disconnected` with a reason to show the player.
+#[derive(Clone, Debug, PartialEq)]
+pub enum Scene {
+    Connect,                                 // Server address, name and team form
+    Connecting { attempt: u32, since: f64 }, // Join sent, waiting for Accept
+    Lobby,                                   // Accepted: level and skin selection
+    Playing,                                 // In the match
+    Disconnected { reason: String },         // Lost the server or it refused us
+}
+
+impl Scene {
+    pub fn connecting(now: f64) -> Self {
+        Scene::Connecting { attempt: 1, since: now }
+    }
+
+    /// The server accepted our Join: on to the lobby (a later Accept is a level change).
+    pub fn accepted(&mut self) {
+        if let Scene::Connecting { .. } = self {
+            *self = Scene::Lobby;
+        }
+    }
+
+    pub fn disconnect(&mut self, reason: impl Into<String>) {
+        *self = Scene::Disconnected { reason: reason.into() };
+    }
+
+    /// A `ServerToClient::Error`. Only fatal ones (join rejected, not in the game)
+    /// end the session; returns false if this one did.
+    pub fn server_error(&mut self, message: &str, fatal: bool) -> bool {
+        if fatal {
+            self.disconnect(format!("Server error: {}", message));
+        }
+        !fatal
+    }
+
+    /// Timeouts, once per frame while online. `last_heard` is when the last
+    /// server message arrived, `net_error` the last socket error, if any.
+    /// Returns true when the Join should be sent again.
+    pub fn tick(&mut self, now: f64, last_heard: f64, net_error: Option<&str>) -> bool {
+        let because = |what: String| match net_error {
+            Some(e) => format!("{} ({})", what, e),
+            None => what,
+        };
+        match *self {
+            Scene::Connecting { attempt, since } if now - since >= CONNECT_TIMEOUT => {
+                if attempt >= CONNECT_ATTEMPTS {
+                    self.disconnect(because(format!("No answer from the server after {} attempts", attempt)));
+                    false
+                } else {
+                    *self = Scene::Connecting { attempt: attempt + 1, since: now };
+                    true
+                }
+            }
+            Scene::Lobby | Scene::Playing if now - last_heard >= SERVER_SILENCE => {
+                self.disconnect(because("Server stopped responding".to_string()));
+                false
+            }
+            _ => false,
+        }
+    }
+}
+
+/// Opens the socket and sends the first Join; a bad address goes straight to `Disconnected`.
+pub fn connect(addr: &str, username: &str, team: Option<u8>, now: f64) -> (Scene, Option<NetClient>) {
+    match NetClient::start(addr.to_string(), username.to_string(), team) {
+        Ok(net) => (Scene::connecting(now), Some(net)),
+        Err(e) => (Scene::Disconnected { reason: format!("Can't connect to {}: {}", addr, e) }, None),
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn connecting_retries_then_gives_up() {
+        let mut scene = Scene::connecting(0.0);
+        assert!(!scene.tick(1.0, 0.0, None));
+        assert!(scene.tick(CONNECT_TIMEOUT, 0.0, None)); // second Join
+        assert_eq!(scene, Scene::Connecting { attempt: 2, since: CONNECT_TIMEOUT });
+        assert!(scene.tick(CONNECT_TIMEOUT * 2.0, 0.0, None)); // third
+        assert!(!scene.tick(CONNECT_TIMEOUT * 3.0, 0.0, Some("Connection refused")));
+        let Scene::Disconnected { reason } = scene else { panic!("still {:?}", scene) };
+        assert!(reason.contains("3 attempts") && reason.contains("Connection refused"));
+    }
+
+    #[test]
+    fn silent_server_disconnects_only_once_accepted() {
+        let mut scene = Scene::connecting(0.0);
+        scene.accepted();
+        assert_eq!(scene, Scene::Lobby);
+        assert!(!scene.tick(SERVER_SILENCE - 0.1, 0.0, None));
+        assert_eq!(scene, Scene::Lobby);
+        scene = Scene::Playing;
+        scene.accepted(); // level change, not a new join
+        assert_eq!(scene, Scene::Playing);
+        scene.tick(10.0, 4.0, None);
+        assert!(matches!(scene, Scene::Disconnected { .. }));
+    }
+
+    #[test]
+    fn only_fatal_server_errors_disconnect() {
+        let mut scene = Scene::Playing;
+        assert!(scene.server_error("bad request: expected value", false));
+        assert_eq!(scene, Scene::Playing);
+        assert!(!scene.server_error("not in the game", true));
+        assert_eq!(scene, Scene::Disconnected { reason: "Server error: not in the game".to_string() });
+    }
+}
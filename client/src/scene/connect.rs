@@ -0,0 +1,80 @@
+use macroquad::prelude::*;
+
+use crate::app::App;
+use crate::ui::draw_connection_screen;
+
+// ---------- Connect Form ----------
+pub struct ConnectForm {
+    pub server_addr: String,                                 // Address to join
+    pub username: String,                                    // Name shown to other players
+    pub input_focus: usize,                                  // 0=server address field, 1=username field
+    pub team_choice: Option<u8>,                             // Preferred team (None = let the server balance)
+}
+
+impl Default for ConnectForm {
+    fn default() -> Self {
+        Self {
+            server_addr: String::from("127.0.0.1:34254"),   // Default server address (localhost)
+            username: String::from("player"),                // Default username
+            input_focus: 0,
+            team_choice: None,
+        }
+    }
+}
+
+pub fn update(app: &mut App) {
+    let form = &mut app.form;
+
+    // Left/Right cycles the team preference: Auto -> Red -> Blue
+    if is_key_pressed(KeyCode::Right) {
+        form.team_choice = match form.team_choice { None => Some(0), Some(0) => Some(1), _ => None };
+    }
+    if is_key_pressed(KeyCode::Left) {
+        form.team_choice = match form.team_choice { None => Some(1), Some(1) => Some(0), _ => None };
+    }
+
+    // Handle text input for server address and username
+    while let Some(c) = get_char_pressed() {
+        if c == '\t' {
+            form.input_focus = 1 - form.input_focus;         // Tab key switches between input fields
+            continue;
+        }
+        if c.is_control() {                                  // Skip control characters
+            continue;
+        }
+        // Add character to appropriate input field
+        if form.input_focus == 0 {
+            form.server_addr.push(c);                        // Add to server address
+        } else {
+            form.username.push(c);                           // Add to username
+        }
+    }
+
+    // Handle backspace key
+    if is_key_pressed(KeyCode::Backspace) {
+        if form.input_focus == 0 {
+            form.server_addr.pop();                          // Remove last character from server address
+        } else {
+            form.username.pop();                             // Remove last character from username
+        }
+    }
+
+    // Handle Enter key to connect
+    if is_key_pressed(KeyCode::Enter) {
+        // Sanitize input by removing control characters
+        form.server_addr.retain(|ch| !ch.is_control());
+        form.username.retain(|ch| !ch.is_control());
+
+        // Validate input and attempt connection
+        if form.input_focus == 0 && form.username.trim().is_empty() {
+            form.input_focus = 1;                            // Switch to username field if empty
+        } else if !form.server_addr.trim().is_empty() && !form.username.trim().is_empty() {
+            app.connect();                                   // Start the network client and wait for the server's Accept
+        }
+    }
+}
+
+pub fn draw(app: &App) {
+    let form = &app.form;
+    draw_connection_screen(&form.server_addr, &form.username, form.input_focus, form.team_choice);
+}
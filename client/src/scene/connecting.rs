@@ -0,0 +1,21 @@
+use macroquad::prelude::*;
+
+use crate::app::App;
+use crate::scene::{self, Scene};
+use crate::ui::draw_connecting_screen;
+
+// ---------- Waiting for Accept (Join is resent on timeout) ----------
+pub fn update(app: &mut App) {
+    app.poll_server();
+    if matches!(app.scene, Scene::Connecting { .. }) && is_key_pressed(KeyCode::Escape) {
+        app.end_session();                                   // Give up; the network thread exits
+        app.scene = Scene::Connect;
+    }
+}
+
+pub fn draw(app: &App) {
+    if let Scene::Connecting { attempt, since } = app.scene {
+        let waited = (macroquad::time::get_time() - since) as f32;
+        draw_connecting_screen(app.form.server_addr.trim(), attempt, scene::CONNECT_ATTEMPTS, waited);
+    }
+}
@@ -0,0 +1,20 @@
+use macroquad::prelude::*;
+
+use crate::app::App;
+use crate::scene::Scene;
+use crate::ui::draw_disconnected_screen;
+
+// ---------- Connection Lost or Refused ----------
+pub fn update(app: &mut App) {
+    if is_key_pressed(KeyCode::Enter) || is_key_pressed(KeyCode::R) {
+        app.connect();                                       // Same address and name again
+    } else if is_key_pressed(KeyCode::Escape) {
+        app.scene = Scene::Connect;                          // Back to the form to change address or name
+    }
+}
+
+pub fn draw(app: &App) {
+    if let Scene::Disconnected { reason } = &app.scene {
+        draw_disconnected_screen(reason);
+    }
+}
@@ -0,0 +1,107 @@
+use macroquad::prelude::*;
+
+use crate::app::App;
+use crate::player::PlayerSkin;
+use crate::scene::Scene;
+use crate::ui::draw_level_selection;
+
+// ---------- Level & Skin Picker ----------
+/// The lobby's lists; F1 brings the same picker up over the game.
+pub struct LevelPicker {
+    pub levels: Vec<(u8, String, String, u8)>,               // ID, name, description and player count
+    pub selected_level: usize,                               // Currently selected level index
+    pub selected_skin: PlayerSkin,                           // Currently selected player skin
+    pub selection_mode: usize,                               // 0=level selection, 1=skin selection
+}
+
+impl Default for LevelPicker {
+    fn default() -> Self {
+        Self {
+            levels: vec![
+                (1, "The Arena".to_string(), "Close-quarters combat arena".to_string(), 8),
+                (2, "The Corridors".to_string(), "Tactical corridor combat".to_string(), 10),
+                (3, "The Zigzag".to_string(), "Compact zigzag maze with tight corridors".to_string(), 12),
+                (4, "The Labyrinth".to_string(), "Complex multi-layer maze".to_string(), 10),
+                (5, "The Brutal Death Maze".to_string(), "Brutal death maze - extremely complex and challenging".to_string(), 15),
+            ],
+            selected_level: 0,
+            selected_skin: PlayerSkin::Soldier,
+            selection_mode: 0,
+        }
+    }
+}
+
+impl LevelPicker {
+    /// Tab switches lists, Up/Down move through them. Returns true when Enter confirms.
+    pub fn update(&mut self) -> bool {
+        if is_key_pressed(KeyCode::Tab) {
+            self.selection_mode = 1 - self.selection_mode;   // Toggle between level and skin selection
+        }
+
+        if self.selection_mode == 0 {
+            // Level selection mode - use Up/Down arrows
+            if is_key_pressed(KeyCode::Up) {
+                self.selected_level = self.selected_level.saturating_sub(1);  // Move up in level list
+            }
+            if is_key_pressed(KeyCode::Down) {
+                self.selected_level = (self.selected_level + 1).min(self.levels.len() - 1);  // Move down in level list
+            }
+        } else {
+            // Skin selection mode - use Up/Down arrows
+            let skins = [
+                PlayerSkin::Soldier,
+                PlayerSkin::Sniper,
+                PlayerSkin::Heavy,
+                PlayerSkin::Scout,
+                PlayerSkin::Medic,
+                PlayerSkin::Engineer,
+            ];
+            let current_index = skins.iter().position(|&s| s == self.selected_skin).unwrap_or(0);
+            if is_key_pressed(KeyCode::Up) {
+                self.selected_skin = skins[current_index.saturating_sub(1)];  // Move up in skin list
+            }
+            if is_key_pressed(KeyCode::Down) {
+                self.selected_skin = skins[(current_index + 1).min(skins.len() - 1)];  // Move down in skin list
+            }
+        }
+
+        is_key_pressed(KeyCode::Enter)
+    }
+
+    pub fn draw(&mut self) {
+        draw_level_selection(&self.levels, &mut self.selected_level, &mut self.selected_skin, self.selection_mode);
+    }
+}
+
+/// Puts on the chosen skin and asks the server for the chosen level.
+pub fn send_selection(app: &mut App) {
+    let picker = &app.picker;
+    app.session.player.skin = picker.selected_skin;
+    if let Some(ref net) = app.net {
+        let (level_id, ref name, ..) = picker.levels[picker.selected_level];
+        println!("🎯 CLIENT: Selecting level {}: '{}' with skin {:?}", level_id, name, picker.selected_skin);
+
+        // Create and send level selection message to server
+        let level_selection = protocol::ClientToServer::SelectLevel(protocol::LevelSelection {
+            player_id: app.session.my_player_id.unwrap_or(0),
+            level_id: level_id as u32,
+        });
+        let _ = net.tx_outgoing.send(level_selection);
+    }
+}
+
+// ---------- Level Selection State ----------
+pub fn update(app: &mut App) {
+    app.poll_server();
+    if app.scene != Scene::Lobby {
+        return;                                              // The server dropped us
+    }
+    if app.picker.update() {
+        send_selection(app);
+        app.scene = Scene::Playing;                          // Move to gameplay state
+    }
+}
+
+pub fn draw(app: &mut App) {
+    app.picker.draw();
+}
@@ -0,0 +1,293 @@
+use macroquad::prelude::*;
+
+use crate::app::App;
+use crate::gamepad::PadInput;
+use crate::input::gather_input;
+use crate::minimap;
+use crate::movement::move_player;
+use crate::player::RemotePlayer;
+use crate::rendering::{draw_world, draw_hud, draw_crosshair, draw_screen_flash, draw_match_status, draw_death_screen, draw_kill_feed, draw_hit_marker, draw_damage_indicators, draw_scoreboard};
+use crate::scene::{Scene, lobby};
+use crate::session::{PingInfo, KILL_FEED_SECS, DAMAGE_INDICATOR_SECS};
+use crate::ui::draw_settings_menu;
+
+// How long the kill-cam follows the killer after we die (seconds)
+const KILL_CAM_SECS: f64 = 2.0;
+
+// ---------- Playing State ----------
+pub fn update(app: &mut App, pad: &PadInput, dt: f32) {
+    // --- Mouse Capture Logic ---
+    // Left Click to capture mouse for FPS controls, Escape to release
+    if !app.mouse_captured && !app.settings_open && is_mouse_button_pressed(MouseButton::Left) {
+        app.capture_mouse(true);
+    }
+    if app.mouse_captured && is_key_pressed(KeyCode::Escape) {
+        app.capture_mouse(false);
+    }
+
+    // --- Map Change Mode Toggle ---
+    // F1 toggles map change mode; the mouse is freed for the picker and captured again after
+    if is_key_pressed(KeyCode::F1) && !app.settings_open {
+        app.map_change_mode = !app.map_change_mode;
+        app.capture_mouse(!app.map_change_mode);
+    }
+
+    // --- Settings Menu Toggle ---
+    // F2 opens the settings menu; the mouse is freed while it's up
+    if is_key_pressed(KeyCode::F2) {
+        app.settings_open = !app.settings_open;
+        app.settings_menu.rebinding = false;
+        app.map_change_mode = false;
+        app.capture_mouse(!app.settings_open);
+    }
+
+    app.poll_server();
+    if app.scene != Scene::Playing {
+        return;                                              // The server dropped us
+    }
+
+    // Gather input from keyboard and mouse
+    let input = gather_input(app.mouse_captured, &app.settings, pad);
+    let in_menu = app.map_change_mode || app.settings_open;  // Menus take the keyboard
+    let s = &mut app.session;
+
+    if app.settings_open {
+        app.settings_menu.update(&mut app.settings, pad);
+    }
+
+    // Weapon switching: remember the choice and keep sending it with every input
+    if !in_menu {
+        let all = protocol::Weapon::ALL;
+        if let Some(slot) = input.weapon_slot {
+            s.requested_weapon = Some(all[slot]);
+        } else if input.weapon_cycle != 0 {
+            let current = s.requested_weapon.unwrap_or(s.player.weapon).index() as i32;
+            let next = (current + input.weapon_cycle).rem_euclid(all.len() as i32);
+            s.requested_weapon = Some(all[next as usize]);
+        }
+    }
+
+    s.show_scoreboard = input.scoreboard && !in_menu;
+
+    // Internal resolution: trade sharpness for frame rate
+    if input.render_scale_step != 0 && !in_menu {
+        app.renderer.adjust_scale(input.render_scale_step);
+        app.settings.render_scale = app.renderer.scale();
+        app.settings.save();
+        s.announce(format!("Render scale: {:.0}%", app.renderer.scale() * 100.0));
+    }
+
+    // Minimap: M rotates, -/= zoom
+    if (input.minimap_zoom_step != 0 || input.toggle_minimap_rotation) && !in_menu {
+        app.settings.minimap_zoom = (app.settings.minimap_zoom + input.minimap_zoom_step as f32 * minimap::ZOOM_STEP)
+            .clamp(minimap::MIN_ZOOM, minimap::MAX_ZOOM);
+        app.settings.minimap_rotate ^= input.toggle_minimap_rotation;
+        app.settings.save();
+    }
+
+    if input.toggle_kill_cam && !in_menu {
+        app.kill_cam_enabled = !app.kill_cam_enabled;
+    }
+
+    // Trigger screen flash when shooting
+    if input.shoot && !in_menu && s.player.life == protocol::LifeState::Alive {
+        s.screen_flash_timer = 0.1;                          // Start flash timer for 0.1 seconds
+    }
+
+    // Update flash timer (countdown)
+    s.screen_flash_timer = (s.screen_flash_timer - dt).max(0.0);
+
+    // Apply local movement first (only if we have a level and not in a menu)
+    if let Some(ref level) = s.level {
+        if !in_menu && s.player.life == protocol::LifeState::Alive {
+            let before = s.player.pos;
+            move_player(level, &mut s.player, &input, dt);   // Update player position based on input
+            app.audio.walked(s.player.pos.distance(before)); // Our own footsteps
+        }
+        app.minimap.reveal(level, &s.player, app.settings.fov_deg); // Uncover what we can see
+    }
+
+    // Track if we've moved locally for reconciliation
+    let is_moving = input.forward.abs() > 0.1 || input.strafe.abs() > 0.1;  // Check if player is moving
+    if is_moving {
+        s.has_moved_locally = true;                          // Mark that we've moved locally
+        s.last_movement_time = 0.0;                          // Reset movement timer
+    } else {
+        s.last_movement_time += dt;                          // Increment time since last movement
+    }
+
+    // --- Position Reconciliation Logic ---
+    // Only reconcile if we haven't moved recently and we're very far out of sync
+    if !is_moving && s.last_movement_time > 0.5 && !s.has_moved_locally {
+        let dist = (s.self_target_pos - s.player.pos).length();  // Distance to server position
+        if dist > 2.0 {
+            s.player.pos = s.self_target_pos;                // Snap to server position
+        }
+    }
+
+    // --- Map Change Mode Input Handling ---
+    // Same picker as the lobby; Enter sends the choice and returns to the game
+    if app.map_change_mode && app.picker.update() {
+        lobby::send_selection(app);
+        app.map_change_mode = false;
+        app.capture_mouse(true);
+    }
+
+    send_input(app, &input, dt);
+}
+
+// Sends this frame's input, and a ping every second to measure latency
+fn send_input(app: &mut App, input: &crate::input::InputState, dt: f32) {
+    let Some(ref net) = app.net else { return };
+    let s = &mut app.session;
+
+    // --- Send Input Update to Server ---
+    // Send input update every frame for smooth movement
+    let action = if app.settings_open {
+        protocol::Action::Move                               // Clicks in the menu aren't shots
+    } else if input.reload {
+        protocol::Action::Reload                             // Reload takes priority over firing
+    } else if input.shoot {
+        protocol::Action::Shoot
+    } else {
+        protocol::Action::Move
+    };
+    let input_msg = protocol::ClientToServer::Input(protocol::InputUpdate {
+        player_id: s.my_player_id.unwrap_or(0),              // Our player ID
+        x: s.player.pos.x,                                   // Current X position
+        y: s.player.pos.y,                                   // Current Y position
+        angle: s.player.dir,                                 // Current rotation angle
+        action,                                              // Current action (move, shoot or reload)
+        weapon: s.requested_weapon,                          // Weapon we want equipped
+    });
+    let _ = net.tx_outgoing.send(input_msg);                 // Send input to server
+
+    // --- Periodic Ping System ---
+    s.ping_timer += dt;
+    if s.ping_timer > 1.0 {
+        s.ping_timer = 0.0;                                  // Reset ping timer
+        let now = macroquad::time::get_time();
+        let nonce = (now * 1_000_000.0) as u64;              // Generate unique ping ID
+        let rtt_ms = s.ping_state.map(|p| p.rtt_ms);         // Report our latency for the scoreboard
+        s.ping_state = Some(PingInfo {
+            last_nonce: nonce,                               // Store ping ID
+            last_send: now,                                  // Store send timestamp
+            rtt_ms: rtt_ms.unwrap_or(0),                     // Keep previous RTT
+        });
+        let ping = protocol::Ping { nonce, rtt_ms: rtt_ms.map(|ms| ms as u32) };
+        let _ = net.tx_outgoing.send(protocol::ClientToServer::Ping(ping));
+    }
+}
+
+pub fn draw(app: &mut App, pad: &PadInput) {
+    let s = &app.session;
+    let now = macroquad::time::get_time();
+
+    if let Some(ref level) = s.level {
+        // Right after dying, look through the killer's eyes for a moment
+        let kill_cam = app.kill_cam_enabled && now - s.killed_at < KILL_CAM_SECS;
+        let killer = match s.player.life {
+            protocol::LifeState::Dead { killer_id: Some(id), .. } if kill_cam => {
+                s.others.iter().find(|o| o.player_id == id)
+            }
+            _ => None,
+        };
+        if let Some(killer) = killer {
+            let mut cam = s.player;
+            cam.pos = killer.pos;
+            cam.dir = killer.angle;
+            let rest: Vec<RemotePlayer> = s.others.iter().filter(|o| o.player_id != killer.player_id).cloned().collect();
+            draw_world(&mut app.renderer, level, &cam, &rest, &s.bullets, &s.flags, &s.pickups);
+        } else {
+            draw_world(&mut app.renderer, level, &s.player, &s.others, &s.bullets, &s.flags, &s.pickups);
+        }
+
+        // Draw game UI elements
+        app.minimap.draw(level, &s.player, &s.others, &s.bullets, &s.flags, &s.pickups);  // Draw minimap in corner
+        let count = s.others.len() + 1;                      // Total player count (including self)
+        draw_hud(
+            level,
+            s.ping_state.map(|p| p.rtt_ms),
+            app.form.username.trim(),
+            count,
+            app.mouse_captured,
+            &s.player,
+            app.map_change_mode,
+            &app.settings.bindings,
+        );
+
+        // Draw match mode, timer and winner banner
+        if let Some(ref state) = s.match_state {
+            let since_update = (now - s.match_state_time) as f32;
+            draw_match_status(state, since_update, s.match_result.as_ref(), s.player.health == 0);
+        }
+
+        // Kill feed and damage direction
+        draw_kill_feed(&s.kill_feed, now, KILL_FEED_SECS);
+        if s.player.life == protocol::LifeState::Alive {
+            draw_damage_indicators(&s.damage_indicators, &s.player, now, DAMAGE_INDICATOR_SECS);
+        }
+
+        // Death screen: who killed us and when we're back
+        if let protocol::LifeState::Dead { killer_id, respawn_in_ms } = s.player.life {
+            let killer_name = killer_id.map(|id| {
+                s.others.iter().find(|o| o.player_id == id)
+                    .map(|o| o.name.clone()).unwrap_or_else(|| format!("Player {}", id))
+            });
+            let since_update = (now - s.life_update_time) as f32;
+            let respawn_in = respawn_in_ms.map(|ms| (ms as f32 / 1000.0 - since_update).max(0.0));
+            draw_death_screen(killer_name.as_deref(), respawn_in, kill_cam, app.kill_cam_enabled, app.settings.bindings.kill_cam);
+        }
+
+        // Draw crosshair when mouse is captured (FPS mode)
+        if app.mouse_captured {
+            draw_crosshair();
+        }
+
+        // Scoreboard while Tab is held
+        if s.show_scoreboard {
+            draw_scoreboard(&s.roster, s.my_player_id, s.match_state.as_ref());
+        }
+
+        // Hit marker over the crosshair when our shots land
+        if let Some((at, kill)) = s.hit_marker {
+            draw_hit_marker((now - at) as f32, kill);
+        }
+
+        // Draw screen flash effect (on top of everything)
+        draw_screen_flash(s.screen_flash_timer);
+
+        // --- Map Change UI Overlay ---
+        if app.map_change_mode {
+            // Semi-transparent black overlay
+            draw_rectangle(0.0, 0.0, screen_width(), screen_height(), Color::from_rgba(0, 0, 0, 180));
+
+            // Draw the level selection UI on top
+            app.picker.draw();
+
+            // Add map change specific instructions
+            let map_change_hint = "F1 to exit map change mode | Enter to confirm selection";
+            let hint_tw = measure_text(map_change_hint, None, 16, 1.0);  // Measure text width
+            draw_text(map_change_hint, (screen_width() - hint_tw.width) * 0.5, screen_height() - 50.0, 16.0, YELLOW);
+        }
+
+        // --- Settings Menu Overlay ---
+        if app.settings_open {
+            draw_settings_menu(&app.settings, &app.settings_menu, pad);
+        }
+    }
+
+    // --- Mouse Capture Hint ---
+    // Show hint to recapture mouse when not captured
+    if !app.mouse_captured && !app.settings_open {
+        let hint = "Click to capture mouse (Esc to release)";
+        let tw = measure_text(hint, None, 24, 1.0);          // Measure text width for centering
+        draw_text(
+            hint,
+            (screen_width() - tw.width) * 0.5,               // Center horizontally
+            screen_height() * 0.5,                           // Center vertically
+            24.0,                                            // Font size
+            YELLOW,                                          // Text color
+        );
+    }
+}
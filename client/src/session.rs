@@ -0,0 +1,395 @@
+use macroquad::prelude::*;
+use std::collections::{HashMap, HashSet};
+
+use crate::audio::{Audio, Sfx};
+use crate::level::{Level, level_from_maze_level, find_safe_spawn};
+use crate::player::{self, Player, RemotePlayer, PlayerSkin, PlayerAnim};
+use crate::rendering::{Bullet, Flag, Pickup, WorldRenderer, KillFeedEntry, DamageIndicator};
+
+// How long kill feed lines and damage indicators stay up (seconds)
+pub const KILL_FEED_SECS: f64 = 6.0;
+pub const DAMAGE_INDICATOR_SECS: f64 = 1.0;
+const KILL_FEED_LINES: usize = 5;
+
+// ---------- Ping Information Structure ----------
+/// Stores information about ping measurements for latency monitoring
+#[derive(Clone, Copy)]
+pub struct PingInfo {
+    pub last_nonce: u64,                                     // Unique identifier for ping request
+    pub last_send: f64,                                      // Timestamp when ping was sent
+    pub rtt_ms: u64,                                         // Round-trip time in milliseconds
+}
+
+// ---------- Session ----------
+/// Everything we know about the game we're connected to. Built up from server
+/// messages in `handle`, and thrown away (`Session::new`) on disconnect.
+pub struct Session {
+    pub level: Option<Level>,                               // Current loaded level (None until server sends it)
+    pub player: Player,                                     // Local player
+    pub my_player_id: Option<u64>,                          // Player ID assigned by server
+    pub others: Vec<RemotePlayer>,                          // List of other players in the game
+    pub bullets: Vec<Bullet>,                               // List of active bullets in the world
+    pub flags: Vec<Flag>,                                   // Capture-the-flag flags (empty in other modes)
+    pub pickups: Vec<Pickup>,                               // Item spots on the map
+    pub hit_bullets: HashSet<u64>,                          // Bullets that hit a player, so they don't spark
+    pub roster: Vec<protocol::PlayerState>,                 // Everyone's stats from the latest snapshot
+    pub announcement: Option<(String, f64)>,                // Centered event message and when it arrived
+
+    // --- Combat Feedback ---
+    pub screen_flash_timer: f32,                            // Timer for screen flash effect when shooting
+    pub requested_weapon: Option<protocol::Weapon>,         // Weapon we asked the server for (None = keep current)
+    pub killed_at: f64,                                     // When we last died (kill-cam timing)
+    pub life_update_time: f64,                              // When player.life arrived (respawn countdown)
+    pub kill_feed: Vec<KillFeedEntry>,                      // Recent kills, oldest first
+    pub hit_marker: Option<(f64, bool)>,                    // When our last shot landed, and whether it killed
+    pub damage_indicators: Vec<DamageIndicator>,            // Recent hits on us
+    pub show_scoreboard: bool,                              // Tab held
+
+    // --- Match State ---
+    pub match_state: Option<protocol::MatchStateUpdate>,    // Latest mode/phase/timer from server
+    pub match_state_time: f64,                              // When match_state arrived (for local countdown)
+    pub match_result: Option<protocol::MatchResult>,        // Winner of the last match, shown until next warmup
+
+    // --- Latency ---
+    pub ping_state: Option<PingInfo>,                       // Ping/latency measurement state
+    pub ping_timer: f32,                                    // Timer for sending periodic pings
+
+    // --- Movement and Reconciliation ---
+    pub self_target_pos: Vec2,                              // Server's version of our position for reconciliation
+    pub last_movement_time: f32,                            // Time since last local movement
+    pub has_moved_locally: bool,                            // Whether we've moved locally recently
+}
+
+impl Session {
+    pub fn new() -> Self {
+        let player = Player::new(1.5, 1.5, 0.0);            // Starting at position (1.5, 1.5) with 0° rotation
+        Self {
+            level: None,
+            player,
+            my_player_id: None,
+            others: Vec::new(),
+            bullets: Vec::new(),
+            flags: Vec::new(),
+            pickups: Vec::new(),
+            hit_bullets: HashSet::new(),
+            roster: Vec::new(),
+            announcement: None,
+            screen_flash_timer: 0.0,
+            requested_weapon: None,
+            killed_at: 0.0,
+            life_update_time: 0.0,
+            kill_feed: Vec::new(),
+            hit_marker: None,
+            damage_indicators: Vec::new(),
+            show_scoreboard: false,
+            match_state: None,
+            match_state_time: 0.0,
+            match_result: None,
+            ping_state: None,
+            ping_timer: 0.0,
+            self_target_pos: player.pos,
+            last_movement_time: 0.0,
+            has_moved_locally: false,
+        }
+    }
+
+    /// Shows `text` in the middle of the screen for a few seconds.
+    pub fn announce(&mut self, text: String) {
+        self.announcement = Some((text, macroquad::time::get_time()));
+    }
+
+    /// Applies one server message (anything but `Error`, which the scene deals with).
+    pub fn handle(&mut self, msg: protocol::ServerToClient, username: &str, audio: &mut Audio, renderer: &mut WorldRenderer) {
+        match msg {
+            // --- Server Accept Message ---
+            protocol::ServerToClient::Accept(acc) => {
+                // Accept server level data and load the level
+                self.level = Some(level_from_maze_level(&acc.level));
+
+                println!("🎮 CLIENT: Level {} loaded: '{}' ({}x{})",
+                        acc.level.level_id, acc.level.name, acc.level.width, acc.level.height);
+
+                // Only set player ID if it's not a level change (player_id != 0)
+                if acc.player_id != 0 {
+                    self.my_player_id = Some(acc.player_id);  // Store our player ID
+                    // Assign skin based on player ID for consistency
+                    self.player.skin = PlayerSkin::from_id(acc.player_id);
+                    println!(
+                        "⚠️ CLIENT: Initial join - You are now player {} with skin {:?}!",
+                        acc.player_id, self.player.skin
+                    );
+                }
+
+                // If this is a level change (player_id == 0), reset player position and state
+                if acc.player_id == 0 {
+                    println!(
+                        "🎯 CLIENT: Level changed to {}! Resetting player position...",
+                        acc.level.level_id
+                    );
+
+                    if let Some(ref lvl) = self.level {
+                        let spawn_pos = find_safe_spawn(lvl);  // Find safe spawn point
+                        println!(
+                            "DEBUG: Spawn position for level {} is {:?}",
+                            acc.level.level_id, spawn_pos
+                        );
+
+                        self.player.pos = spawn_pos;                // Set new spawn position
+                        self.self_target_pos = self.player.pos;     // Update reconciliation target
+                    }
+                    // Reset movement and game state
+                    self.has_moved_locally = false;
+                    self.last_movement_time = 0.0;
+                    self.others.clear();                           // Clear other players
+                    self.bullets.clear();                          // Clear bullets
+                    self.flags.clear();                            // Flags move to the new level's bases
+                    self.pickups.clear();                          // So do item spots
+                } else {
+                    // Reset movement tracking when joining a new server
+                    self.has_moved_locally = false;
+                    self.last_movement_time = 0.0;
+                }
+            }
+
+            // --- Server Snapshot Message ---
+            protocol::ServerToClient::Snapshot(snap) => {
+                // Build other players list (excluding self if known), carrying animations over
+                let now = macroquad::time::get_time();
+                let mut anims: HashMap<u64, PlayerAnim> = self.others.drain(..).map(|o| (o.player_id, o.anim)).collect();
+                for p in snap.players.iter() {
+                    if Some(p.player_id) == self.my_player_id {
+                        let player = &mut self.player;
+                        // This is us - update server target position for reconciliation
+                        // Only update server target if we haven't moved locally recently
+                        if !self.has_moved_locally || self.last_movement_time > 1.0 {
+                            self.self_target_pos = vec2(p.x, p.y);  // Update reconciliation target
+                        }
+                        // Update player stats from server
+                        player.health = p.health;
+                        player.ammo = p.ammo;
+                        player.kills = p.kills;
+                        player.deaths = p.deaths;
+                        player.spawn_protected = p.spawn_protected;
+                        player.team_id = p.team_id;
+                        player.weapon = p.weapon;
+                        player.reserve_ammo = p.reserve_ammo;
+                        player.reloading = p.reloading;
+                        player.armor = p.armor;
+                        player.speed_boost_ms = p.speed_boost_ms;
+                        player.damage_boost_ms = p.damage_boost_ms;
+
+                        // Dying starts the death screen; respawning puts us where the server did
+                        let was_alive = player.life == protocol::LifeState::Alive;
+                        let is_alive = p.life == protocol::LifeState::Alive;
+                        if was_alive && !is_alive {
+                            self.killed_at = now;
+                        } else if !was_alive && is_alive {
+                            player.pos = vec2(p.x, p.y);
+                            player.dir = p.angle;
+                            self.self_target_pos = player.pos;
+                            self.has_moved_locally = false;
+                        }
+                        player.life = p.life;
+                        self.life_update_time = now;
+                        continue;                           // Skip adding to others list
+                    }
+                    // Add other players to the list
+                    let pos = vec2(p.x, p.y);
+                    let mut anim = anims.remove(&p.player_id).unwrap_or_else(|| PlayerAnim::new(pos));
+                    let steps = anim.steps();
+                    anim.update(pos, p.life == protocol::LifeState::Alive, now);
+                    if let Some(ref lvl) = self.level {
+                        if anim.steps() != steps {
+                            audio.play_at(Sfx::Footstep, lvl, &self.player, pos);
+                        }
+                    }
+                    self.others.push(RemotePlayer {
+                        player_id: p.player_id,
+                        pos,
+                        angle: p.angle,
+                        name: p.username.clone(),
+                        team_id: p.team_id,
+                        skin: PlayerSkin::from_id(p.player_id),
+                        anim,
+                    });
+                }
+                self.roster = snap.players.clone();                // Keep everyone's stats for the scoreboard
+
+                // Bullets we haven't seen before mean their shooter just fired
+                for b in snap.bullets.iter().filter(|b| !self.bullets.iter().any(|old| old.id == b.bullet_id)) {
+                    if let Some(shooter) = self.others.iter_mut().find(|o| o.player_id == b.shooter_id) {
+                        shooter.anim.shot(now);
+                    }
+                    if Some(b.shooter_id) == self.my_player_id {
+                        audio.play_local(Sfx::Gunshot);
+                    } else if let Some(ref lvl) = self.level {
+                        audio.play_at(Sfx::Gunshot, lvl, &self.player, vec2(b.x, b.y));
+                    }
+                }
+
+                // Bullets that vanished in front of a wall hit it
+                if let Some(ref lvl) = self.level {
+                    for old in self.bullets.iter().filter(|old| !snap.bullets.iter().any(|b| b.bullet_id == old.id)) {
+                        if self.hit_bullets.contains(&old.id) {
+                            continue;
+                        }
+                        if let Some(pos) = renderer.add_impact(lvl, old, now) {
+                            audio.play_at(Sfx::Impact, lvl, &self.player, pos);
+                        }
+                    }
+                }
+                self.hit_bullets.retain(|id| snap.bullets.iter().any(|b| b.bullet_id == *id));
+
+                // Update bullets from server snapshot
+                self.bullets.clear();                               // Clear previous bullets
+                for b in snap.bullets.iter() {
+                    self.bullets.push(Bullet {
+                        id: b.bullet_id,
+                        x: b.x,
+                        y: b.y,
+                        angle: b.angle,
+                        speed: b.speed,
+                    });
+                }
+
+                // Update item spots
+                self.pickups.clear();
+                for item in snap.pickups.iter() {
+                    self.pickups.push(Pickup {
+                        pos: vec2(item.x, item.y),
+                        kind: item.kind,
+                        available: item.available,
+                    });
+                }
+
+                // Update flags; note whether we're the one carrying an enemy flag
+                self.flags.clear();
+                self.player.carrying_flag = None;
+                for f in snap.flags.iter() {
+                    let carrier = match f.status {
+                        protocol::FlagStatus::Carried { player_id } => Some(player_id),
+                        _ => None,
+                    };
+                    if carrier.is_some() && carrier == self.my_player_id {
+                        self.player.carrying_flag = Some(f.team_id);
+                    }
+                    self.flags.push(Flag {
+                        pos: vec2(f.x, f.y),
+                        base: vec2(f.base_x, f.base_y),
+                        team_id: f.team_id,
+                        carrier,
+                    });
+                }
+            }
+
+            // --- Game Event Messages ---
+            protocol::ServerToClient::Hit(hit_event) => {
+                println!("💥 Hit! Damage: {}", hit_event.damage);  // Log hit event
+                self.hit_bullets.insert(hit_event.bullet_id);      // Hit a player, not a wall
+                let now = macroquad::time::get_time();
+                if Some(hit_event.shooter_id) == self.my_player_id {
+                    self.hit_marker = Some((now, false));          // Our shot landed
+                }
+                if Some(hit_event.victim_id) == self.my_player_id {
+                    audio.play_local(Sfx::Hit);
+                    // Point toward where the shooter is standing
+                    if let Some(shooter) = self.others.iter().find(|o| o.player_id == hit_event.shooter_id) {
+                        let to = shooter.pos - self.player.pos;
+                        if to.length_squared() > 1e-6 {
+                            self.damage_indicators.retain(|d| now - d.at < DAMAGE_INDICATOR_SECS);
+                            self.damage_indicators.push(DamageIndicator { dir: to.normalize(), damage: hit_event.damage, at: now });
+                        }
+                    }
+                }
+            }
+            protocol::ServerToClient::Death(death_event) => {
+                println!("💀 {} killed {} with {}",
+                        death_event.killer_id, death_event.victim_id, death_event.weapon);  // Log death event
+                let now = macroquad::time::get_time();
+                if Some(death_event.killer_id) == self.my_player_id {
+                    self.hit_marker = Some((now, true));           // Killing blow
+                }
+                if let Some(victim) = self.others.iter_mut().find(|o| o.player_id == death_event.victim_id) {
+                    victim.anim.update(victim.pos, false, now); // Start falling before the next snapshot
+                    if let Some(ref lvl) = self.level {
+                        audio.play_at(Sfx::Death, lvl, &self.player, victim.pos);
+                    }
+                } else if Some(death_event.victim_id) == self.my_player_id {
+                    audio.play_local(Sfx::Death);
+                }
+                // Names and teams of the players involved (we're not in `others`)
+                let who = |id: u64| -> (String, Option<u8>) {
+                    if Some(id) == self.my_player_id {
+                        (username.to_string(), self.player.team_id)
+                    } else {
+                        self.others.iter().find(|o| o.player_id == id)
+                            .map(|o| (o.name.clone(), o.team_id))
+                            .unwrap_or_else(|| (format!("Player {}", id), None))
+                    }
+                };
+                let (killer, killer_team) = who(death_event.killer_id);
+                let (victim, victim_team) = who(death_event.victim_id);
+                let involves_me = self.my_player_id.is_some_and(|me| me == death_event.killer_id || me == death_event.victim_id);
+                self.kill_feed.push(KillFeedEntry {
+                    killer,
+                    killer_team,
+                    victim,
+                    victim_team,
+                    weapon: death_event.weapon,
+                    involves_me,
+                    at: now,
+                });
+                self.kill_feed.retain(|e| now - e.at < KILL_FEED_SECS);
+                if self.kill_feed.len() > KILL_FEED_LINES {
+                    self.kill_feed.remove(0);              // Drop the oldest line
+                }
+            }
+
+            // --- Match Lifecycle Messages ---
+            protocol::ServerToClient::MatchState(update) => {
+                if update.phase != protocol::MatchPhase::Ended {
+                    self.match_result = None;                   // New round: hide the old winner banner
+                }
+                self.match_state = Some(update);
+                self.match_state_time = macroquad::time::get_time();
+            }
+            protocol::ServerToClient::MatchOver(result) => {
+                println!("🏆 Match over: {:?} ({})", result.winner, result.reason);
+                self.match_result = Some(result);
+            }
+
+            protocol::ServerToClient::Flag(event) => {
+                // Look up who did it among the players we know about
+                let who = match event.player_id {
+                    Some(id) if Some(id) == self.my_player_id => "You".to_string(),
+                    Some(id) => self.others.iter().find(|o| o.player_id == id)
+                        .map(|o| o.name.clone()).unwrap_or_else(|| format!("Player {}", id)),
+                    None => String::new(),
+                };
+                let flag = format!("{} flag", player::team_name(event.team_id));
+                let text = match event.kind {
+                    protocol::FlagEventKind::PickedUp => format!("{who} took the {flag}"),
+                    protocol::FlagEventKind::Dropped => format!("{who} dropped the {flag}"),
+                    protocol::FlagEventKind::Returned if event.player_id.is_none() => format!("The {flag} was returned"),
+                    protocol::FlagEventKind::Returned => format!("{who} returned the {flag}"),
+                    protocol::FlagEventKind::Captured => format!("{who} captured the {flag}!"),
+                };
+                println!("🚩 {}", text);
+                self.announce(text);
+            }
+
+            protocol::ServerToClient::Pong(p) => {
+                if let Some(pi) = &mut self.ping_state {
+                    if pi.last_nonce == p.nonce {              // Verify this is our ping response
+                        let now = macroquad::time::get_time();
+                        let rtt = ((now - pi.last_send) * 1000.0).round() as u64;  // Calculate round-trip time
+                        pi.rtt_ms = rtt;                       // Store RTT in milliseconds
+                    }
+                }
+            }
+
+            // Players who left drop out of the next snapshot; errors are the scene's
+            protocol::ServerToClient::PlayerLeft(_) | protocol::ServerToClient::Error { .. } => {}
+        }
+    }
+}
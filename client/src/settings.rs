@@ -0,0 +1,532 @@
+use macroquad::prelude::*;
+use serde::{Deserialize, Deserializer, Serialize};
+use std::collections::HashMap;
+use std::path::PathBuf;
+use crate::gamepad::{GamepadSettings, PadInput};
+use crate::minimap::{DEFAULT_ZOOM, MAX_ZOOM, MIN_ZOOM, ZOOM_STEP};
+use crate::rendering::{DEFAULT_FOV_DEG, DEFAULT_RENDER_SCALE, MIN_RENDER_SCALE, RENDER_SCALE_STEP};
+
+// ---------- Config ----------
+// Saved as <config dir>/maze-war/settings.json (e.g. ~/.config on Linux)
+const APP_DIR: &str = "maze-war";
+const FILE_NAME: &str = "settings.json";
+
+// Ranges and step sizes the menu moves through
+const FOV_RANGE: (f32, f32) = (50.0, 110.0);
+const FOV_STEP: f32 = 5.0;
+const SENSITIVITY_RANGE: (f32, f32) = (0.05, 2.0);
+const SENSITIVITY_STEP: f32 = 0.05;
+const VOLUME_STEP: f32 = 0.1;
+const DEAD_ZONE_RANGE: (f32, f32) = (0.0, 0.5);
+const DEAD_ZONE_STEP: f32 = 0.05;
+const CURVE_RANGE: (f32, f32) = (1.0, 3.0);
+const CURVE_STEP: f32 = 0.25;
+const LOOK_SPEED_RANGE: (f32, f32) = (1.0, 8.0);
+const LOOK_SPEED_STEP: f32 = 0.5;
+const MAX_PAD_INDEX: usize = 7;
+
+// ---------- Bindings ----------
+/// A key or mouse button an action is bound to. Saved by name, e.g. "W" or "Mouse Left".
+#[derive(Clone, Copy, Debug, PartialEq, Eq, Serialize, Deserialize)]
+#[serde(into = "String", try_from = "String")]
+pub enum Binding {
+    Key(KeyCode),
+    Mouse(MouseButton),
+}
+
+// Keys that can be bound; anything else is ignored when rebinding. Left out on
+// purpose: 1-4 (weapon slots), M, - and = (minimap), [ ] (render scale), F1/F2.
+const BINDABLE_KEYS: &[KeyCode] = &[
+    KeyCode::A, KeyCode::B, KeyCode::C, KeyCode::D, KeyCode::E, KeyCode::F, KeyCode::G, KeyCode::H, KeyCode::I,
+    KeyCode::J, KeyCode::K, KeyCode::L, KeyCode::N, KeyCode::O, KeyCode::P, KeyCode::Q, KeyCode::R,
+    KeyCode::S, KeyCode::T, KeyCode::U, KeyCode::V, KeyCode::W, KeyCode::X, KeyCode::Y, KeyCode::Z,
+    KeyCode::Key0, KeyCode::Key5, KeyCode::Key6, KeyCode::Key7, KeyCode::Key8, KeyCode::Key9,
+    KeyCode::Space, KeyCode::Tab, KeyCode::Enter, KeyCode::Backspace, KeyCode::CapsLock,
+    KeyCode::LeftShift, KeyCode::RightShift, KeyCode::LeftControl, KeyCode::RightControl, KeyCode::LeftAlt, KeyCode::RightAlt,
+    KeyCode::Up, KeyCode::Down, KeyCode::Left, KeyCode::Right,
+    KeyCode::Comma, KeyCode::Period, KeyCode::Slash, KeyCode::Semicolon, KeyCode::Apostrophe,
+    KeyCode::Backslash, KeyCode::GraveAccent,
+    KeyCode::F3, KeyCode::F4, KeyCode::F5, KeyCode::F6, KeyCode::F7, KeyCode::F8,
+    KeyCode::Kp0, KeyCode::Kp1, KeyCode::Kp2, KeyCode::Kp3, KeyCode::Kp4,
+    KeyCode::Kp5, KeyCode::Kp6, KeyCode::Kp7, KeyCode::Kp8, KeyCode::Kp9,
+];
+const BINDABLE_BUTTONS: &[MouseButton] = &[MouseButton::Left, MouseButton::Right, MouseButton::Middle];
+
+impl Binding {
+    pub fn is_down(self) -> bool {
+        match self {
+            Binding::Key(k) => is_key_down(k),
+            Binding::Mouse(b) => is_mouse_button_down(b),
+        }
+    }
+
+    pub fn is_pressed(self) -> bool {
+        match self {
+            Binding::Key(k) => is_key_pressed(k),
+            Binding::Mouse(b) => is_mouse_button_pressed(b),
+        }
+    }
+
+    pub fn name(self) -> String {
+        match self {
+            Binding::Key(k) => format!("{:?}", k),
+            Binding::Mouse(b) => format!("Mouse {:?}", b),
+        }
+    }
+
+    // Whatever bindable key or button went down this frame.
+    fn pressed_now() -> Option<Self> {
+        BINDABLE_KEYS.iter().find(|k| is_key_pressed(**k)).map(|k| Binding::Key(*k))
+            .or_else(|| BINDABLE_BUTTONS.iter().find(|b| is_mouse_button_pressed(**b)).map(|b| Binding::Mouse(*b)))
+    }
+}
+
+impl From<Binding> for String {
+    fn from(b: Binding) -> Self {
+        b.name()
+    }
+}
+
+impl TryFrom<String> for Binding {
+    type Error = String;
+
+    fn try_from(name: String) -> Result<Self, Self::Error> {
+        BINDABLE_KEYS.iter().map(|k| Binding::Key(*k))
+            .chain(BINDABLE_BUTTONS.iter().map(|b| Binding::Mouse(*b)))
+            .find(|b| b.name() == name)
+            .ok_or_else(|| format!("unknown key '{}'", name))
+    }
+}
+
+/// Rebindable actions, in menu order.
+#[derive(Clone, Copy, Debug, PartialEq, Eq)]
+pub enum Action {
+    Forward,
+    Back,
+    StrafeLeft,
+    StrafeRight,
+    Shoot,
+    Reload,
+    NextWeapon,
+    PrevWeapon,
+    Scoreboard,
+    KillCam,
+}
+
+impl Action {
+    pub const ALL: [Action; 10] = [
+        Action::Forward,
+        Action::Back,
+        Action::StrafeLeft,
+        Action::StrafeRight,
+        Action::Shoot,
+        Action::Reload,
+        Action::NextWeapon,
+        Action::PrevWeapon,
+        Action::Scoreboard,
+        Action::KillCam,
+    ];
+
+    pub fn label(self) -> &'static str {
+        match self {
+            Action::Forward => "Move forward",
+            Action::Back => "Move back",
+            Action::StrafeLeft => "Strafe left",
+            Action::StrafeRight => "Strafe right",
+            Action::Shoot => "Shoot",
+            Action::Reload => "Reload",
+            Action::NextWeapon => "Next weapon",
+            Action::PrevWeapon => "Previous weapon",
+            Action::Scoreboard => "Scoreboard",
+            Action::KillCam => "Toggle kill-cam",
+        }
+    }
+
+    /// Its field in `KeyBindings` / `PadBindings` (and key in the settings file).
+    pub fn field(self) -> &'static str {
+        match self {
+            Action::Forward => "forward",
+            Action::Back => "back",
+            Action::StrafeLeft => "strafe_left",
+            Action::StrafeRight => "strafe_right",
+            Action::Shoot => "shoot",
+            Action::Reload => "reload",
+            Action::NextWeapon => "next_weapon",
+            Action::PrevWeapon => "prev_weapon",
+            Action::Scoreboard => "scoreboard",
+            Action::KillCam => "kill_cam",
+        }
+    }
+}
+
+#[derive(Clone, Debug, PartialEq, Serialize)]
+pub struct KeyBindings {
+    pub forward: Binding,
+    pub back: Binding,
+    pub strafe_left: Binding,
+    pub strafe_right: Binding,
+    pub shoot: Binding,
+    pub reload: Binding,
+    pub next_weapon: Binding,
+    pub prev_weapon: Binding,
+    pub scoreboard: Binding,
+    pub kill_cam: Binding,
+}
+
+impl Default for KeyBindings {
+    fn default() -> Self {
+        Self {
+            forward: Binding::Key(KeyCode::W),
+            back: Binding::Key(KeyCode::S),
+            strafe_left: Binding::Key(KeyCode::A),
+            strafe_right: Binding::Key(KeyCode::D),
+            shoot: Binding::Mouse(MouseButton::Left),
+            reload: Binding::Key(KeyCode::R),
+            next_weapon: Binding::Key(KeyCode::E),
+            prev_weapon: Binding::Key(KeyCode::Q),
+            scoreboard: Binding::Key(KeyCode::Tab),
+            kill_cam: Binding::Key(KeyCode::K),
+        }
+    }
+}
+
+// Field by field, so a missing or stale key name (say, from an older version)
+// only resets that one binding instead of failing the whole settings file.
+impl<'de> Deserialize<'de> for KeyBindings {
+    fn deserialize<D: Deserializer<'de>>(deserializer: D) -> Result<Self, D::Error> {
+        let saved = HashMap::<String, serde_json::Value>::deserialize(deserializer)?;
+        let mut bindings = KeyBindings::default();
+        for action in Action::ALL {
+            let Some(value) = saved.get(action.field()) else {
+                continue;
+            };
+            match Binding::deserialize(value) {
+                Ok(binding) => bindings.set(action, binding),
+                Err(e) => println!("⚠️ CLIENT: Default binding for {}: {}", action.label(), e),
+            }
+        }
+        Ok(bindings)
+    }
+}
+
+impl KeyBindings {
+    pub fn get(&self, action: Action) -> Binding {
+        match action {
+            Action::Forward => self.forward,
+            Action::Back => self.back,
+            Action::StrafeLeft => self.strafe_left,
+            Action::StrafeRight => self.strafe_right,
+            Action::Shoot => self.shoot,
+            Action::Reload => self.reload,
+            Action::NextWeapon => self.next_weapon,
+            Action::PrevWeapon => self.prev_weapon,
+            Action::Scoreboard => self.scoreboard,
+            Action::KillCam => self.kill_cam,
+        }
+    }
+
+    /// The action `binding` is assigned to, if any.
+    pub fn action_for(&self, binding: Binding) -> Option<Action> {
+        Action::ALL.into_iter().find(|a| self.get(*a) == binding)
+    }
+
+    /// Binds `action` to `binding`, unless another action already has it
+    /// (returned as the error, so one key never fires two actions).
+    pub fn rebind(&mut self, action: Action, binding: Binding) -> Result<(), Action> {
+        match self.action_for(binding) {
+            Some(other) if other != action => Err(other),
+            _ => {
+                self.set(action, binding);
+                Ok(())
+            }
+        }
+    }
+
+    fn set(&mut self, action: Action, binding: Binding) {
+        let slot = match action {
+            Action::Forward => &mut self.forward,
+            Action::Back => &mut self.back,
+            Action::StrafeLeft => &mut self.strafe_left,
+            Action::StrafeRight => &mut self.strafe_right,
+            Action::Shoot => &mut self.shoot,
+            Action::Reload => &mut self.reload,
+            Action::NextWeapon => &mut self.next_weapon,
+            Action::PrevWeapon => &mut self.prev_weapon,
+            Action::Scoreboard => &mut self.scoreboard,
+            Action::KillCam => &mut self.kill_cam,
+        };
+        *slot = binding;
+    }
+}
+
+// ---------- Settings ----------
+/// Player preferences, loaded at startup and saved whenever the menu changes them.
+/// Missing fields fall back to their defaults, so older files keep working.
+#[derive(Clone, Debug, PartialEq, Serialize, Deserialize)]
+#[serde(default)]
+pub struct Settings {
+    pub fov_deg: f32,
+    pub sensitivity: f32, // mouse look speed
+    pub invert_look: bool,
+    pub render_scale: f32,
+    pub volume: f32,      // master volume, 0..1
+    pub minimap_rotate: bool, // player-up instead of north-up
+    pub minimap_zoom: f32,    // pixels per cell
+    pub fog_of_war: bool,     // minimap only shows cells we've seen
+    pub bindings: KeyBindings,
+    pub gamepad: GamepadSettings,
+}
+
+impl Default for Settings {
+    fn default() -> Self {
+        Self {
+            fov_deg: DEFAULT_FOV_DEG,
+            sensitivity: 0.3,
+            invert_look: false,
+            render_scale: DEFAULT_RENDER_SCALE,
+            volume: 0.8,
+            minimap_rotate: false,
+            minimap_zoom: DEFAULT_ZOOM,
+            fog_of_war: false,
+            bindings: KeyBindings::default(),
+            gamepad: GamepadSettings::default(),
+        }
+    }
+}
+
+impl Settings {
+    pub fn path() -> Option<PathBuf> {
+        dirs::config_dir().map(|dir| dir.join(APP_DIR).join(FILE_NAME))
+    }
+
+    /// Reads the settings file; defaults if there is none or it can't be read.
+    pub fn load() -> Self {
+        let Some(path) = Self::path() else {
+            return Self::default();
+        };
+        let text = match std::fs::read_to_string(&path) {
+            Ok(text) => text,
+            Err(_) => return Self::default(), // first run
+        };
+        match serde_json::from_str::<Settings>(&text) {
+            Ok(mut settings) => {
+                settings.clamp();
+                println!("⚙️ CLIENT: Loaded settings from {}", path.display());
+                settings
+            }
+            Err(e) => {
+                println!("⚠️ CLIENT: Ignoring {}: {}", path.display(), e);
+                Self::default()
+            }
+        }
+    }
+
+    pub fn save(&self) {
+        let Some(path) = Self::path() else {
+            return;
+        };
+        let result = path
+            .parent()
+            .map_or(Ok(()), std::fs::create_dir_all)
+            .and_then(|_| std::fs::write(&path, serde_json::to_string_pretty(self).unwrap_or_default()));
+        if let Err(e) = result {
+            println!("⚠️ CLIENT: Could not save settings to {}: {}", path.display(), e);
+        }
+    }
+
+    // Keeps hand-edited values within what the menu allows.
+    fn clamp(&mut self) {
+        self.fov_deg = self.fov_deg.clamp(FOV_RANGE.0, FOV_RANGE.1);
+        self.sensitivity = self.sensitivity.clamp(SENSITIVITY_RANGE.0, SENSITIVITY_RANGE.1);
+        self.render_scale = self.render_scale.clamp(MIN_RENDER_SCALE, 1.0);
+        self.volume = self.volume.clamp(0.0, 1.0);
+        self.minimap_zoom = self.minimap_zoom.clamp(MIN_ZOOM, MAX_ZOOM);
+        let pad = &mut self.gamepad;
+        pad.dead_zone = pad.dead_zone.clamp(DEAD_ZONE_RANGE.0, DEAD_ZONE_RANGE.1);
+        pad.curve = pad.curve.clamp(CURVE_RANGE.0, CURVE_RANGE.1);
+        pad.look_speed = pad.look_speed.clamp(LOOK_SPEED_RANGE.0, LOOK_SPEED_RANGE.1);
+        pad.index = pad.index.min(MAX_PAD_INDEX);
+    }
+}
+
+// ---------- Menu ----------
+/// A row of the settings menu.
+#[derive(Clone, Copy, Debug, PartialEq)]
+pub enum MenuItem {
+    Fov,
+    Sensitivity,
+    InvertLook,
+    RenderScale,
+    Volume,
+    MinimapRotate,
+    MinimapZoom,
+    FogOfWar,
+    Bind(Action),
+    PadIndex,
+    PadDeadZone,
+    PadCurve,
+    PadLookSpeed,
+    PadBind(Action), // only actions the sticks don't cover
+    Reset,
+}
+
+/// Cursor and rebinding state of the settings menu (F2).
+#[derive(Default)]
+pub struct SettingsMenu {
+    pub selected: usize,
+    pub rebinding: bool, // waiting for a key (or pad button) for the selected action
+    pub taken: Option<(Binding, Action)>, // last key refused while rebinding, and who has it
+}
+
+impl SettingsMenu {
+    pub fn items() -> Vec<MenuItem> {
+        let mut items = vec![MenuItem::Fov, MenuItem::Sensitivity, MenuItem::InvertLook, MenuItem::RenderScale, MenuItem::Volume];
+        items.extend([MenuItem::MinimapRotate, MenuItem::MinimapZoom, MenuItem::FogOfWar]);
+        items.extend(Action::ALL.iter().map(|a| MenuItem::Bind(*a)));
+        items.extend([MenuItem::PadIndex, MenuItem::PadDeadZone, MenuItem::PadCurve, MenuItem::PadLookSpeed]);
+        let pad = GamepadSettings::default().bindings;
+        items.extend(Action::ALL.iter().filter(|a| pad.get(**a).is_some()).map(|a| MenuItem::PadBind(*a)));
+        items.push(MenuItem::Reset);
+        items
+    }
+
+    /// Up/Down pick a row, Left/Right change it, Enter toggles, rebinds or resets.
+    /// Returns true if `settings` changed (and was saved).
+    pub fn update(&mut self, settings: &mut Settings, pad: &PadInput) -> bool {
+        let items = Self::items();
+        let item = items[self.selected.min(items.len() - 1)];
+
+        if self.rebinding {
+            if is_key_pressed(KeyCode::Escape) {
+                self.rebinding = false; // keep the old binding
+                self.taken = None;
+                return false;
+            }
+            match item {
+                MenuItem::Bind(action) => {
+                    let Some(binding) = Binding::pressed_now() else {
+                        return false;
+                    };
+                    if let Err(other) = settings.bindings.rebind(action, binding) {
+                        self.taken = Some((binding, other)); // keep waiting for a free key
+                        return false;
+                    }
+                }
+                MenuItem::PadBind(action) => {
+                    let Some(button) = pad.pressed.first() else {
+                        return false;
+                    };
+                    settings.gamepad.bindings.set(action, *button);
+                }
+                _ => {}
+            }
+            self.rebinding = false;
+            self.taken = None;
+            settings.save();
+            return true;
+        }
+
+        if is_key_pressed(KeyCode::Up) {
+            self.selected = self.selected.saturating_sub(1);
+        }
+        if is_key_pressed(KeyCode::Down) {
+            self.selected = (self.selected + 1).min(items.len() - 1);
+        }
+        let step = if is_key_pressed(KeyCode::Right) {
+            1.0
+        } else if is_key_pressed(KeyCode::Left) {
+            -1.0
+        } else {
+            0.0
+        };
+        let enter = is_key_pressed(KeyCode::Enter);
+
+        let before = settings.clone();
+        match item {
+            MenuItem::Fov => settings.fov_deg += step * FOV_STEP,
+            MenuItem::Sensitivity => settings.sensitivity += step * SENSITIVITY_STEP,
+            MenuItem::InvertLook if step != 0.0 || enter => settings.invert_look = !settings.invert_look,
+            MenuItem::InvertLook => {}
+            MenuItem::RenderScale => settings.render_scale += step * RENDER_SCALE_STEP,
+            MenuItem::Volume => settings.volume += step * VOLUME_STEP,
+            MenuItem::MinimapRotate if step != 0.0 || enter => settings.minimap_rotate = !settings.minimap_rotate,
+            MenuItem::MinimapZoom => settings.minimap_zoom += step * ZOOM_STEP,
+            MenuItem::FogOfWar if step != 0.0 || enter => settings.fog_of_war = !settings.fog_of_war,
+            MenuItem::MinimapRotate | MenuItem::FogOfWar => {}
+            MenuItem::Bind(_) | MenuItem::PadBind(_) => {
+                self.rebinding = enter;
+                self.taken = None;
+            }
+            MenuItem::PadIndex => {
+                settings.gamepad.index = settings.gamepad.index.saturating_add_signed(step as isize);
+            }
+            MenuItem::PadDeadZone => settings.gamepad.dead_zone += step * DEAD_ZONE_STEP,
+            MenuItem::PadCurve => settings.gamepad.curve += step * CURVE_STEP,
+            MenuItem::PadLookSpeed => settings.gamepad.look_speed += step * LOOK_SPEED_STEP,
+            MenuItem::Reset if enter => *settings = Settings::default(),
+            MenuItem::Reset => {}
+        }
+        settings.clamp();
+        let changed = *settings != before;
+        if changed {
+            settings.save();
+        }
+        changed
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::gamepad::{PadBindings, PadButton};
+
+    #[test]
+    fn settings_round_trip_through_json() {
+        let mut settings = Settings { fov_deg: 90.0, invert_look: true, ..Settings::default() };
+        settings.bindings.shoot = Binding::Mouse(MouseButton::Right);
+        settings.bindings.forward = Binding::Key(KeyCode::Up);
+        let json = serde_json::to_string(&settings).unwrap();
+        assert!(json.contains("\"Mouse Right\"") && json.contains("\"Up\""));
+        assert_eq!(serde_json::from_str::<Settings>(&json).unwrap(), settings);
+    }
+
+    #[test]
+    fn missing_fields_fall_back_to_defaults() {
+        let settings: Settings = serde_json::from_str(r#"{"fov_deg": 80.0, "bindings": {"reload": "F"}}"#).unwrap();
+        assert_eq!(settings.fov_deg, 80.0);
+        assert_eq!(settings.sensitivity, Settings::default().sensitivity);
+        assert_eq!(settings.bindings.reload, Binding::Key(KeyCode::F));
+        assert_eq!(settings.bindings.forward, Binding::Key(KeyCode::W));
+    }
+
+    #[test]
+    fn a_bad_binding_only_resets_itself() {
+        let json = r#"{"fov_deg": 95.0, "volume": 0.2, "bindings": {"reload": "NoSuchKey", "shoot": 3, "forward": "Up"}}"#;
+        let settings: Settings = serde_json::from_str(json).unwrap();
+        assert_eq!((settings.fov_deg, settings.volume), (95.0, 0.2));
+        assert_eq!(settings.bindings.forward, Binding::Key(KeyCode::Up));
+        assert_eq!(settings.bindings.reload, KeyBindings::default().reload);
+        assert_eq!(settings.bindings.shoot, KeyBindings::default().shoot);
+
+        let json = r#"{"gamepad": {"dead_zone": 0.3, "bindings": {"reload": "Turbo", "shoot": "South"}}}"#;
+        let settings: Settings = serde_json::from_str(json).unwrap();
+        assert_eq!(settings.gamepad.dead_zone, 0.3);
+        assert_eq!(settings.gamepad.bindings.shoot, PadButton::South);
+        assert_eq!(settings.gamepad.bindings.reload, PadBindings::default().reload);
+    }
+
+    #[test]
+    fn fixed_and_taken_keys_cannot_be_bound() {
+        // Weapon slots and minimap keys are hard-wired in gather_input
+        for name in ["Key1", "Key4", "M", "Minus", "Equal"] {
+            assert!(Binding::try_from(name.to_string()).is_err(), "{name} is bindable");
+        }
+
+        let mut bindings = KeyBindings::default();
+        assert_eq!(bindings.rebind(Action::Reload, Binding::Key(KeyCode::W)), Err(Action::Forward));
+        assert_eq!(bindings.reload, Binding::Key(KeyCode::R));
+        assert_eq!(bindings.rebind(Action::Reload, Binding::Key(KeyCode::R)), Ok(())); // its own key
+        assert_eq!(bindings.rebind(Action::Reload, Binding::Key(KeyCode::F)), Ok(()));
+        assert_eq!(bindings.action_for(Binding::Key(KeyCode::F)), Some(Action::Reload));
+    }
+}
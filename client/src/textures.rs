@@ -0,0 +1,167 @@
+use macroquad::prelude::*;
+
+// ---------- Config ----------
+/// Atlas image looked up relative to the working directory: a horizontal strip
+/// of square tiles in `Surface` order. Missing or malformed files fall back to
+/// generated textures.
+pub const ATLAS_PATH: &str = "assets/textures.png";
+const GENERATED_TILE: u16 = 64;
+
+// ---------- Surfaces ----------
+/// What a tile of the atlas is used for; the discriminant is its slot in the strip.
+#[derive(Clone, Copy, Debug, PartialEq, Eq)]
+pub enum Surface {
+    Wall = 0,
+    Spawn = 1,
+    Cover = 2,
+    Floor = 3,
+    Ceiling = 4,
+}
+
+impl Surface {
+    const COUNT: u32 = 5;
+
+    /// Floor texture for a level tile (see `Level::tiles`): spawn points and
+    /// cover zones are marked, everything else is plain floor.
+    pub fn for_floor(tile: u8) -> Self {
+        match tile {
+            2 => Surface::Spawn,
+            3 => Surface::Cover,
+            _ => Surface::Floor,
+        }
+    }
+}
+
+// ---------- Atlas ----------
+/// Kept on the CPU: the raycaster samples it while filling its framebuffer.
+pub struct TextureAtlas {
+    image: Image,
+    tile: u32, // side of one square tile in pixels
+}
+
+impl TextureAtlas {
+    /// Loads the atlas from `path`, or generates one if that fails.
+    pub async fn load(path: &str) -> Self {
+        match load_image(path).await {
+            Ok(image) if image.height > 0
+                && image.width % image.height == 0
+                && (image.width / image.height) as u32 >= Surface::COUNT => {
+                println!("🧱 CLIENT: Loaded textures from {}", path);
+                Self::from_image(image)
+            }
+            Ok(_) => {
+                println!("⚠️ CLIENT: {} is not a strip of {} square tiles; using generated textures", path, Surface::COUNT);
+                Self::generate()
+            }
+            Err(_) => Self::generate(),
+        }
+    }
+
+    /// Procedural fallback: bricks, a spawn pad, a hazard-striped cover zone,
+    /// concrete floor slabs and ceiling panels.
+    pub fn generate() -> Self {
+        let t = GENERATED_TILE;
+        let mut image = Image::gen_image_color(t * Surface::COUNT as u16, t, BLACK);
+        for y in 0..t as u32 {
+            for x in 0..t as u32 {
+                image.set_pixel(x, y, brick(x, y, t as u32));
+                image.set_pixel(x + t as u32, y, spawn_panel(x, y, t as u32));
+                image.set_pixel(x + 2 * t as u32, y, cover_zone(x, y, t as u32));
+                image.set_pixel(x + 3 * t as u32, y, concrete(x, y, t as u32));
+                image.set_pixel(x + 4 * t as u32, y, ceiling_panel(x, y, t as u32));
+            }
+        }
+        Self::from_image(image)
+    }
+
+    fn from_image(image: Image) -> Self {
+        let tile = image.height as u32;
+        Self { image, tile }
+    }
+
+    /// RGBA texel of `surface` at texture coordinates `u`, `v` (0..1, wrapping).
+    pub fn sample(&self, surface: Surface, u: f32, v: f32) -> [u8; 4] {
+        let t = self.tile;
+        let tx = ((u.rem_euclid(1.0) * t as f32) as u32).min(t - 1);
+        let ty = ((v.rem_euclid(1.0) * t as f32) as u32).min(t - 1);
+        let x = surface as u32 * t + tx;
+        self.image.get_image_data()[(ty * self.image.width as u32 + x) as usize]
+    }
+}
+
+// ---------- Procedural textures ----------
+// Cheap deterministic per-texel noise in 0..1.
+fn noise(x: u32, y: u32, seed: u32) -> f32 {
+    let mut h = x.wrapping_mul(374_761_393) ^ y.wrapping_mul(668_265_263) ^ seed.wrapping_mul(2_246_822_519);
+    h = (h ^ (h >> 13)).wrapping_mul(1_274_126_177);
+    (h ^ (h >> 16)) as f32 / u32::MAX as f32
+}
+
+fn shade(r: u8, g: u8, b: u8, k: f32) -> Color {
+    Color::new(r as f32 / 255.0 * k, g as f32 / 255.0 * k, b as f32 / 255.0 * k, 1.0)
+}
+
+fn brick(x: u32, y: u32, t: u32) -> Color {
+    let row_h = t / 4;
+    let brick_w = t / 2;
+    let row = y / row_h;
+    let offset = if row.is_multiple_of(2) { 0 } else { brick_w / 2 };
+    let bx = (x + offset) % brick_w;
+    if y.is_multiple_of(row_h) || bx == 0 {
+        return shade(90, 88, 85, 0.9 + 0.1 * noise(x, y, 1)); // mortar
+    }
+    // Each brick gets its own tint, plus grain
+    let brick_id = (x + offset) / brick_w + row * 7;
+    let k = 0.8 + 0.15 * noise(brick_id, row, 2) + 0.08 * noise(x, y, 3);
+    shade(150, 70, 55, k)
+}
+
+fn spawn_panel(x: u32, y: u32, t: u32) -> Color {
+    let edge = x < 3 || y < 3 || x >= t - 3 || y >= t - 3;
+    if edge {
+        return shade(150, 170, 230, 1.0);
+    }
+    // Diamond marker in the middle
+    let (cx, cy) = (t as i32 / 2, t as i32 / 2);
+    let d = (x as i32 - cx).abs() + (y as i32 - cy).abs();
+    if d > t as i32 / 5 && d < t as i32 / 4 {
+        return shade(120, 200, 255, 1.0);
+    }
+    shade(40, 55, 120, 0.85 + 0.15 * noise(x, y, 4))
+}
+
+// Concrete with a yellow and black striped border
+fn cover_zone(x: u32, y: u32, t: u32) -> Color {
+    let border = x < t / 8 || y < t / 8 || x >= t - t / 8 || y >= t - t / 8;
+    if border {
+        return if ((x + y) / (t / 8)).is_multiple_of(2) {
+            shade(220, 180, 40, 0.9 + 0.1 * noise(x, y, 5))
+        } else {
+            shade(30, 30, 30, 1.0)
+        };
+    }
+    concrete(x, y, t)
+}
+
+fn concrete(x: u32, y: u32, t: u32) -> Color {
+    // Two slabs per cell with a dark joint
+    let slab = t / 2;
+    if x.is_multiple_of(slab) || y.is_multiple_of(slab) {
+        return shade(55, 55, 55, 1.0);
+    }
+    let k = 0.85 + 0.1 * noise(x / slab, y / slab, 6) + 0.05 * noise(x, y, 7);
+    shade(110, 110, 105, k)
+}
+
+fn ceiling_panel(x: u32, y: u32, t: u32) -> Color {
+    let frame = x < 2 || y < 2 || x >= t - 2 || y >= t - 2;
+    if frame {
+        return shade(40, 40, 48, 1.0);
+    }
+    // Light strip down the middle
+    let (cx, cy) = (t as i32 / 2, t as i32 / 2);
+    if (x as i32 - cx).abs() < t as i32 / 3 && (y as i32 - cy).abs() < t as i32 / 16 {
+        return shade(230, 230, 210, 1.0);
+    }
+    shade(85, 85, 95, 0.9 + 0.1 * noise(x, y, 8))
+}
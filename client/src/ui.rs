@@ -1,5 +1,7 @@
 use macroquad::prelude::*;
-use crate::player::PlayerSkin;
+use crate::player::{PlayerSkin, team_color, team_name};
+use crate::gamepad::PadInput;
+use crate::settings::{self, MenuItem, Settings, SettingsMenu};
 
 // ---------- Level Selection UI ----------
 pub fn draw_level_selection(levels: &[(u8, String, String, u8)], selected_level: &mut usize, selected_skin: &mut PlayerSkin, selection_mode: usize) {
@@ -91,7 +93,7 @@ pub fn draw_level_selection(levels: &[(u8, String, String, u8)], selected_level:
 }
 
 // ---------- Connection Screen ----------
-pub fn draw_connection_screen(server_addr: &str, username: &str, input_focus: usize) {
+pub fn draw_connection_screen(server_addr: &str, username: &str, input_focus: usize, team_choice: Option<u8>) {
     // Render a simple input form
     let title = "Connect to Maze War FPS Server";
     let tw = measure_text(title, None, 32, 1.0);
@@ -127,6 +129,122 @@ pub fn draw_connection_screen(server_addr: &str, username: &str, input_focus: us
     draw_text(server_addr, bx, by1, 28.0, WHITE);
     draw_text(username, bx, by2, 28.0, WHITE);
 
-    let hint = "Tab switch, Enter connect";
-    draw_text(hint, bx, by2 + 40.0, 20.0, GRAY);
+    // Preferred team (only used if the server runs a team mode)
+    draw_text("Team:", 200.0, by2 + 50.0, 24.0, GRAY);
+    let (team_txt, team_col) = match team_choice {
+        Some(t) => (team_name(t), team_color(t)),
+        None => ("Auto", WHITE),
+    };
+    draw_text(&format!("< {} >", team_txt), bx, by2 + 50.0, 28.0, team_col);
+
+    let hint = "Tab switch, Left/Right pick team, Enter connect";
+    draw_text(hint, bx, by2 + 90.0, 20.0, GRAY);
+}
+
+// ---------- Connecting Screen ----------
+pub fn draw_connecting_screen(server_addr: &str, attempt: u32, max_attempts: u32, waited: f32) {
+    let dots = ".".repeat(1 + (waited * 2.0) as usize % 3);
+    let title = format!("Connecting to {}{}", server_addr, dots);
+    let tw = measure_text(&title, None, 32, 1.0);
+    draw_text(&title, (screen_width() - tw.width) * 0.5, 200.0, 32.0, WHITE);
+
+    let status = if attempt > 1 {
+        format!("No answer yet, attempt {} of {}", attempt, max_attempts)
+    } else {
+        "Waiting for the server...".to_string()
+    };
+    let sw = measure_text(&status, None, 24, 1.0);
+    draw_text(&status, (screen_width() - sw.width) * 0.5, 250.0, 24.0, if attempt > 1 { YELLOW } else { GRAY });
+
+    let hint = "Esc cancel";
+    let hw = measure_text(hint, None, 20, 1.0);
+    draw_text(hint, (screen_width() - hw.width) * 0.5, 300.0, 20.0, GRAY);
+}
+
+// ---------- Disconnected Screen ----------
+pub fn draw_disconnected_screen(reason: &str) {
+    let title = "Disconnected";
+    let tw = measure_text(title, None, 40, 1.0);
+    draw_text(title, (screen_width() - tw.width) * 0.5, 200.0, 40.0, RED);
+
+    let rw = measure_text(reason, None, 24, 1.0);
+    draw_text(reason, ((screen_width() - rw.width) * 0.5).max(10.0), 250.0, 24.0, WHITE);
+
+    let hint = "Enter or R retry, Esc back";
+    let hw = measure_text(hint, None, 20, 1.0);
+    draw_text(hint, (screen_width() - hw.width) * 0.5, 300.0, 20.0, GRAY);
+}
+
+// ---------- Settings Menu ----------
+pub fn draw_settings_menu(settings: &Settings, menu: &SettingsMenu, pad: &PadInput) {
+    draw_rectangle(0.0, 0.0, screen_width(), screen_height(), Color::from_rgba(0, 0, 0, 200));
+
+    let title = "Settings";
+    let tw = measure_text(title, None, 32, 1.0);
+    draw_text(title, (screen_width() - tw.width) * 0.5, 50.0, 32.0, WHITE);
+
+    let x = screen_width() * 0.5 - 220.0;
+    let row_h = 26.0;
+    let mut y = 90.0;
+    for (i, item) in SettingsMenu::items().iter().enumerate() {
+        let (label, value) = match item {
+            MenuItem::Fov => ("Field of view".to_string(), format!("{:.0}", settings.fov_deg)),
+            MenuItem::Sensitivity => ("Mouse sensitivity".to_string(), format!("{:.2}", settings.sensitivity)),
+            MenuItem::InvertLook => ("Invert mouse look".to_string(), if settings.invert_look { "On" } else { "Off" }.to_string()),
+            MenuItem::RenderScale => ("Resolution scale".to_string(), format!("{:.0}%", settings.render_scale * 100.0)),
+            MenuItem::Volume => ("Volume".to_string(), format!("{:.0}%", settings.volume * 100.0)),
+            MenuItem::MinimapRotate => ("Minimap".to_string(), if settings.minimap_rotate { "Rotating" } else { "North up" }.to_string()),
+            MenuItem::MinimapZoom => ("Minimap zoom".to_string(), format!("{:.0} px/cell", settings.minimap_zoom)),
+            MenuItem::FogOfWar => ("Minimap fog of war".to_string(), if settings.fog_of_war { "On" } else { "Off" }.to_string()),
+            MenuItem::Bind(action) => {
+                let value = if menu.rebinding && i == menu.selected {
+                    match menu.taken {
+                        Some((key, other)) => format!("{} is {} - press another key (Esc cancels)", key.name(), other.label()),
+                        None => "press a key... (Esc cancels)".to_string(),
+                    }
+                } else {
+                    settings.bindings.get(*action).name()
+                };
+                (action.label().to_string(), value)
+            }
+            MenuItem::PadIndex => {
+                let status = if pad.connected { "connected" } else { "not found" };
+                ("Gamepad".to_string(), format!("#{} ({})", settings.gamepad.index + 1, status))
+            }
+            MenuItem::PadDeadZone => ("Stick dead zone".to_string(), format!("{:.0}%", settings.gamepad.dead_zone * 100.0)),
+            MenuItem::PadCurve => ("Stick response curve".to_string(), format!("{:.2}", settings.gamepad.curve)),
+            MenuItem::PadLookSpeed => ("Stick look speed".to_string(), format!("{:.1}", settings.gamepad.look_speed)),
+            MenuItem::PadBind(action) => {
+                let value = if menu.rebinding && i == menu.selected {
+                    "press a pad button... (Esc cancels)".to_string()
+                } else {
+                    settings.gamepad.bindings.get(*action).map_or(String::new(), |b| format!("{:?}", b))
+                };
+                (format!("{} (pad)", action.label()), value)
+            }
+            MenuItem::Reset => ("Reset to defaults".to_string(), String::new()),
+        };
+
+        // Gap between the general settings, the key bindings, the gamepad and reset
+        if matches!(item, MenuItem::Bind(settings::Action::Forward) | MenuItem::PadIndex | MenuItem::Reset) {
+            y += row_h * 0.5;
+        }
+        let selected = i == menu.selected;
+        if selected {
+            draw_rectangle(x - 10.0, y - 18.0, 460.0, row_h - 2.0, Color::from_rgba(100, 100, 200, 255));
+            draw_text(">", x - 25.0, y, 18.0, YELLOW);
+        }
+        draw_text(&label, x, y, 20.0, WHITE);
+        draw_text(&value, x + 240.0, y, 20.0, if selected { YELLOW } else { LIGHTGRAY });
+        y += row_h;
+    }
+
+    let hint = "Up/Down select, Left/Right adjust, Enter toggle/rebind, F2 close";
+    let hint_tw = measure_text(hint, None, 16, 1.0);
+    draw_text(hint, (screen_width() - hint_tw.width) * 0.5, screen_height() - 30.0, 16.0, GRAY);
+    if let Some(path) = Settings::path() {
+        let saved = format!("Saved to {}", path.display());
+        let tw = measure_text(&saved, None, 14, 1.0);
+        draw_text(&saved, (screen_width() - tw.width) * 0.5, screen_height() - 12.0, 14.0, DARKGRAY);
+    }
 }
@@ -0,0 +1,486 @@
+//! Headless load-test client: spawns N simulated players against a running
+//! server and reports latency, packet loss, snapshot rate and error replies.
+
+use std::collections::HashMap;
+use std::net::UdpSocket;
+use std::thread;
+use std::time::{Duration, Instant};
+
+use protocol::{ClientToServer, ServerToClient};
+
+// Movement speed of simulated players (cells/sec), a bit under the real client's.
+const MOVE_SPEED: f32 = 4.0;
+// A ping unanswered this long is lost; a later pong for it doesn't count.
+// Pings sent this close to the end aren't counted at all.
+const LOSS_GRACE: Duration = Duration::from_secs(1);
+
+/// What each simulated player does once it has joined.
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+enum Behavior {
+    Idle,   // only pings; measures the network baseline
+    Spin,   // stays at spawn, turns in place and keeps shooting
+    Random, // random walk through the maze, shooting now and then
+}
+
+#[derive(Debug, Clone)]
+struct Options {
+    server: String,
+    clients: usize,
+    duration: Duration,
+    behavior: Behavior,
+    input_hz: u64,
+    ping_interval: Duration,
+}
+
+/// Everything one simulated player measured.
+#[derive(Debug, Default)]
+struct ClientStats {
+    joined: bool,
+    join_ms: Option<f64>,
+    pings_sent: u32,
+    pongs_received: u32,
+    pings_late: u32, // sent within LOSS_GRACE of the end, never answered
+    rtts_ms: Vec<f64>,
+    snapshots: u32,
+    inputs_sent: u32,
+    hits_taken: u32,
+    deaths: u32,
+    errors: Vec<String>,
+    active_secs: f64,
+    match_phase: Option<protocol::MatchPhase>,
+}
+
+fn main() {
+    let args = clap::Command::new("loadtest")
+        .about("Headless load-test client for the Maze War FPS server")
+        .arg(
+            clap::Arg::new("server")
+                .long("server")
+                .default_value("127.0.0.1:34254"),
+        )
+        .arg(
+            clap::Arg::new("clients")
+                .short('n')
+                .long("clients")
+                .value_parser(clap::value_parser!(usize))
+                .default_value("10"),
+        )
+        .arg(
+            clap::Arg::new("duration")
+                .long("duration")
+                .value_name("SECS")
+                .value_parser(clap::value_parser!(u64))
+                .default_value("30"),
+        )
+        .arg(
+            clap::Arg::new("behavior")
+                .long("behavior")
+                .value_parser(["idle", "spin", "random"])
+                .default_value("random"),
+        )
+        .arg(
+            clap::Arg::new("input-hz")
+                .long("input-hz")
+                .help("Input packets per second per player")
+                .value_parser(clap::value_parser!(u64))
+                .default_value("30"),
+        )
+        .get_matches();
+
+    let opts = Options {
+        server: args
+            .get_one::<String>("server")
+            .cloned()
+            .unwrap_or_default(),
+        clients: *args.get_one::<usize>("clients").unwrap_or(&10),
+        duration: Duration::from_secs(*args.get_one::<u64>("duration").unwrap_or(&30)),
+        behavior: match args.get_one::<String>("behavior").map(String::as_str) {
+            Some("idle") => Behavior::Idle,
+            Some("spin") => Behavior::Spin,
+            _ => Behavior::Random,
+        },
+        input_hz: (*args.get_one::<u64>("input-hz").unwrap_or(&30)).max(1),
+        ping_interval: Duration::from_millis(250),
+    };
+
+    println!(
+        "Load test: {} x {:?} players against {} for {}s",
+        opts.clients,
+        opts.behavior,
+        opts.server,
+        opts.duration.as_secs()
+    );
+
+    let handles: Vec<_> = (0..opts.clients)
+        .map(|i| {
+            let opts = opts.clone();
+            thread::spawn(move || run_client(i, &opts))
+        })
+        .collect();
+
+    let results: Vec<ClientStats> = handles
+        .into_iter()
+        .map(|h| {
+            h.join().unwrap_or_else(|_| ClientStats {
+                errors: vec!["client thread panicked".to_string()],
+                ..Default::default()
+            })
+        })
+        .collect();
+
+    print_report(&results);
+}
+
+// Walkability grid decoded from the server's MazeLevel.
+struct Grid {
+    w: usize,
+    h: usize,
+    walkable: Vec<bool>,
+}
+
+impl Grid {
+    fn from_level(level: &protocol::MazeLevel) -> Self {
+        let walkable = level
+            .cells
+            .iter()
+            .map(|c| !(c.wall_north || c.wall_south || c.wall_east || c.wall_west))
+            .collect();
+        Self {
+            w: level.width as usize,
+            h: level.height as usize,
+            walkable,
+        }
+    }
+
+    fn is_walkable(&self, x: f32, y: f32) -> bool {
+        if x < 0.0 || y < 0.0 {
+            return false;
+        }
+        let (x, y) = (x as usize, y as usize);
+        x < self.w && y < self.h && self.walkable[y * self.w + x]
+    }
+}
+
+fn run_client(index: usize, opts: &Options) -> ClientStats {
+    let mut stats = ClientStats::default();
+    let socket = match open_socket(&opts.server) {
+        Ok(s) => s,
+        Err(e) => {
+            stats.errors.push(format!("socket: {}", e));
+            return stats;
+        }
+    };
+
+    let send = |msg: &ClientToServer| {
+        if let Ok(bytes) = protocol::encode_client(msg) {
+            let _ = socket.send(&bytes);
+        }
+    };
+
+    let start = Instant::now();
+    send(&ClientToServer::Join(protocol::JoinRequest {
+        team: None,
+        username: format!("loadbot{:02}", index),
+    }));
+
+    let mut buf = vec![0u8; 64 * 1024];
+    let mut my_id: Option<u64> = None;
+    let mut grid: Option<Grid> = None;
+    let mut pos: Option<(f32, f32)> = None;
+    let mut angle: f32 = rand::random::<f32>() * std::f32::consts::TAU;
+    let mut pending_pings: HashMap<u64, Instant> = HashMap::new();
+    let mut next_nonce = (index as u64) << 32;
+    let input_dt = Duration::from_micros(1_000_000 / opts.input_hz);
+    let mut last_input = Instant::now();
+    let mut last_ping = Instant::now() - opts.ping_interval;
+
+    while start.elapsed() < opts.duration {
+        // Drain everything the server sent
+        loop {
+            match socket.recv(&mut buf) {
+                Ok(len) => match protocol::decode_server(&buf[..len]) {
+                    Ok(msg) => handle_message(
+                        msg,
+                        &mut stats,
+                        &mut my_id,
+                        &mut grid,
+                        &mut pos,
+                        &mut pending_pings,
+                        start,
+                    ),
+                    Err(e) => stats.errors.push(format!("undecodable reply: {}", e)),
+                },
+                Err(ref e) if e.kind() == std::io::ErrorKind::WouldBlock => break,
+                Err(e) => {
+                    stats.errors.push(format!("recv: {}", e));
+                    thread::sleep(Duration::from_millis(50));
+                    break;
+                }
+            }
+        }
+
+        // Give up on pings that waited too long, so late pongs aren't counted
+        pending_pings.retain(|_, sent| sent.elapsed() < LOSS_GRACE);
+
+        if last_ping.elapsed() >= opts.ping_interval {
+            last_ping = Instant::now();
+            next_nonce += 1;
+            pending_pings.insert(next_nonce, last_ping);
+            stats.pings_sent += 1;
+            send(&ClientToServer::Ping(protocol::Ping {
+                nonce: next_nonce,
+                rtt_ms: stats.rtts_ms.last().map(|ms| ms.round() as u32),
+            }));
+        }
+
+        if last_input.elapsed() >= input_dt {
+            let dt = last_input.elapsed().as_secs_f32();
+            last_input = Instant::now();
+            if let (Some(id), Some(grid), Some(p)) = (my_id, grid.as_ref(), pos.as_mut()) {
+                let shoot = simulate(opts.behavior, grid, p, &mut angle, dt);
+                if opts.behavior != Behavior::Idle {
+                    stats.inputs_sent += 1;
+                    send(&ClientToServer::Input(protocol::InputUpdate {
+                        player_id: id,
+                        x: p.0,
+                        y: p.1,
+                        angle,
+                        action: if shoot {
+                            protocol::Action::Shoot
+                        } else {
+                            protocol::Action::Move
+                        },
+                        weapon: None,
+                    }));
+                }
+            }
+        }
+
+        thread::sleep(Duration::from_millis(2));
+    }
+
+    let end = Instant::now();
+    stats.pings_late = pending_pings
+        .values()
+        .filter(|sent| end.duration_since(**sent) < LOSS_GRACE)
+        .count() as u32;
+    stats.active_secs = start.elapsed().as_secs_f64();
+
+    if let Some(id) = my_id {
+        send(&ClientToServer::Leave(protocol::LeaveNotice {
+            player_id: id,
+        }));
+    }
+    stats
+}
+
+fn open_socket(server: &str) -> std::io::Result<UdpSocket> {
+    let socket = UdpSocket::bind("0.0.0.0:0")?;
+    socket.connect(server)?;
+    socket.set_nonblocking(true)?;
+    Ok(socket)
+}
+
+fn handle_message(
+    msg: ServerToClient,
+    stats: &mut ClientStats,
+    my_id: &mut Option<u64>,
+    grid: &mut Option<Grid>,
+    pos: &mut Option<(f32, f32)>,
+    pending_pings: &mut HashMap<u64, Instant>,
+    start: Instant,
+) {
+    match msg {
+        ServerToClient::Accept(acc) => {
+            if acc.player_id != 0 {
+                *my_id = Some(acc.player_id);
+                stats.joined = true;
+                stats.join_ms = Some(start.elapsed().as_secs_f64() * 1000.0);
+            }
+            // New level: forget our position until the next snapshot places us
+            *grid = Some(Grid::from_level(&acc.level));
+            *pos = None;
+        }
+        ServerToClient::Snapshot(snap) => {
+            stats.snapshots += 1;
+            // Dead players wait for the respawn before taking a position
+            if pos.is_none()
+                && let Some(me) = snap.players.iter().find(|p| Some(p.player_id) == *my_id)
+                && me.life == protocol::LifeState::Alive
+            {
+                *pos = Some((me.x, me.y));
+            }
+        }
+        ServerToClient::Pong(p) => {
+            if let Some(sent) = pending_pings.remove(&p.nonce) {
+                stats.pongs_received += 1;
+                stats.rtts_ms.push(sent.elapsed().as_secs_f64() * 1000.0);
+            }
+        }
+        ServerToClient::Hit(hit) => {
+            if Some(hit.victim_id) == *my_id {
+                stats.hits_taken += 1;
+            }
+        }
+        ServerToClient::Death(death) => {
+            if Some(death.victim_id) == *my_id {
+                stats.deaths += 1;
+                // The server will move us to a spawn point; resync once we're back
+                *pos = None;
+            }
+        }
+        ServerToClient::MatchState(update) => {
+            // Everyone is respawned when a match starts; resync like after a death
+            if stats.match_phase != Some(update.phase) {
+                stats.match_phase = Some(update.phase);
+                *pos = None;
+            }
+        }
+        ServerToClient::Error { message, .. } => stats.errors.push(message),
+        ServerToClient::PlayerLeft(_) | ServerToClient::MatchOver(_) | ServerToClient::Flag(_) => {}
+    }
+}
+
+// Advances one simulated player by `dt`; returns whether it pulls the trigger.
+fn simulate(
+    behavior: Behavior,
+    grid: &Grid,
+    pos: &mut (f32, f32),
+    angle: &mut f32,
+    dt: f32,
+) -> bool {
+    match behavior {
+        Behavior::Idle => false,
+        Behavior::Spin => {
+            *angle = (*angle + 2.0 * dt) % std::f32::consts::TAU;
+            true
+        }
+        Behavior::Random => {
+            // Occasionally pick a new heading; always turn away from walls
+            if rand::random::<f32>() < 0.02 {
+                *angle = rand::random::<f32>() * std::f32::consts::TAU;
+            }
+            let step = MOVE_SPEED * dt;
+            for _ in 0..8 {
+                let next = (pos.0 + angle.cos() * step, pos.1 + angle.sin() * step);
+                if grid.is_walkable(next.0, next.1) {
+                    *pos = next;
+                    break;
+                }
+                *angle = rand::random::<f32>() * std::f32::consts::TAU;
+            }
+            rand::random::<f32>() < 0.1
+        }
+    }
+}
+
+fn percentile(sorted: &[f64], p: f64) -> f64 {
+    if sorted.is_empty() {
+        return 0.0;
+    }
+    let idx = ((sorted.len() - 1) as f64 * p).round() as usize;
+    sorted[idx]
+}
+
+fn print_report(results: &[ClientStats]) {
+    println!();
+    println!(
+        "{:>3} {:>6} {:>8} {:>8} {:>8} {:>7} {:>9} {:>6} {:>6} {:>6}",
+        "#",
+        "joined",
+        "rtt avg",
+        "rtt p95",
+        "loss %",
+        "snap/s",
+        "inputs",
+        "hits",
+        "deaths",
+        "errors"
+    );
+
+    let mut all_rtts = Vec::new();
+    let (mut sent, mut received, mut snapshots, mut secs) = (0u32, 0u32, 0u32, 0f64);
+    let mut errors: HashMap<&str, u32> = HashMap::new();
+
+    for (i, s) in results.iter().enumerate() {
+        let mut rtts = s.rtts_ms.clone();
+        rtts.sort_by(f64::total_cmp);
+        let avg = if rtts.is_empty() {
+            0.0
+        } else {
+            rtts.iter().sum::<f64>() / rtts.len() as f64
+        };
+        let counted = s.pings_sent.saturating_sub(s.pings_late);
+        let loss = if counted == 0 {
+            0.0
+        } else {
+            100.0 * (1.0 - s.pongs_received as f64 / counted as f64)
+        };
+        let rate = if s.active_secs > 0.0 {
+            s.snapshots as f64 / s.active_secs
+        } else {
+            0.0
+        };
+        println!(
+            "{:>3} {:>6} {:>8.1} {:>8.1} {:>8.1} {:>7.1} {:>9} {:>6} {:>6} {:>6}",
+            i,
+            if s.joined { "yes" } else { "NO" },
+            avg,
+            percentile(&rtts, 0.95),
+            loss,
+            rate,
+            s.inputs_sent,
+            s.hits_taken,
+            s.deaths,
+            s.errors.len()
+        );
+
+        all_rtts.extend(rtts);
+        sent += counted;
+        received += s.pongs_received;
+        snapshots += s.snapshots;
+        secs += s.active_secs;
+        for e in &s.errors {
+            *errors.entry(e.as_str()).or_default() += 1;
+        }
+    }
+
+    all_rtts.sort_by(f64::total_cmp);
+    let joined = results.iter().filter(|s| s.joined).count();
+    let join_times: Vec<f64> = results.iter().filter_map(|s| s.join_ms).collect();
+    println!();
+    println!("Players joined : {}/{}", joined, results.len());
+    if !join_times.is_empty() {
+        let worst = join_times.iter().copied().fold(0.0, f64::max);
+        println!("Slowest join   : {:.1} ms", worst);
+    }
+    println!(
+        "RTT            : p50 {:.1} ms, p95 {:.1} ms, max {:.1} ms",
+        percentile(&all_rtts, 0.5),
+        percentile(&all_rtts, 0.95),
+        all_rtts.last().copied().unwrap_or(0.0)
+    );
+    let loss = if sent == 0 {
+        0.0
+    } else {
+        100.0 * (1.0 - received as f64 / sent as f64)
+    };
+    println!(
+        "Ping loss      : {:.2}% ({} of {} answered)",
+        loss, received, sent
+    );
+    if secs > 0.0 {
+        println!(
+            "Snapshot rate  : {:.1}/s per player",
+            snapshots as f64 / secs
+        );
+    }
+    if errors.is_empty() {
+        println!("Server errors  : none");
+    } else {
+        println!("Server errors  :");
+        for (msg, count) in errors {
+            println!("  {:>5} x {}", count, msg);
+        }
+    }
+}
@@ -10,6 +10,7 @@ pub enum Action {
     Shoot = 2,     // Shooting action
     Ping = 3,      // Ping for latency measurement
     SelectLevel = 4, // Level selection
+    Reload = 5,    // Reload the equipped weapon from reserve ammo
 }
 
 /// Describes the state of a player in the game.
@@ -26,6 +27,41 @@ pub struct PlayerState {
     pub ammo: u8,
     pub kills: u32,
     pub deaths: u32,
+    #[serde(default)]
+    pub spawn_protected: bool, // immune to damage right after (re)spawning
+    #[serde(default)]
+    pub team_id: Option<u8>,   // None outside team modes
+    #[serde(default)]
+    pub weapon: Weapon,        // currently equipped; `ammo` is its magazine
+    #[serde(default)]
+    pub reserve_ammo: u16,     // spare rounds for the equipped weapon
+    #[serde(default)]
+    pub reloading: bool,
+    #[serde(default)]
+    pub armor: u8,             // absorbs half of incoming damage until used up
+    #[serde(default)]
+    pub speed_boost_ms: u32,   // time left on the speed boost, 0 if none
+    #[serde(default)]
+    pub damage_boost_ms: u32,  // time left on the damage boost, 0 if none
+    #[serde(default)]
+    pub life: LifeState,
+    #[serde(default)]
+    pub ping_ms: Option<u32>, // latest RTT the client reported; None for bots
+    #[serde(default)]
+    pub is_bot: bool,         // server-driven player
+}
+
+/// Whether a player is in play.
+#[derive(Debug, Clone, Copy, Serialize, Deserialize, PartialEq, Default)]
+pub enum LifeState {
+    #[default]
+    Alive,
+    /// Waiting to respawn; `respawn_in_ms` is None when the player sits out
+    /// until the round ends (last man standing).
+    Dead {
+        killer_id: Option<u64>,
+        respawn_in_ms: Option<u32>,
+    },
 }
 
 /// Represents a bullet in the game world.
@@ -49,6 +85,18 @@ pub struct MazeCell {
     pub wall_south: bool,
     pub wall_east: bool,
     pub wall_west: bool,
+    #[serde(default)]
+    pub kind: CellKind,
+}
+
+/// What a cell is used for, so clients can mark spawn points and cover zones.
+#[derive(Debug, Clone, Copy, Serialize, Deserialize, PartialEq, Eq, Default)]
+pub enum CellKind {
+    #[default]
+    Path,
+    Wall,
+    SpawnPoint,
+    Cover,
 }
 
 /// Describes the entire maze level.
@@ -69,6 +117,8 @@ pub struct MazeLevel {
 #[derive(Debug, Clone, Serialize, Deserialize)]
 pub struct JoinRequest {
     pub username: String,
+    #[serde(default)]
+    pub team: Option<u8>, // preferred team in team modes; None = auto-balance
 }
 
 /// Sent by server to accept a join request.
@@ -88,6 +138,8 @@ pub struct InputUpdate {
     pub y: f32,
     pub angle: f32,
     pub action: Action,
+    #[serde(default)]
+    pub weapon: Option<Weapon>, // weapon the player wants equipped; None keeps the current one
 }
 
 /// Sent by client to notify server of leaving the game.
@@ -101,6 +153,8 @@ pub struct LeaveNotice {
 #[derive(Debug, Clone, Serialize, Deserialize)]
 pub struct Ping {
     pub nonce: u64,
+    #[serde(default)]
+    pub rtt_ms: Option<u32>, // sender's latest measured round trip, shown on the scoreboard
 }
 
 /// Sent by server in response to a ping.
@@ -117,6 +171,75 @@ pub struct Snapshot {
     pub server_time_ms: u64,
     pub players: Vec<PlayerState>,
     pub bullets: Vec<Bullet>,
+    #[serde(default)]
+    pub flags: Vec<FlagState>, // empty outside capture the flag
+    #[serde(default)]
+    pub pickups: Vec<PickupState>,
+}
+
+/// Kinds of items lying around the map.
+#[derive(Debug, Clone, Copy, Serialize, Deserialize, PartialEq, Eq, Hash)]
+pub enum PickupKind {
+    Ammo,        // one magazine of reserve ammo for every weapon
+    Health,      // restores health up to the maximum
+    Armor,       // adds armor up to the maximum
+    SpeedBoost,  // faster movement for a while
+    DamageBoost, // harder-hitting bullets for a while
+}
+
+/// Movement speed multiplier while a speed boost is active. Movement is
+/// client-side, so the client applies this; the server only times it.
+pub const SPEED_BOOST_FACTOR: f32 = 1.5;
+/// Bullet damage multiplier while a damage boost is active.
+pub const DAMAGE_BOOST_FACTOR: f32 = 1.5;
+
+/// Server ticks (and `Snapshot`s sent) per second.
+pub const SNAPSHOT_HZ: u32 = 20;
+
+/// A pickup spot; `available` is false while it waits to respawn.
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct PickupState {
+    pub pickup_id: u32,
+    pub kind: PickupKind,
+    pub x: f32,
+    pub y: f32,
+    pub available: bool,
+}
+
+/// Where a team's flag is right now.
+#[derive(Debug, Clone, Copy, Serialize, Deserialize, PartialEq)]
+pub enum FlagStatus {
+    AtBase,
+    Carried { player_id: u64 },
+    Dropped,
+}
+
+/// A capture-the-flag flag; `x`/`y` follow the carrier while carried.
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct FlagState {
+    pub team_id: u8,
+    pub x: f32,
+    pub y: f32,
+    pub base_x: f32,
+    pub base_y: f32,
+    pub status: FlagStatus,
+}
+
+/// What happened to a flag.
+#[derive(Debug, Clone, Copy, Serialize, Deserialize, PartialEq, Eq)]
+pub enum FlagEventKind {
+    PickedUp,
+    Dropped,
+    Returned, // back at base, by a defender or after lying around too long
+    Captured,
+}
+
+/// Sent by server whenever a flag changes hands.
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct FlagEvent {
+    pub team_id: u8,            // whose flag
+    pub player_id: Option<u64>, // who did it; None for automatic returns
+    pub kind: FlagEventKind,
 }
 
 /// Sent when a player is hit by a bullet
@@ -133,9 +256,122 @@ pub struct HitEvent {
 pub struct DeathEvent {
     pub victim_id: u64,
     pub killer_id: u64,
-    pub weapon: String,
+    pub weapon: Weapon,
 }
 
+/// Weapons players can carry. Every player has all of them and switches freely.
+#[derive(Debug, Clone, Copy, Serialize, Deserialize, PartialEq, Eq, Hash, Default)]
+pub enum Weapon {
+    #[default]
+    Laser,   // steady all-rounder
+    Rifle,   // fast, weak, a little inaccurate
+    Shotgun, // pellet spread, short range
+    Railgun, // slow, hard-hitting, long range
+}
+
+/// Stats for one weapon; shared so the client HUD agrees with the server.
+#[derive(Debug, Clone, Copy)]
+pub struct WeaponStats {
+    pub damage: u8,            // per projectile
+    pub fire_interval: f32,    // seconds between shots
+    pub spread: f32,           // total cone in radians
+    pub projectile_speed: f32, // cells per second
+    pub pellets: u8,           // projectiles per shot
+    pub magazine: u8,          // shots before running dry
+    pub range: f32,            // cells a projectile travels before vanishing
+    pub reload_time: f32,      // seconds to refill the magazine from reserve
+    pub starting_reserve: u16, // spare rounds on spawn
+    pub max_reserve: u16,      // most spare rounds a player can carry
+}
+
+impl Weapon {
+    pub const ALL: [Weapon; 4] = [Weapon::Laser, Weapon::Rifle, Weapon::Shotgun, Weapon::Railgun];
+
+    pub fn stats(self) -> WeaponStats {
+        match self {
+            Weapon::Laser => WeaponStats { damage: 25, fire_interval: 0.5, spread: 0.0, projectile_speed: 25.0, pellets: 1, magazine: 30, range: 75.0, reload_time: 1.5, starting_reserve: 60, max_reserve: 120 },
+            Weapon::Rifle => WeaponStats { damage: 12, fire_interval: 0.12, spread: 0.06, projectile_speed: 35.0, pellets: 1, magazine: 30, range: 40.0, reload_time: 2.0, starting_reserve: 60, max_reserve: 150 },
+            Weapon::Shotgun => WeaponStats { damage: 12, fire_interval: 0.9, spread: 0.35, projectile_speed: 20.0, pellets: 6, magazine: 8, range: 10.0, reload_time: 2.5, starting_reserve: 16, max_reserve: 32 },
+            Weapon::Railgun => WeaponStats { damage: 90, fire_interval: 1.5, spread: 0.0, projectile_speed: 60.0, pellets: 1, magazine: 5, range: 100.0, reload_time: 2.5, starting_reserve: 10, max_reserve: 20 },
+        }
+    }
+
+    pub fn name(self) -> &'static str {
+        match self {
+            Weapon::Laser => "Laser",
+            Weapon::Rifle => "Rifle",
+            Weapon::Shotgun => "Shotgun",
+            Weapon::Railgun => "Railgun",
+        }
+    }
+
+    /// Position in `Weapon::ALL` (also the number key that selects it, minus one).
+    pub fn index(self) -> usize {
+        Weapon::ALL.iter().position(|w| *w == self).unwrap_or(0)
+    }
+}
+
+impl std::fmt::Display for Weapon {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        f.write_str(self.name())
+    }
+}
+
+
+/// Rules the server is running.
+#[derive(Debug, Clone, Copy, Serialize, Deserialize, PartialEq, Eq)]
+pub enum GameMode {
+    Deathmatch,       // free-for-all, most frags wins
+    TeamDeathmatch,   // two teams, team with most frags wins
+    LastManStanding,  // no respawns during a round, last one alive wins
+    CaptureTheFlag,   // two teams, bring the enemy flag to your base
+}
+
+/// Where the current match is in its lifecycle.
+#[derive(Debug, Clone, Copy, Serialize, Deserialize, PartialEq, Eq)]
+pub enum MatchPhase {
+    Warmup, // waiting for players / countdown; kills don't count
+    Active, // match running
+    Ended,  // winner announced, next warmup starts after a short pause
+}
+
+/// Which enemies clients show on the minimap.
+#[derive(Debug, Clone, Copy, Serialize, Deserialize, PartialEq, Eq, Default)]
+pub enum RadarMode {
+    Off,     // teammates only
+    Visible, // enemies in your line of sight
+    #[default]
+    All,     // every enemy, wherever they are
+}
+
+/// Who won a match.
+#[derive(Debug, Clone, Serialize, Deserialize, PartialEq)]
+pub enum MatchWinner {
+    Player { player_id: u64, username: String },
+    Team { team_id: u8 },
+    Draw,
+}
+
+/// Sent by server on every phase change and periodically while a match runs.
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct MatchStateUpdate {
+    pub mode: GameMode,
+    pub phase: MatchPhase,
+    pub time_remaining_ms: Option<u64>, // until the phase ends, if it's timed
+    pub frag_limit: Option<u32>,
+    pub capture_limit: Option<u32>, // only set in capture the flag
+    pub team_scores: Vec<u32>, // kills (or captures in CTF) indexed by team id; empty outside team modes
+    #[serde(default)]
+    pub enemy_radar: RadarMode,
+}
+
+/// Sent by server when a match ends.
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct MatchResult {
+    pub mode: GameMode,
+    pub winner: MatchWinner,
+    pub reason: String, // e.g. "frag limit reached"
+}
 
 /// Sent by client to select a level
 #[derive(Debug, Clone, Serialize, Deserialize)]
@@ -165,7 +401,16 @@ pub enum ServerToClient {
     Pong(Pong),
     Hit(HitEvent),
     Death(DeathEvent),
-    Error { message: String },
+    MatchState(MatchStateUpdate),
+    MatchOver(MatchResult),
+    Flag(FlagEvent),
+    /// `fatal` errors (join rejected, not a player here) end the session;
+    /// the rest only report a request the server couldn't use.
+    Error {
+        message: String,
+        #[serde(default)]
+        fatal: bool,
+    },
 }
 
 /// Protocol error type for serialization/deserialization.
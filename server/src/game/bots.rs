@@ -0,0 +1,345 @@
+use super::logic::{Maze, distance};
+use std::time::{Duration, Instant};
+
+// Walking speed in cells/sec (humans move at 5.0 client-side).
+const BOT_SPEED: f32 = 4.0;
+// Bots don't notice enemies beyond this many cells.
+const SIGHT_RANGE: f32 = 14.0;
+// Bots stop closing in once this close to their target.
+const ENGAGE_DISTANCE: f32 = 3.0;
+// How often a bot re-plans its route even if it still has one.
+const REPATH_INTERVAL: Duration = Duration::from_secs(1);
+
+pub const BOT_NAMES: [&str; 12] = [
+    "Alpha", "Bravo", "Charlie", "Delta", "Echo", "Foxtrot", "Golf", "Hotel", "India", "Juliet",
+    "Kilo", "Lima",
+];
+
+/// The first of `BOT_NAMES` no live bot is using (numbered once all are taken),
+/// so removing and adding bots never gives two the same name.
+pub fn bot_name(taken: &[&str]) -> String {
+    (1..)
+        .flat_map(|round| {
+            BOT_NAMES.iter().map(move |name| match round {
+                1 => format!("[BOT] {}", name),
+                _ => format!("[BOT] {} {}", name, round),
+            })
+        })
+        .find(|name| !taken.contains(&name.as_str()))
+        .expect("names never run out")
+}
+
+/// How sharp a bot is: how long it needs to react to a visible enemy and how
+/// far (in radians, either side) its aim may wander.
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum Difficulty {
+    Easy,
+    Normal,
+    Hard,
+}
+
+impl Difficulty {
+    pub fn parse(s: &str) -> Option<Self> {
+        match s.to_ascii_lowercase().as_str() {
+            "easy" => Some(Difficulty::Easy),
+            "normal" => Some(Difficulty::Normal),
+            "hard" => Some(Difficulty::Hard),
+            _ => None,
+        }
+    }
+
+    pub fn reaction_time(self) -> Duration {
+        match self {
+            Difficulty::Easy => Duration::from_millis(800),
+            Difficulty::Normal => Duration::from_millis(450),
+            Difficulty::Hard => Duration::from_millis(200),
+        }
+    }
+
+    pub fn aim_spread(self) -> f32 {
+        match self {
+            Difficulty::Easy => 0.20,
+            Difficulty::Normal => 0.08,
+            Difficulty::Hard => 0.03,
+        }
+    }
+}
+
+/// Per-bot decision state. The server feeds the result of `think` through
+/// `handle_input`, exactly like a packet from a human client.
+#[derive(Debug)]
+pub struct BotBrain {
+    pub difficulty: Difficulty,
+    path: Vec<(usize, usize)>, // remaining tiles to walk, next first
+    next_repath: Instant,
+    target: Option<u64>,
+    target_seen_since: Option<Instant>,
+    last_pos: (f32, f32),
+}
+
+impl BotBrain {
+    pub fn new(difficulty: Difficulty) -> Self {
+        Self {
+            difficulty,
+            path: Vec::new(),
+            next_repath: Instant::now(),
+            target: None,
+            target_seen_since: None,
+            last_pos: (0.0, 0.0),
+        }
+    }
+
+    /// Forgets the current route and target (after a level change or respawn).
+    pub fn reset(&mut self) {
+        self.path.clear();
+        self.target = None;
+        self.target_seen_since = None;
+        self.next_repath = Instant::now();
+    }
+
+    /// Decides this tick's movement, facing and trigger. `enemies` holds the
+    /// ids and positions of living opponents; `objective` is a place the mode
+    /// wants the bot to go (e.g. a flag), which beats hunting enemies.
+    #[allow(clippy::too_many_arguments)]
+    pub fn think(
+        &mut self,
+        player_id: u64,
+        me: (f32, f32, f32),
+        maze: &Maze,
+        enemies: &[(u64, (f32, f32))],
+        objective: Option<(f32, f32)>,
+        dt: f32,
+        now: Instant,
+    ) -> protocol::InputUpdate {
+        let (x, y, angle) = me;
+
+        // A jump of more than a cell means we respawned or the level changed
+        if distance((x, y), self.last_pos) > 1.5 {
+            self.reset();
+        }
+
+        // Closest enemy we can actually see
+        let visible = enemies
+            .iter()
+            .map(|&(id, pos)| (id, pos, distance((x, y), pos)))
+            .filter(|&(_, pos, d)| d <= SIGHT_RANGE && maze.line_of_sight((x, y), pos))
+            .min_by(|a, b| a.2.total_cmp(&b.2));
+
+        match visible {
+            Some((id, _, _)) if self.target == Some(id) => {}
+            Some((id, _, _)) => {
+                self.target = Some(id);
+                self.target_seen_since = Some(now);
+            }
+            None => {
+                self.target = None;
+                self.target_seen_since = None;
+            }
+        }
+
+        if self.path.is_empty() || now >= self.next_repath {
+            let goal = objective.or(visible.map(|v| v.1));
+            self.plan_route((x, y), maze, enemies, goal);
+            self.next_repath = now + REPATH_INTERVAL;
+        }
+
+        // Walk toward the next tile center unless we're already in fighting range
+        let mut pos = (x, y);
+        let mut facing = angle;
+        let close_enough = objective.is_none() && visible.is_some_and(|v| v.2 <= ENGAGE_DISTANCE);
+        if !close_enough {
+            let mut budget = BOT_SPEED * dt;
+            while budget > 0.0 {
+                let Some(&(tx, ty)) = self.path.first() else {
+                    break;
+                };
+                let goal = (tx as f32 + 0.5, ty as f32 + 0.5);
+                let d = distance(pos, goal);
+                if d <= budget {
+                    pos = goal;
+                    budget -= d;
+                    self.path.remove(0);
+                } else {
+                    pos.0 += (goal.0 - pos.0) / d * budget;
+                    pos.1 += (goal.1 - pos.1) / d * budget;
+                    budget = 0.0;
+                }
+                facing = (goal.1 - y).atan2(goal.0 - x);
+            }
+        }
+
+        // Aim and fire once we've had the target in view for our reaction time
+        let mut shoot = false;
+        if let (Some((_, target_pos, _)), Some(since)) = (visible, self.target_seen_since) {
+            facing = (target_pos.1 - pos.1).atan2(target_pos.0 - pos.0);
+            if now.duration_since(since) >= self.difficulty.reaction_time() {
+                let spread = self.difficulty.aim_spread();
+                facing += (rand::random::<f32>() * 2.0 - 1.0) * spread;
+                shoot = true;
+            }
+        }
+
+        self.last_pos = pos;
+        protocol::InputUpdate {
+            player_id,
+            x: pos.0,
+            y: pos.1,
+            angle: facing,
+            action: if shoot {
+                protocol::Action::Shoot
+            } else {
+                protocol::Action::Move
+            },
+            weapon: None,
+        }
+    }
+
+    // Heads for the objective or visible target, else the nearest enemy by
+    // walking distance, else a random walkable cell.
+    fn plan_route(
+        &mut self,
+        pos: (f32, f32),
+        maze: &Maze,
+        enemies: &[(u64, (f32, f32))],
+        preferred: Option<(f32, f32)>,
+    ) {
+        let here = (pos.0 as usize, pos.1 as usize);
+        let field = maze.distance_field(here);
+        let goal = preferred
+            .or_else(|| {
+                enemies
+                    .iter()
+                    .filter_map(|&(_, p)| field.get(p.0 as usize, p.1 as usize).map(|d| (d, p)))
+                    .min_by_key(|&(d, _)| d)
+                    .map(|(_, p)| p)
+            })
+            .map(|p| (p.0 as usize, p.1 as usize))
+            .or_else(|| random_reachable_cell(maze, &field));
+
+        self.path = goal
+            .and_then(|g| maze.find_path(here, g))
+            .map(|mut p| {
+                p.remove(0); // drop the tile we're standing on
+                p
+            })
+            .unwrap_or_default();
+    }
+}
+
+fn random_reachable_cell(
+    maze: &Maze,
+    field: &super::logic::DistanceField,
+) -> Option<(usize, usize)> {
+    let cells: Vec<(usize, usize)> = (0..maze.height)
+        .flat_map(|y| (0..maze.width).map(move |x| (x, y)))
+        .filter(|&(x, y)| field.get(x, y).is_some_and(|d| d > 0))
+        .collect();
+    if cells.is_empty() {
+        return None;
+    }
+    Some(cells[rand::random_range(0..cells.len())])
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::game::logic::tests::maze_from;
+
+    fn corridor() -> Maze {
+        maze_from(&["############", "#..........#", "############"])
+    }
+
+    const BOT: (f32, f32, f32) = (5.5, 1.5, 0.0);
+    const NEARBY_ENEMY: [(u64, (f32, f32)); 1] = [(2, (3.5, 1.5))]; // two cells west
+
+    #[test]
+    fn names_skip_ones_in_use() {
+        assert_eq!(bot_name(&[]), "[BOT] Alpha");
+        assert_eq!(bot_name(&["[BOT] Alpha", "[BOT] Charlie"]), "[BOT] Bravo");
+        let all: Vec<String> = BOT_NAMES.iter().map(|n| format!("[BOT] {}", n)).collect();
+        let taken: Vec<&str> = all.iter().map(|n| n.as_str()).collect();
+        assert_eq!(bot_name(&taken), "[BOT] Alpha 2");
+    }
+
+    #[test]
+    fn waits_its_reaction_time_before_firing() {
+        let maze = corridor();
+        let now = Instant::now();
+        for difficulty in [Difficulty::Easy, Difficulty::Normal, Difficulty::Hard] {
+            let mut brain = BotBrain::new(difficulty);
+            let first = brain.think(1, BOT, &maze, &NEARBY_ENEMY, None, 0.0, now);
+            assert_eq!(first.action, protocol::Action::Move);
+            let early = now + difficulty.reaction_time() - Duration::from_millis(10);
+            let held = brain.think(1, BOT, &maze, &NEARBY_ENEMY, None, 0.0, early);
+            assert_eq!(held.action, protocol::Action::Move);
+            let ready = now + difficulty.reaction_time();
+            let fired = brain.think(1, BOT, &maze, &NEARBY_ENEMY, None, 0.0, ready);
+            assert_eq!(fired.action, protocol::Action::Shoot);
+        }
+    }
+
+    #[test]
+    fn does_not_fire_through_walls() {
+        let maze = maze_from(&["#######", "#.....#", "###.###", "#.....#", "#######"]);
+        let hidden = [(2, (1.5, 3.5))];
+        let mut brain = BotBrain::new(Difficulty::Hard);
+        let now = Instant::now();
+        brain.think(1, (1.5, 1.5, 0.0), &maze, &hidden, None, 0.0, now);
+        let later = brain.think(
+            1,
+            (1.5, 1.5, 0.0),
+            &maze,
+            &hidden,
+            None,
+            0.0,
+            now + Duration::from_secs(2),
+        );
+        assert_eq!(later.action, protocol::Action::Move);
+    }
+
+    #[test]
+    fn aim_wanders_within_the_difficulty_spread() {
+        let maze = corridor();
+        let exact = std::f32::consts::PI; // straight west
+        for difficulty in [Difficulty::Easy, Difficulty::Normal, Difficulty::Hard] {
+            let spread = difficulty.aim_spread();
+            let mut widest: f32 = 0.0;
+            for _ in 0..200 {
+                let mut brain = BotBrain::new(difficulty);
+                let now = Instant::now();
+                brain.think(1, BOT, &maze, &NEARBY_ENEMY, None, 0.0, now);
+                let shot = brain.think(
+                    1,
+                    BOT,
+                    &maze,
+                    &NEARBY_ENEMY,
+                    None,
+                    0.0,
+                    now + difficulty.reaction_time(),
+                );
+                let off = (shot.angle - exact).abs();
+                assert!(off <= spread + 1e-5, "{:?} missed by {}", difficulty, off);
+                widest = widest.max(off);
+            }
+            // Sloppier bots really do use their wider cone
+            assert!(widest > spread * 0.5);
+        }
+    }
+
+    #[test]
+    fn objective_beats_standing_and_fighting() {
+        let maze = corridor();
+        let now = Instant::now();
+
+        // Without an objective, a close enemy is fought from where we stand
+        let mut brain = BotBrain::new(Difficulty::Normal);
+        let hold = brain.think(1, BOT, &maze, &NEARBY_ENEMY, None, 0.1, now);
+        assert_eq!((hold.x, hold.y), (BOT.0, BOT.1));
+
+        // With one, the bot heads for it (east) even with the enemy in range
+        let mut brain = BotBrain::new(Difficulty::Normal);
+        let go = brain.think(1, BOT, &maze, &NEARBY_ENEMY, Some((10.5, 1.5)), 0.1, now);
+        assert!(go.x > BOT.0);
+        assert!((go.x - BOT.0 - BOT_SPEED * 0.1).abs() < 1e-4);
+    }
+}
@@ -0,0 +1,322 @@
+use super::logic::{Maze, distance};
+use protocol::{FlagEvent, FlagEventKind, FlagState, FlagStatus};
+use std::time::{Duration, Instant};
+
+// How close (in cells) a player must get to touch a flag or base.
+const TOUCH_RADIUS: f32 = 0.7;
+// A dropped flag nobody touches goes home on its own after this long.
+const AUTO_RETURN: Duration = Duration::from_secs(30);
+
+/// What the flag rules need to know about a player this tick.
+#[derive(Debug, Clone, Copy)]
+pub struct Runner {
+    pub player_id: u64,
+    pub team: u8,
+    pub pos: (f32, f32),
+    pub alive: bool,
+}
+
+#[derive(Debug)]
+struct Flag {
+    team: u8,
+    base: (f32, f32),
+    pos: (f32, f32),
+    status: FlagStatus,
+    dropped_at: Option<Instant>,
+}
+
+impl Flag {
+    fn send_home(&mut self) {
+        self.pos = self.base;
+        self.status = FlagStatus::AtBase;
+        self.dropped_at = None;
+    }
+}
+
+/// One flag per team, placed at the maze's flag bases. Walking over the
+/// enemy flag picks it up, dying drops it, touching your own dropped flag
+/// returns it, and bringing the enemy flag to your own flag while it is at
+/// base scores a capture.
+#[derive(Debug)]
+pub struct Flags {
+    flags: Vec<Flag>,
+}
+
+impl Flags {
+    pub fn new(maze: &Maze) -> Self {
+        let flags = maze
+            .flag_bases
+            .iter()
+            .enumerate()
+            .map(|(team, &(x, y))| {
+                let base = (x as f32 + 0.5, y as f32 + 0.5);
+                Flag {
+                    team: team as u8,
+                    base,
+                    pos: base,
+                    status: FlagStatus::AtBase,
+                    dropped_at: None,
+                }
+            })
+            .collect();
+        Self { flags }
+    }
+
+    /// Puts every flag back at its base (new round).
+    pub fn reset(&mut self) {
+        for flag in &mut self.flags {
+            flag.send_home();
+        }
+    }
+
+    /// Where bots should head: home while carrying the enemy flag, after our
+    /// own flag while it's away from base, otherwise the enemy flag.
+    pub fn objective(&self, runner: &Runner) -> Option<(f32, f32)> {
+        let own = self.flags.iter().find(|f| f.team == runner.team);
+        if self.carried_by(runner.player_id).is_some() {
+            return own.map(|f| f.base);
+        }
+        if let Some(own) = own
+            && own.status != FlagStatus::AtBase
+        {
+            return Some(own.pos);
+        }
+        self.flags
+            .iter()
+            .find(|f| f.team != runner.team)
+            .map(|f| f.pos)
+    }
+
+    fn carried_by(&self, player_id: u64) -> Option<usize> {
+        self.flags
+            .iter()
+            .position(|f| f.status == FlagStatus::Carried { player_id })
+    }
+
+    /// Applies pickup/drop/return/capture rules. Returns the events that
+    /// happened; captures are reported with the capturing player's id.
+    pub fn update(&mut self, runners: &[Runner], now: Instant) -> Vec<FlagEvent> {
+        let mut events = Vec::new();
+
+        // Carried flags follow their carrier, or fall where the carrier died
+        for flag in &mut self.flags {
+            let FlagStatus::Carried { player_id } = flag.status else {
+                continue;
+            };
+            match runners.iter().find(|r| r.player_id == player_id) {
+                Some(r) if r.alive => flag.pos = r.pos,
+                carrier => {
+                    if let Some(r) = carrier {
+                        flag.pos = r.pos;
+                    }
+                    flag.status = FlagStatus::Dropped;
+                    flag.dropped_at = Some(now);
+                    events.push(FlagEvent {
+                        team_id: flag.team,
+                        player_id: Some(player_id),
+                        kind: FlagEventKind::Dropped,
+                    });
+                }
+            }
+        }
+
+        // Dropped flags left alone too long go home
+        for flag in &mut self.flags {
+            if flag
+                .dropped_at
+                .is_some_and(|at| now.duration_since(at) >= AUTO_RETURN)
+            {
+                flag.send_home();
+                events.push(FlagEvent {
+                    team_id: flag.team,
+                    player_id: None,
+                    kind: FlagEventKind::Returned,
+                });
+            }
+        }
+
+        for runner in runners.iter().filter(|r| r.alive) {
+            for i in 0..self.flags.len() {
+                let flag = &self.flags[i];
+                if distance(runner.pos, flag.pos) > TOUCH_RADIUS {
+                    continue;
+                }
+                let own = flag.team == runner.team;
+                match flag.status {
+                    // Grab the enemy flag, one at a time
+                    FlagStatus::AtBase | FlagStatus::Dropped
+                        if !own && self.carried_by(runner.player_id).is_none() =>
+                    {
+                        let flag = &mut self.flags[i];
+                        flag.status = FlagStatus::Carried {
+                            player_id: runner.player_id,
+                        };
+                        flag.dropped_at = None;
+                        events.push(FlagEvent {
+                            team_id: flag.team,
+                            player_id: Some(runner.player_id),
+                            kind: FlagEventKind::PickedUp,
+                        });
+                    }
+                    // Return our own dropped flag
+                    FlagStatus::Dropped if own => {
+                        self.flags[i].send_home();
+                        events.push(FlagEvent {
+                            team_id: runner.team,
+                            player_id: Some(runner.player_id),
+                            kind: FlagEventKind::Returned,
+                        });
+                    }
+                    // Touch our flag at base while carrying theirs: capture
+                    FlagStatus::AtBase if own => {
+                        if let Some(carried) = self.carried_by(runner.player_id) {
+                            let enemy = &mut self.flags[carried];
+                            enemy.send_home();
+                            events.push(FlagEvent {
+                                team_id: enemy.team,
+                                player_id: Some(runner.player_id),
+                                kind: FlagEventKind::Captured,
+                            });
+                        }
+                    }
+                    _ => {}
+                }
+            }
+        }
+
+        events
+    }
+
+    pub fn to_wire(&self) -> Vec<FlagState> {
+        self.flags
+            .iter()
+            .map(|f| FlagState {
+                team_id: f.team,
+                x: f.pos.0,
+                y: f.pos.1,
+                base_x: f.base.0,
+                base_y: f.base.1,
+                status: f.status,
+            })
+            .collect()
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::game::logic::tests::maze_from;
+
+    const RED_BASE: (f32, f32) = (1.5, 1.5);
+    const BLUE_BASE: (f32, f32) = (8.5, 1.5);
+    const MIDFIELD: (f32, f32) = (5.5, 1.5);
+
+    // Red (team 0) and blue (team 1) bases at either end of a corridor.
+    fn flags() -> Flags {
+        let mut maze = maze_from(&["##########", "#........#", "##########"]);
+        maze.set_flag_base(0, 1, 1);
+        maze.set_flag_base(1, 8, 1);
+        Flags::new(&maze)
+    }
+
+    fn runner(player_id: u64, team: u8, pos: (f32, f32)) -> Runner {
+        Runner {
+            player_id,
+            team,
+            pos,
+            alive: true,
+        }
+    }
+
+    fn summary(events: &[FlagEvent]) -> Vec<(u8, Option<u64>, FlagEventKind)> {
+        events
+            .iter()
+            .map(|e| (e.team_id, e.player_id, e.kind))
+            .collect()
+    }
+
+    fn status(flags: &Flags, team: u8) -> (FlagStatus, (f32, f32)) {
+        let f = &flags.flags[team as usize];
+        (f.status, f.pos)
+    }
+
+    #[test]
+    fn only_the_enemy_flag_can_be_picked_up() {
+        let mut flags = flags();
+        let now = Instant::now();
+        // Standing on our own flag at base does nothing
+        assert!(flags.update(&[runner(2, 1, BLUE_BASE)], now).is_empty());
+        let events = flags.update(&[runner(1, 0, BLUE_BASE)], now);
+        assert_eq!(summary(&events), [(1, Some(1), FlagEventKind::PickedUp)]);
+        assert_eq!(status(&flags, 1).0, FlagStatus::Carried { player_id: 1 });
+
+        // The flag follows its carrier
+        flags.update(&[runner(1, 0, MIDFIELD)], now);
+        assert_eq!(status(&flags, 1).1, MIDFIELD);
+    }
+
+    #[test]
+    fn dying_drops_the_flag_where_the_carrier_fell() {
+        let mut flags = flags();
+        let now = Instant::now();
+        flags.update(&[runner(1, 0, BLUE_BASE)], now);
+        let dead = Runner {
+            alive: false,
+            ..runner(1, 0, MIDFIELD)
+        };
+        let events = flags.update(&[dead], now);
+        assert_eq!(summary(&events), [(1, Some(1), FlagEventKind::Dropped)]);
+        assert_eq!(status(&flags, 1), (FlagStatus::Dropped, MIDFIELD));
+    }
+
+    #[test]
+    fn touching_your_dropped_flag_returns_it() {
+        let mut flags = flags();
+        let now = Instant::now();
+        flags.update(&[runner(1, 0, BLUE_BASE)], now);
+        flags.update(&[runner(1, 0, MIDFIELD)], now);
+        flags.update(&[], now); // carrier left the game
+        let events = flags.update(&[runner(2, 1, MIDFIELD)], now);
+        assert_eq!(summary(&events), [(1, Some(2), FlagEventKind::Returned)]);
+        assert_eq!(status(&flags, 1), (FlagStatus::AtBase, BLUE_BASE));
+    }
+
+    #[test]
+    fn dropped_flag_returns_itself_after_a_while() {
+        let mut flags = flags();
+        let now = Instant::now();
+        flags.update(&[runner(1, 0, BLUE_BASE)], now);
+        flags.update(&[runner(1, 0, MIDFIELD)], now);
+        flags.update(&[], now);
+        let almost = now + AUTO_RETURN - Duration::from_millis(1);
+        assert!(flags.update(&[], almost).is_empty());
+        let events = flags.update(&[], now + AUTO_RETURN);
+        assert_eq!(summary(&events), [(1, None, FlagEventKind::Returned)]);
+        assert_eq!(status(&flags, 1), (FlagStatus::AtBase, BLUE_BASE));
+    }
+
+    #[test]
+    fn capture_needs_your_own_flag_at_home() {
+        let mut flags = flags();
+        let now = Instant::now();
+        // Both flags taken; red's is then dropped in midfield
+        flags.update(&[runner(1, 0, BLUE_BASE), runner(2, 1, RED_BASE)], now);
+        flags.update(&[runner(1, 0, MIDFIELD), runner(2, 1, MIDFIELD)], now);
+        let blue_dies = Runner {
+            alive: false,
+            ..runner(2, 1, (4.5, 1.5))
+        };
+        flags.update(&[runner(1, 0, MIDFIELD), blue_dies], now);
+        assert_eq!(status(&flags, 0).0, FlagStatus::Dropped);
+
+        // Red carrier reaches home, but the red flag isn't there
+        assert!(flags.update(&[runner(1, 0, RED_BASE)], now).is_empty());
+        assert_eq!(status(&flags, 1).0, FlagStatus::Carried { player_id: 1 });
+
+        // A teammate returns it; now the capture counts and blue's flag goes home
+        flags.update(&[runner(1, 0, RED_BASE), runner(3, 0, (4.5, 1.5))], now);
+        let events = flags.update(&[runner(1, 0, RED_BASE)], now);
+        assert_eq!(summary(&events), [(1, Some(1), FlagEventKind::Captured)]);
+        assert_eq!(status(&flags, 1), (FlagStatus::AtBase, BLUE_BASE));
+    }
+}
@@ -1,3 +1,7 @@
+use std::cell::RefCell;
+use std::collections::{BinaryHeap, HashMap};
+use std::sync::Arc;
+
 #[derive(Debug, Clone, Copy, PartialEq)]
 pub enum Cell {
     Wall,
@@ -6,6 +10,13 @@ pub enum Cell {
     Cover,      // New: cover objects for tactical gameplay
 }
 
+// Number of built-in levels served by `load_level`.
+pub const LEVEL_COUNT: u8 = 5;
+
+// Marks cells a distance field can't reach.
+pub const UNREACHED: u32 = u32::MAX;
+const NEIGHBORS: [(i32, i32); 4] = [(0, -1), (1, 0), (0, 1), (-1, 0)];
+
 #[derive(Debug)]
 pub struct Maze {
     // This struct stores the maze size and the actual grid.
@@ -16,6 +27,36 @@ pub struct Maze {
     pub name: String,
     pub description: String,
     // pub max_players: u8,
+    // Capture-the-flag base cell per team, indexed by team id
+    pub flag_bases: Vec<(usize, usize)>,
+    // Where items appear and what kind
+    pub pickup_spots: Vec<(protocol::PickupKind, (usize, usize))>,
+    // BFS distance fields keyed by source cell; cleared whenever the grid changes.
+    distance_cache: RefCell<HashMap<(usize, usize), Arc<DistanceField>>>,
+}
+
+/// Walking distance (in 4-connected steps) from one source cell to every cell.
+#[derive(Debug)]
+pub struct DistanceField {
+    pub width: usize,
+    pub height: usize,
+    dist: Vec<u32>,
+}
+
+impl DistanceField {
+    /// Steps from the source, or `None` for walls and cells in other regions.
+    pub fn get(&self, x: usize, y: usize) -> Option<u32> {
+        if x >= self.width || y >= self.height {
+            return None;
+        }
+        let d = self.dist[y * self.width + x];
+        (d != UNREACHED).then_some(d)
+    }
+
+    /// Raw distances in row-major order, `UNREACHED` where unreachable.
+    pub fn as_slice(&self) -> &[u32] {
+        &self.dist
+    }
 }
 
 /////////////////////////////////////////////////////////////////////////////////////////////////////
@@ -40,11 +81,15 @@ impl Maze {
             name,
             description,
             // max_players,
+            flag_bases: Vec::new(),
+            pickup_spots: Vec::new(),
+            distance_cache: RefCell::new(HashMap::new()),
         }
     }
 
     pub fn set_path(&mut self, x: usize, y: usize) {
         if x < self.width && y < self.height {
+            self.distance_cache.get_mut().clear();
             self.grid[y][x] = Cell::Path;
             //Turns a cell into a Path (walkable).
         }
@@ -52,6 +97,7 @@ impl Maze {
 
     pub fn set_spawn_point(&mut self, x: usize, y: usize) {
         if x < self.width && y < self.height {
+            self.distance_cache.get_mut().clear();
             self.grid[y][x] = Cell::SpawnPoint;
             // Marks a cell as a spawn point for FPS gameplay.
         }
@@ -59,11 +105,40 @@ impl Maze {
 
     pub fn set_cover(&mut self, x: usize, y: usize) {
         if x < self.width && y < self.height {
+            self.distance_cache.get_mut().clear();
             self.grid[y][x] = Cell::Cover;
             // Marks a cell as cover (walkable but provides protection).
         }
     }
 
+    // Walkable cell closest to (x, y), so level layouts can name a rough spot.
+    fn nearest_walkable(&self, x: usize, y: usize) -> Option<(usize, usize)> {
+        (0..self.height)
+            .flat_map(|cy| (0..self.width).map(move |cx| (cx, cy)))
+            .filter(|&(cx, cy)| self.is_walkable(cx, cy))
+            .min_by_key(|&(cx, cy)| cx.abs_diff(x).pow(2) + cy.abs_diff(y).pow(2))
+    }
+
+    /// Places `team`'s flag base on the walkable cell nearest to (x, y).
+    /// Call after carving the level.
+    pub fn set_flag_base(&mut self, team: u8, x: usize, y: usize) {
+        if let Some(cell) = self.nearest_walkable(x, y) {
+            let team = team as usize;
+            if self.flag_bases.len() <= team {
+                self.flag_bases.resize(team + 1, cell);
+            }
+            self.flag_bases[team] = cell;
+        }
+    }
+
+    /// Adds a pickup spot on the walkable cell nearest to (x, y).
+    /// Call after carving the level.
+    pub fn add_pickup(&mut self, kind: protocol::PickupKind, x: usize, y: usize) {
+        if let Some(cell) = self.nearest_walkable(x, y) {
+            self.pickup_spots.push((kind, cell));
+        }
+    }
+
     pub fn is_walkable(&self, x: usize, y: usize) -> bool {
         if x >= self.width || y >= self.height {
             return false;
@@ -87,7 +162,30 @@ impl Maze {
 
     ///////////////////////////////////////      levels            /////////////////////////////////////
 
+    /// Builds a level and repairs it if validation finds isolated pockets or
+    /// clustered spawns. Levels that stay broken are rejected in favour of level 1.
     pub fn load_level(level: u8) -> Self {
+        let mut maze = Maze::build_level(level);
+        if maze.validate().is_playable() {
+            return maze;
+        }
+        for action in maze.repair() {
+            println!("⚠️ Level {}: {}", level, action);
+        }
+        let report = maze.validate();
+        if !report.is_playable() {
+            println!(
+                "❌ Level {} rejected: {:?}; falling back to level 1",
+                level,
+                report.problems()
+            );
+            return Maze::level1_arena();
+        }
+        maze
+    }
+
+    /// Builds a level exactly as designed, without validation.
+    pub fn build_level(level: u8) -> Self {
         match level {
             1 => Maze::level1_arena(),
             2 => Maze::level2_corridors(),
@@ -157,6 +255,21 @@ impl Maze {
             maze.set_spawn_point(x, y);
         }
 
+        // Capture-the-flag bases on opposite sides
+        maze.set_flag_base(0, 1, 10);
+        maze.set_flag_base(1, 18, 10);
+
+        // Ammo pickups
+        maze.add_pickup(protocol::PickupKind::Ammo, 10, 3);
+        maze.add_pickup(protocol::PickupKind::Ammo, 10, 16);
+
+        // Health, armor and power-ups
+        maze.add_pickup(protocol::PickupKind::Health, 3, 3);
+        maze.add_pickup(protocol::PickupKind::Health, 16, 16);
+        maze.add_pickup(protocol::PickupKind::Armor, 10, 10);
+        maze.add_pickup(protocol::PickupKind::SpeedBoost, 3, 16);
+        maze.add_pickup(protocol::PickupKind::DamageBoost, 16, 3);
+
         maze
     }
 
@@ -225,6 +338,23 @@ impl Maze {
             maze.set_spawn_point(x, y);
         }
 
+        // Capture-the-flag bases on opposite sides
+        maze.set_flag_base(0, 1, 10);
+        maze.set_flag_base(1, 23, 10);
+
+        // Ammo pickups
+        maze.add_pickup(protocol::PickupKind::Ammo, 12, 5);
+        maze.add_pickup(protocol::PickupKind::Ammo, 12, 20);
+        maze.add_pickup(protocol::PickupKind::Ammo, 5, 12);
+        maze.add_pickup(protocol::PickupKind::Ammo, 20, 12);
+
+        // Health, armor and power-ups
+        maze.add_pickup(protocol::PickupKind::Health, 2, 2);
+        maze.add_pickup(protocol::PickupKind::Health, 22, 22);
+        maze.add_pickup(protocol::PickupKind::Armor, 12, 12);
+        maze.add_pickup(protocol::PickupKind::SpeedBoost, 2, 22);
+        maze.add_pickup(protocol::PickupKind::DamageBoost, 22, 2);
+
         maze
     }
 
@@ -318,6 +448,21 @@ impl Maze {
             }
         }
 
+        // Capture-the-flag bases on opposite sides
+        maze.set_flag_base(0, 2, 9);
+        maze.set_flag_base(1, 17, 9);
+
+        // Ammo pickups
+        maze.add_pickup(protocol::PickupKind::Ammo, 10, 3);
+        maze.add_pickup(protocol::PickupKind::Ammo, 10, 15);
+
+        // Health, armor and power-ups
+        maze.add_pickup(protocol::PickupKind::Health, 3, 3);
+        maze.add_pickup(protocol::PickupKind::Health, 16, 16);
+        maze.add_pickup(protocol::PickupKind::Armor, 10, 9);
+        maze.add_pickup(protocol::PickupKind::SpeedBoost, 3, 16);
+        maze.add_pickup(protocol::PickupKind::DamageBoost, 16, 3);
+
         maze
     }
 
@@ -364,6 +509,21 @@ impl Maze {
             }
         }
 
+        // Capture-the-flag bases on opposite sides
+        maze.set_flag_base(0, 1, 13);
+        maze.set_flag_base(1, 26, 13);
+
+        // Ammo pickups
+        maze.add_pickup(protocol::PickupKind::Ammo, 14, 9);
+        maze.add_pickup(protocol::PickupKind::Ammo, 14, 19);
+
+        // Health, armor and power-ups
+        maze.add_pickup(protocol::PickupKind::Health, 2, 2);
+        maze.add_pickup(protocol::PickupKind::Health, 25, 25);
+        maze.add_pickup(protocol::PickupKind::Armor, 14, 14);
+        maze.add_pickup(protocol::PickupKind::SpeedBoost, 2, 25);
+        maze.add_pickup(protocol::PickupKind::DamageBoost, 25, 2);
+
         maze
     }
 
@@ -556,6 +716,21 @@ impl Maze {
             }
         }
 
+        // Capture-the-flag bases on opposite sides
+        maze.set_flag_base(0, 1, 12);
+        maze.set_flag_base(1, 23, 12);
+
+        // Ammo pickups
+        maze.add_pickup(protocol::PickupKind::Ammo, 6, 12);
+        maze.add_pickup(protocol::PickupKind::Ammo, 18, 12);
+
+        // Health, armor and power-ups
+        maze.add_pickup(protocol::PickupKind::Health, 2, 2);
+        maze.add_pickup(protocol::PickupKind::Health, 22, 22);
+        maze.add_pickup(protocol::PickupKind::Armor, 12, 12);
+        maze.add_pickup(protocol::PickupKind::SpeedBoost, 2, 22);
+        maze.add_pickup(protocol::PickupKind::DamageBoost, 22, 2);
+
         maze
     }
 
@@ -600,6 +775,19 @@ impl Maze {
         }
     }
 
+    // Cells explicitly marked as spawn points, in scan order.
+    pub fn spawn_cells(&self) -> Vec<(usize, usize)> {
+        let mut out = Vec::new();
+        for y in 0..self.height {
+            for x in 0..self.width {
+                if self.grid[y][x] == Cell::SpawnPoint {
+                    out.push((x, y));
+                }
+            }
+        }
+        out
+    }
+
     pub fn spawn_points(&self, count: usize) -> Vec<(usize, usize)> {
         let mut pts = Vec::with_capacity(count);
         'outer: for y in 0..self.height {
@@ -658,4 +846,288 @@ impl Maze {
     //     }
     //     count
     // }
-}
\ No newline at end of file
+}
+
+/////////////////////////////////////////////////////////////////////////////////////////////////////
+///////////////////////////////////////      Navigation       ///////////////////////////////////////
+/////////////////////////////////////////////////////////////////////////////////////////////////////
+impl Maze {
+    /// Walkable 4-connected neighbours of a cell.
+    pub fn walkable_neighbors(
+        &self,
+        x: usize,
+        y: usize,
+    ) -> impl Iterator<Item = (usize, usize)> + '_ {
+        NEIGHBORS.iter().filter_map(move |&(dx, dy)| {
+            let nx = x as i32 + dx;
+            let ny = y as i32 + dy;
+            if nx < 0 || ny < 0 {
+                return None;
+            }
+            let (nx, ny) = (nx as usize, ny as usize);
+            self.is_walkable(nx, ny).then_some((nx, ny))
+        })
+    }
+
+    /// BFS distance field from `source`, computed once per source and cached
+    /// until the grid changes (i.e. for the lifetime of the loaded level).
+    pub fn distance_field(&self, source: (usize, usize)) -> Arc<DistanceField> {
+        if let Some(field) = self.distance_cache.borrow().get(&source) {
+            return Arc::clone(field);
+        }
+
+        let mut dist = vec![UNREACHED; self.width * self.height];
+        if self.is_walkable(source.0, source.1) {
+            dist[source.1 * self.width + source.0] = 0;
+            let mut queue = std::collections::VecDeque::from([source]);
+            while let Some((x, y)) = queue.pop_front() {
+                let d = dist[y * self.width + x];
+                for (nx, ny) in self.walkable_neighbors(x, y) {
+                    if dist[ny * self.width + nx] == UNREACHED {
+                        dist[ny * self.width + nx] = d + 1;
+                        queue.push_back((nx, ny));
+                    }
+                }
+            }
+        }
+
+        let field = Arc::new(DistanceField {
+            width: self.width,
+            height: self.height,
+            dist,
+        });
+        self.distance_cache
+            .borrow_mut()
+            .insert(source, Arc::clone(&field));
+        field
+    }
+
+    /// Shortest 4-connected path from `from` to `to` (both inclusive) using A*
+    /// with a Manhattan heuristic. `None` if either end is blocked or unreachable.
+    pub fn find_path(
+        &self,
+        from: (usize, usize),
+        to: (usize, usize),
+    ) -> Option<Vec<(usize, usize)>> {
+        if !self.is_walkable(from.0, from.1) || !self.is_walkable(to.0, to.1) {
+            return None;
+        }
+        let idx = |(x, y): (usize, usize)| y * self.width + x;
+        let heuristic = |(x, y): (usize, usize)| (x.abs_diff(to.0) + y.abs_diff(to.1)) as u32;
+
+        let mut g = vec![UNREACHED; self.width * self.height];
+        let mut came_from: Vec<Option<(usize, usize)>> = vec![None; self.width * self.height];
+        // Max-heap on Reverse((f, g)) pops the lowest f, preferring deeper nodes on ties.
+        let mut open = BinaryHeap::new();
+        g[idx(from)] = 0;
+        open.push(std::cmp::Reverse((heuristic(from), 0u32, from)));
+
+        while let Some(std::cmp::Reverse((_, cost, cur))) = open.pop() {
+            if cur == to {
+                let mut path = vec![cur];
+                let mut at = cur;
+                while let Some(prev) = came_from[idx(at)] {
+                    path.push(prev);
+                    at = prev;
+                }
+                path.reverse();
+                return Some(path);
+            }
+            if cost > g[idx(cur)] {
+                continue; // stale heap entry
+            }
+            for next in self.walkable_neighbors(cur.0, cur.1) {
+                let next_cost = cost + 1;
+                if next_cost < g[idx(next)] {
+                    g[idx(next)] = next_cost;
+                    came_from[idx(next)] = Some(cur);
+                    open.push(std::cmp::Reverse((
+                        next_cost + heuristic(next),
+                        next_cost,
+                        next,
+                    )));
+                }
+            }
+        }
+        None
+    }
+
+    /// True if the straight segment between two world positions crosses only
+    /// walkable cells. Walks the grid with DDA, visiting every cell the segment touches.
+    pub fn line_of_sight(&self, from: (f32, f32), to: (f32, f32)) -> bool {
+        let walkable =
+            |x: i32, y: i32| x >= 0 && y >= 0 && self.is_walkable(x as usize, y as usize);
+
+        let (dx, dy) = (to.0 - from.0, to.1 - from.1);
+        let mut map_x = from.0.floor() as i32;
+        let mut map_y = from.1.floor() as i32;
+        let end_x = to.0.floor() as i32;
+        let end_y = to.1.floor() as i32;
+        let step_x = if dx < 0.0 { -1 } else { 1 };
+        let step_y = if dy < 0.0 { -1 } else { 1 };
+
+        // Distances are in segment-parameter space: t = 0 at `from`, t = 1 at `to`.
+        let delta_x = if dx == 0.0 {
+            f32::INFINITY
+        } else {
+            (1.0 / dx).abs()
+        };
+        let delta_y = if dy == 0.0 {
+            f32::INFINITY
+        } else {
+            (1.0 / dy).abs()
+        };
+        let mut side_x = if dx == 0.0 {
+            f32::INFINITY
+        } else if dx < 0.0 {
+            (from.0 - map_x as f32) * delta_x
+        } else {
+            (map_x as f32 + 1.0 - from.0) * delta_x
+        };
+        let mut side_y = if dy == 0.0 {
+            f32::INFINITY
+        } else if dy < 0.0 {
+            (from.1 - map_y as f32) * delta_y
+        } else {
+            (map_y as f32 + 1.0 - from.1) * delta_y
+        };
+
+        loop {
+            if !walkable(map_x, map_y) {
+                return false;
+            }
+            if (map_x == end_x && map_y == end_y) || side_x.min(side_y) > 1.0 {
+                return true;
+            }
+            if side_x < side_y {
+                side_x += delta_x;
+                map_x += step_x;
+            } else {
+                side_y += delta_y;
+                map_y += step_y;
+            }
+        }
+    }
+}
+
+/// Straight-line distance between two world positions, in cells.
+pub(crate) fn distance(a: (f32, f32), b: (f32, f32)) -> f32 {
+    ((a.0 - b.0).powi(2) + (a.1 - b.1).powi(2)).sqrt()
+}
+
+#[cfg(test)]
+pub(crate) mod tests {
+    use super::*;
+
+    // Parses rows of '#' (wall), '.' (path) and 'S' (spawn) into a maze.
+    pub(crate) fn maze_from(rows: &[&str]) -> Maze {
+        let mut maze = Maze::new(
+            rows[0].len(),
+            rows.len(),
+            0,
+            "test".to_string(),
+            String::new(),
+            0,
+        );
+        for (y, row) in rows.iter().enumerate() {
+            for (x, c) in row.chars().enumerate() {
+                match c {
+                    '.' => maze.set_path(x, y),
+                    'S' => maze.set_spawn_point(x, y),
+                    _ => {}
+                }
+            }
+        }
+        maze
+    }
+
+    #[test]
+    fn distance_field_counts_steps_around_walls() {
+        let maze = maze_from(&[
+            "#####", //
+            "#...#", //
+            "###.#", //
+            "#...#", //
+            "#####",
+        ]);
+        let field = maze.distance_field((1, 1));
+        assert_eq!(field.get(1, 1), Some(0));
+        assert_eq!(field.get(3, 1), Some(2));
+        assert_eq!(field.get(1, 3), Some(6));
+        assert_eq!(field.get(0, 0), None);
+        assert_eq!(field.get(99, 99), None);
+    }
+
+    #[test]
+    fn distance_field_is_cached_until_grid_changes() {
+        let mut maze = maze_from(&["#####", "#...#", "#####"]);
+        let a = maze.distance_field((1, 1));
+        let b = maze.distance_field((1, 1));
+        assert!(Arc::ptr_eq(&a, &b));
+
+        maze.set_path(4, 1);
+        let c = maze.distance_field((1, 1));
+        assert!(!Arc::ptr_eq(&a, &c));
+        assert_eq!(c.get(4, 1), Some(3));
+    }
+
+    #[test]
+    fn find_path_returns_shortest_route() {
+        let maze = maze_from(&[
+            "#######", //
+            "#.....#", //
+            "#.###.#", //
+            "#.....#", //
+            "#######",
+        ]);
+        let path = maze.find_path((1, 1), (5, 3)).expect("path exists");
+        assert_eq!(path.first(), Some(&(1, 1)));
+        assert_eq!(path.last(), Some(&(5, 3)));
+        assert_eq!(
+            path.len() as u32 - 1,
+            maze.distance_field((1, 1)).get(5, 3).unwrap()
+        );
+        for pair in path.windows(2) {
+            let (a, b) = (pair[0], pair[1]);
+            assert_eq!(a.0.abs_diff(b.0) + a.1.abs_diff(b.1), 1);
+            assert!(maze.is_walkable(b.0, b.1));
+        }
+    }
+
+    #[test]
+    fn find_path_fails_for_blocked_or_disconnected_cells() {
+        let maze = maze_from(&["#####", "#.#.#", "#####"]);
+        assert_eq!(maze.find_path((1, 1), (3, 1)), None);
+        assert_eq!(maze.find_path((0, 0), (1, 1)), None);
+        assert_eq!(maze.find_path((1, 1), (1, 1)), Some(vec![(1, 1)]));
+    }
+
+    #[test]
+    fn line_of_sight_is_blocked_by_walls() {
+        let maze = maze_from(&[
+            "#####", //
+            "#...#", //
+            "#.#.#", //
+            "#...#", //
+            "#####",
+        ]);
+        assert!(maze.line_of_sight((1.5, 1.5), (3.5, 1.5)));
+        assert!(maze.line_of_sight((1.5, 1.5), (1.5, 3.5)));
+        assert!(!maze.line_of_sight((1.5, 1.5), (3.5, 3.5)));
+        assert!(!maze.line_of_sight((1.5, 2.5), (3.5, 2.5)));
+        assert!(maze.line_of_sight((2.5, 1.5), (2.5, 1.5)));
+    }
+
+    #[test]
+    fn line_of_sight_catches_walls_clipped_by_shallow_diagonals() {
+        let maze = maze_from(&[
+            "######", //
+            "#....#", //
+            "#...##", //
+            "######",
+        ]);
+        // The segment passes through (4, 2) on its way down-right.
+        assert!(!maze.line_of_sight((1.5, 1.2), (4.5, 2.9)));
+        assert!(maze.line_of_sight((1.5, 1.2), (3.5, 2.5)));
+    }
+}
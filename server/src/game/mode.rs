@@ -0,0 +1,421 @@
+use protocol::{GameMode, MatchPhase, MatchWinner};
+use std::time::{Duration, Instant};
+
+// Players (humans or bots) needed before the warmup countdown starts.
+const MIN_PLAYERS: usize = 2;
+// Pause between a match ending and the next warmup.
+const INTERMISSION: Duration = Duration::from_secs(10);
+// Number of teams in team modes.
+pub const TEAM_COUNT: usize = 2;
+
+/// Match rules chosen on the command line.
+#[derive(Debug, Clone)]
+pub struct MatchSettings {
+    pub mode: GameMode,
+    pub frag_limit: Option<u32>,
+    pub capture_limit: Option<u32>,
+    pub time_limit: Option<Duration>,
+    pub warmup: Duration,
+    pub enemy_radar: protocol::RadarMode,
+}
+
+impl MatchSettings {
+    pub fn parse_mode(s: &str) -> Option<GameMode> {
+        match s.to_ascii_lowercase().as_str() {
+            "dm" | "deathmatch" => Some(GameMode::Deathmatch),
+            "tdm" | "team" => Some(GameMode::TeamDeathmatch),
+            "lms" | "last-man-standing" => Some(GameMode::LastManStanding),
+            "ctf" | "capture-the-flag" => Some(GameMode::CaptureTheFlag),
+            _ => None,
+        }
+    }
+
+    pub fn parse_radar(s: &str) -> Option<protocol::RadarMode> {
+        match s.to_ascii_lowercase().as_str() {
+            "off" => Some(protocol::RadarMode::Off),
+            "visible" => Some(protocol::RadarMode::Visible),
+            "all" => Some(protocol::RadarMode::All),
+            _ => None,
+        }
+    }
+
+    pub fn is_team_mode(&self) -> bool {
+        matches!(
+            self.mode,
+            GameMode::TeamDeathmatch | GameMode::CaptureTheFlag
+        )
+    }
+}
+
+/// What the match needs to know about each player to decide the outcome.
+#[derive(Debug, Clone)]
+pub struct Standing {
+    pub player_id: u64,
+    pub username: String,
+    pub kills: u32,
+    pub alive: bool,
+}
+
+/// Phase changes the server must act on.
+#[derive(Debug, Clone, PartialEq)]
+pub enum Transition {
+    Started,                    // warmup over: reset stats, respawn everyone
+    Ended(MatchWinner, String), // winner and reason
+    Warmup,                     // intermission over: revive everyone for the next warmup
+}
+
+/// Warmup -> Active -> Ended -> Warmup round lifecycle for the selected mode.
+#[derive(Debug)]
+pub struct Match {
+    pub settings: MatchSettings,
+    pub phase: MatchPhase,
+    phase_started: Instant,
+    pub team_scores: [u32; TEAM_COUNT], // kills, or captures in CTF
+}
+
+impl Match {
+    pub fn new(settings: MatchSettings) -> Self {
+        Self {
+            settings,
+            phase: MatchPhase::Warmup,
+            phase_started: Instant::now(),
+            team_scores: [0; TEAM_COUNT],
+        }
+    }
+
+    /// Kills only count toward scores while the match is live.
+    pub fn counts_kills(&self) -> bool {
+        self.phase == MatchPhase::Active
+    }
+
+    /// In last-man-standing, players who die stay dead until the round ends.
+    pub fn respawns_allowed(&self) -> bool {
+        !(self.settings.mode == GameMode::LastManStanding && self.phase == MatchPhase::Active)
+    }
+
+    /// Credits a kill to the killer's team; team kills don't score, and in
+    /// CTF only captures do.
+    pub fn record_kill(&mut self, killer_team: Option<u8>, victim_team: Option<u8>) {
+        if !self.counts_kills() || self.settings.mode != GameMode::TeamDeathmatch {
+            return;
+        }
+        if let Some(team) = killer_team
+            && killer_team != victim_team
+        {
+            self.team_scores[team as usize % TEAM_COUNT] += 1;
+        }
+    }
+
+    /// Credits a flag capture to `team`.
+    pub fn record_capture(&mut self, team: u8) {
+        if self.counts_kills() {
+            self.team_scores[team as usize % TEAM_COUNT] += 1;
+        }
+    }
+
+    fn phase_length(&self) -> Option<Duration> {
+        match self.phase {
+            MatchPhase::Warmup => Some(self.settings.warmup),
+            MatchPhase::Active => self.settings.time_limit,
+            MatchPhase::Ended => Some(INTERMISSION),
+        }
+    }
+
+    pub fn time_remaining(&self, now: Instant) -> Option<Duration> {
+        self.phase_length()
+            .map(|len| len.saturating_sub(now.duration_since(self.phase_started)))
+    }
+
+    fn enter(&mut self, phase: MatchPhase, now: Instant) {
+        self.phase = phase;
+        self.phase_started = now;
+        if phase == MatchPhase::Active {
+            self.team_scores = [0; TEAM_COUNT];
+        }
+    }
+
+    /// Advances the lifecycle; returns the transition that happened, if any.
+    pub fn tick(&mut self, now: Instant, standings: &[Standing]) -> Option<Transition> {
+        let timed_out = self.time_remaining(now).is_some_and(|t| t.is_zero());
+        match self.phase {
+            MatchPhase::Warmup => {
+                if standings.len() < MIN_PLAYERS {
+                    // Hold the countdown until enough players are around
+                    self.phase_started = now;
+                    None
+                } else if timed_out {
+                    self.enter(MatchPhase::Active, now);
+                    Some(Transition::Started)
+                } else {
+                    None
+                }
+            }
+            MatchPhase::Active => {
+                let outcome = self.check_limits(standings).or_else(|| {
+                    timed_out.then(|| (self.leader(standings), "time limit reached".to_string()))
+                });
+                outcome.map(|(winner, reason)| {
+                    self.enter(MatchPhase::Ended, now);
+                    Transition::Ended(winner, reason)
+                })
+            }
+            MatchPhase::Ended => timed_out.then(|| {
+                self.enter(MatchPhase::Warmup, now);
+                Transition::Warmup
+            }),
+        }
+    }
+
+    // Frag-limit or elimination end conditions.
+    fn check_limits(&self, standings: &[Standing]) -> Option<(MatchWinner, String)> {
+        match self.settings.mode {
+            GameMode::Deathmatch => {
+                let limit = self.settings.frag_limit?;
+                standings
+                    .iter()
+                    .any(|s| s.kills >= limit)
+                    .then(|| (self.leader(standings), "frag limit reached".to_string()))
+            }
+            GameMode::TeamDeathmatch => {
+                let limit = self.settings.frag_limit?;
+                self.team_scores
+                    .iter()
+                    .any(|&k| k >= limit)
+                    .then(|| (self.leader(standings), "frag limit reached".to_string()))
+            }
+            GameMode::CaptureTheFlag => {
+                let limit = self.settings.capture_limit?;
+                self.team_scores
+                    .iter()
+                    .any(|&k| k >= limit)
+                    .then(|| (self.leader(standings), "capture limit reached".to_string()))
+            }
+            GameMode::LastManStanding => {
+                let alive = standings.iter().filter(|s| s.alive).count();
+                (alive <= 1).then(|| (self.leader(standings), "last player standing".to_string()))
+            }
+        }
+    }
+
+    // Current leader under the mode's rules; ties are a draw.
+    fn leader(&self, standings: &[Standing]) -> MatchWinner {
+        if self.settings.is_team_mode() {
+            let best = *self.team_scores.iter().max().unwrap_or(&0);
+            let leaders: Vec<usize> = (0..TEAM_COUNT)
+                .filter(|&t| self.team_scores[t] == best)
+                .collect();
+            return match leaders.as_slice() {
+                [team] => MatchWinner::Team {
+                    team_id: *team as u8,
+                },
+                _ => MatchWinner::Draw,
+            };
+        }
+
+        // In last-man-standing only survivors can win
+        let eligible: Vec<&Standing> = if self.settings.mode == GameMode::LastManStanding {
+            standings.iter().filter(|s| s.alive).collect()
+        } else {
+            standings.iter().collect()
+        };
+        let best = eligible.iter().map(|s| s.kills).max();
+        let leaders: Vec<&&Standing> = eligible.iter().filter(|s| Some(s.kills) == best).collect();
+        match leaders.as_slice() {
+            [s] => MatchWinner::Player {
+                player_id: s.player_id,
+                username: s.username.clone(),
+            },
+            _ => MatchWinner::Draw,
+        }
+    }
+
+    pub fn to_wire(&self, now: Instant) -> protocol::MatchStateUpdate {
+        protocol::MatchStateUpdate {
+            mode: self.settings.mode,
+            phase: self.phase,
+            time_remaining_ms: self.time_remaining(now).map(|t| t.as_millis() as u64),
+            frag_limit: self.settings.frag_limit,
+            capture_limit: if self.settings.mode == GameMode::CaptureTheFlag {
+                self.settings.capture_limit
+            } else {
+                None
+            },
+            team_scores: if self.settings.is_team_mode() {
+                self.team_scores.to_vec()
+            } else {
+                Vec::new()
+            },
+            enemy_radar: self.settings.enemy_radar,
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn settings(mode: GameMode) -> MatchSettings {
+        MatchSettings {
+            mode,
+            frag_limit: None,
+            capture_limit: None,
+            time_limit: None,
+            warmup: Duration::from_secs(5),
+            enemy_radar: protocol::RadarMode::All,
+        }
+    }
+
+    fn standing(player_id: u64, kills: u32, alive: bool) -> Standing {
+        Standing {
+            player_id,
+            username: format!("p{}", player_id),
+            kills,
+            alive,
+        }
+    }
+
+    fn winner(player_id: u64) -> MatchWinner {
+        MatchWinner::Player {
+            player_id,
+            username: format!("p{}", player_id),
+        }
+    }
+
+    // A match that has just gone live at the returned instant.
+    fn started(mut settings: MatchSettings, players: &[Standing]) -> (Match, Instant) {
+        settings.warmup = Duration::ZERO;
+        let mut m = Match::new(settings);
+        let now = Instant::now();
+        assert_eq!(m.tick(now, players), Some(Transition::Started));
+        (m, now)
+    }
+
+    #[test]
+    fn warmup_waits_for_enough_players() {
+        let mut m = Match::new(settings(GameMode::Deathmatch));
+        let t0 = Instant::now();
+        let alone = [standing(1, 0, true)];
+        assert_eq!(m.tick(t0 + Duration::from_secs(30), &alone), None);
+        assert_eq!(m.phase, MatchPhase::Warmup);
+
+        // The countdown only starts once MIN_PLAYERS are in
+        let pair = [standing(1, 0, true), standing(2, 0, true)];
+        let joined = t0 + Duration::from_secs(30);
+        assert_eq!(m.tick(joined + Duration::from_secs(4), &pair), None);
+        assert_eq!(
+            m.tick(joined + Duration::from_secs(5), &pair),
+            Some(Transition::Started)
+        );
+        assert_eq!(m.phase, MatchPhase::Active);
+        assert!(m.counts_kills());
+    }
+
+    #[test]
+    fn frag_limit_ends_the_match() {
+        let mut rules = settings(GameMode::Deathmatch);
+        rules.frag_limit = Some(3);
+        let (mut m, t0) = started(rules, &[standing(1, 0, true), standing(2, 0, true)]);
+        assert_eq!(
+            m.tick(t0, &[standing(1, 2, true), standing(2, 1, true)]),
+            None
+        );
+        assert_eq!(
+            m.tick(t0, &[standing(1, 2, true), standing(2, 3, true)]),
+            Some(Transition::Ended(
+                winner(2),
+                "frag limit reached".to_string()
+            ))
+        );
+        assert_eq!(m.phase, MatchPhase::Ended);
+        assert!(!m.counts_kills());
+    }
+
+    #[test]
+    fn time_limit_ends_the_match_with_the_leader_or_a_draw() {
+        let mut rules = settings(GameMode::Deathmatch);
+        rules.time_limit = Some(Duration::from_secs(60));
+        let players = [standing(1, 4, true), standing(2, 7, true)];
+        let (mut m, t0) = started(rules.clone(), &players);
+        assert_eq!(m.tick(t0 + Duration::from_secs(59), &players), None);
+        assert_eq!(
+            m.tick(t0 + Duration::from_secs(60), &players),
+            Some(Transition::Ended(
+                winner(2),
+                "time limit reached".to_string()
+            ))
+        );
+
+        let tied = [standing(1, 5, true), standing(2, 5, true)];
+        let (mut m, t0) = started(rules, &tied);
+        assert_eq!(
+            m.tick(t0 + Duration::from_secs(60), &tied),
+            Some(Transition::Ended(
+                MatchWinner::Draw,
+                "time limit reached".to_string()
+            ))
+        );
+    }
+
+    #[test]
+    fn intermission_leads_back_to_warmup() {
+        let mut rules = settings(GameMode::TeamDeathmatch);
+        rules.frag_limit = Some(1);
+        let players = [standing(1, 0, true), standing(2, 0, true)];
+        let (mut m, t0) = started(rules, &players);
+        m.record_kill(Some(1), Some(0));
+        assert!(matches!(
+            m.tick(t0, &players),
+            Some(Transition::Ended(MatchWinner::Team { team_id: 1 }, _))
+        ));
+
+        assert_eq!(
+            m.tick(t0 + INTERMISSION - Duration::from_millis(1), &players),
+            None
+        );
+        assert_eq!(
+            m.tick(t0 + INTERMISSION, &players),
+            Some(Transition::Warmup)
+        );
+        assert_eq!(m.phase, MatchPhase::Warmup);
+        // Team scores carry over until the next match starts
+        assert_eq!(m.team_scores, [0, 1]);
+        assert_eq!(
+            m.tick(t0 + INTERMISSION, &players), // no warmup in these tests
+            Some(Transition::Started)
+        );
+        assert_eq!(m.team_scores, [0, 0]);
+    }
+
+    #[test]
+    fn last_man_standing_goes_to_the_survivor() {
+        let players = [
+            standing(1, 0, true),
+            standing(2, 0, true),
+            standing(3, 0, true),
+        ];
+        let (mut m, t0) = started(settings(GameMode::LastManStanding), &players);
+        assert!(!m.respawns_allowed());
+
+        let two_left = [
+            standing(1, 3, false),
+            standing(2, 0, true),
+            standing(3, 1, true),
+        ];
+        assert_eq!(m.tick(t0, &two_left), None);
+
+        // Player 1 has the most kills but died; the survivor wins
+        let one_left = [
+            standing(1, 3, false),
+            standing(2, 0, false),
+            standing(3, 1, true),
+        ];
+        assert_eq!(
+            m.tick(t0, &one_left),
+            Some(Transition::Ended(
+                winner(3),
+                "last player standing".to_string()
+            ))
+        );
+        assert!(m.respawns_allowed());
+    }
+}
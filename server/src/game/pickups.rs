@@ -0,0 +1,128 @@
+use super::logic::{Maze, distance};
+use protocol::{PickupKind, PickupState};
+use std::time::{Duration, Instant};
+
+// How close (in cells) a player must get to grab an item.
+const TOUCH_RADIUS: f32 = 0.6;
+
+// How long a spot stays empty after someone takes its item.
+fn respawn_time(kind: PickupKind) -> Duration {
+    match kind {
+        PickupKind::Ammo => Duration::from_secs(20),
+        PickupKind::Health => Duration::from_secs(15),
+        PickupKind::Armor => Duration::from_secs(25),
+        PickupKind::SpeedBoost => Duration::from_secs(30),
+        PickupKind::DamageBoost => Duration::from_secs(45),
+    }
+}
+
+#[derive(Debug)]
+struct Pickup {
+    id: u32,
+    kind: PickupKind,
+    pos: (f32, f32),
+    respawn_at: Option<Instant>, // Some while taken
+}
+
+/// Items placed at the maze's pickup spots. The server decides what touching
+/// one does; this only tracks which spots are stocked and when empty ones refill.
+#[derive(Debug)]
+pub struct Pickups {
+    items: Vec<Pickup>,
+}
+
+impl Pickups {
+    pub fn new(maze: &Maze) -> Self {
+        let items = maze
+            .pickup_spots
+            .iter()
+            .enumerate()
+            .map(|(i, &(kind, (x, y)))| Pickup {
+                id: i as u32,
+                kind,
+                pos: (x as f32 + 0.5, y as f32 + 0.5),
+                respawn_at: None,
+            })
+            .collect();
+        Self { items }
+    }
+
+    /// Restocks spots whose respawn timer has run out.
+    pub fn update(&mut self, now: Instant) {
+        for item in &mut self.items {
+            if item.respawn_at.is_some_and(|at| now >= at) {
+                item.respawn_at = None;
+            }
+        }
+    }
+
+    /// Stocked items within reach of `pos`, as (id, kind).
+    pub fn touching(&self, pos: (f32, f32)) -> Vec<(u32, PickupKind)> {
+        self.items
+            .iter()
+            .filter(|i| i.respawn_at.is_none() && distance(i.pos, pos) <= TOUCH_RADIUS)
+            .map(|i| (i.id, i.kind))
+            .collect()
+    }
+
+    /// Empties a spot and starts its respawn timer.
+    pub fn take(&mut self, id: u32, now: Instant) {
+        if let Some(item) = self.items.iter_mut().find(|i| i.id == id) {
+            item.respawn_at = Some(now + respawn_time(item.kind));
+        }
+    }
+
+    /// Closest stocked item of `kind` as the crow flies (for bots).
+    pub fn nearest(&self, kind: PickupKind, pos: (f32, f32)) -> Option<(f32, f32)> {
+        self.items
+            .iter()
+            .filter(|i| i.kind == kind && i.respawn_at.is_none())
+            .map(|i| i.pos)
+            .min_by(|a, b| distance(*a, pos).total_cmp(&distance(*b, pos)))
+    }
+
+    pub fn to_wire(&self) -> Vec<PickupState> {
+        self.items
+            .iter()
+            .map(|i| PickupState {
+                pickup_id: i.id,
+                kind: i.kind,
+                x: i.pos.0,
+                y: i.pos.1,
+                available: i.respawn_at.is_none(),
+            })
+            .collect()
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::game::logic::tests::maze_from;
+
+    #[test]
+    fn taken_items_come_back_after_their_respawn_time() {
+        let mut maze = maze_from(&["#####", "#...#", "#####"]);
+        maze.add_pickup(PickupKind::Health, 1, 1);
+        maze.add_pickup(PickupKind::Ammo, 3, 1);
+        let mut pickups = Pickups::new(&maze);
+        let t0 = Instant::now();
+
+        assert_eq!(pickups.touching((1.4, 1.5)), vec![(0, PickupKind::Health)]);
+        assert!(pickups.touching((2.5, 1.5)).is_empty()); // out of reach of both
+        pickups.take(0, t0);
+        assert!(pickups.touching((1.5, 1.5)).is_empty());
+        assert!(!pickups.to_wire()[0].available && pickups.to_wire()[1].available);
+        assert_eq!(pickups.nearest(PickupKind::Health, (3.5, 1.5)), None);
+
+        let respawn = respawn_time(PickupKind::Health);
+        pickups.update(t0 + respawn - Duration::from_millis(1));
+        assert!(pickups.touching((1.5, 1.5)).is_empty());
+        pickups.update(t0 + respawn);
+        assert_eq!(pickups.touching((1.5, 1.5)), vec![(0, PickupKind::Health)]);
+        assert_eq!(
+            pickups.nearest(PickupKind::Health, (3.5, 1.5)),
+            Some((1.5, 1.5))
+        );
+    }
+}
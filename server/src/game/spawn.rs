@@ -0,0 +1,178 @@
+use super::logic::{Maze, distance};
+use std::time::{Duration, Instant};
+
+// How long players are immune to damage after spawning.
+pub const SPAWN_PROTECTION: Duration = Duration::from_secs(2);
+
+// Enemies farther than this (in cells) don't make a spawn any safer.
+const MAX_USEFUL_DISTANCE: f32 = 12.0;
+// Score penalties, in "cells of distance" so they weigh against the above.
+const LINE_OF_SIGHT_PENALTY: f32 = 8.0;
+const RECENT_USE_PENALTY: f32 = 6.0;
+const RECENT_DEATH_PENALTY: f32 = 4.0;
+// Radius around a recent death that counts as a hot zone.
+const DEATH_ZONE_RADIUS: f32 = 4.0;
+// Window over which recent use and recent deaths decay to nothing.
+const MEMORY: Duration = Duration::from_secs(10);
+
+/// Picks spawn points away from living enemies, out of their line of sight,
+/// and avoiding points that were just used or where someone just died.
+#[derive(Debug)]
+pub struct SpawnSelector {
+    points: Vec<(usize, usize)>,
+    last_used: Vec<Option<Instant>>,
+    recent_deaths: Vec<((f32, f32), Instant)>,
+}
+
+impl SpawnSelector {
+    pub fn new(maze: &Maze) -> Self {
+        let mut points = maze.spawn_cells();
+        if points.is_empty() {
+            points = maze.spawn_points(16);
+        }
+        if points.is_empty() {
+            // fall back to a safe-ish default
+            points.push((1, 1));
+        }
+        let last_used = vec![None; points.len()];
+        Self {
+            points,
+            last_used,
+            recent_deaths: Vec::new(),
+        }
+    }
+
+    /// Remembers where a player died so nobody spawns into the same fight.
+    pub fn note_death(&mut self, pos: (f32, f32), now: Instant) {
+        self.recent_deaths.push((pos, now));
+    }
+
+    /// Returns the best-scoring spawn (tile center) given the living enemies' positions.
+    pub fn pick(&mut self, maze: &Maze, enemies: &[(f32, f32)], now: Instant) -> (f32, f32) {
+        self.recent_deaths
+            .retain(|(_, at)| now.duration_since(*at) < MEMORY);
+
+        let mut best = 0;
+        let mut best_score = f32::NEG_INFINITY;
+        for (i, &(x, y)) in self.points.iter().enumerate() {
+            let pos = (x as f32 + 0.5, y as f32 + 0.5);
+            let score = self.score(maze, i, pos, enemies, now) + rand::random::<f32>() * 0.5;
+            if score > best_score {
+                best_score = score;
+                best = i;
+            }
+        }
+
+        self.last_used[best] = Some(now);
+        let (x, y) = self.points[best];
+        (x as f32 + 0.5, y as f32 + 0.5)
+    }
+
+    fn score(
+        &self,
+        maze: &Maze,
+        index: usize,
+        pos: (f32, f32),
+        enemies: &[(f32, f32)],
+        now: Instant,
+    ) -> f32 {
+        let nearest_enemy = enemies
+            .iter()
+            .map(|&e| distance(pos, e))
+            .fold(MAX_USEFUL_DISTANCE, f32::min);
+        let visible = enemies
+            .iter()
+            .filter(|&&e| maze.line_of_sight(pos, e))
+            .count() as f32;
+        let recently_used = self.last_used[index]
+            .map(|at| recency(now, at))
+            .unwrap_or(0.0);
+        let death_heat: f32 = self
+            .recent_deaths
+            .iter()
+            .filter(|(d, _)| distance(pos, *d) < DEATH_ZONE_RADIUS)
+            .map(|(_, at)| recency(now, *at))
+            .sum();
+
+        nearest_enemy
+            - LINE_OF_SIGHT_PENALTY * visible
+            - RECENT_USE_PENALTY * recently_used
+            - RECENT_DEATH_PENALTY * death_heat
+    }
+}
+
+// 1.0 right after `at`, fading linearly to 0.0 once MEMORY has passed.
+fn recency(now: Instant, at: Instant) -> f32 {
+    1.0 - (now.duration_since(at).as_secs_f32() / MEMORY.as_secs_f32()).min(1.0)
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::game::logic::tests::maze_from;
+
+    // Two spawns 8 cells apart in an open corridor.
+    fn corridor() -> Maze {
+        maze_from(&["###########", "#S.......S#", "###########"])
+    }
+
+    #[test]
+    fn hidden_spawn_beats_one_in_enemy_sight() {
+        let maze = maze_from(&[
+            "######", //
+            "#S...#", //
+            "####.#", //
+            "#S...#", //
+            "######",
+        ]);
+        // The enemy is about as close to both, but only sees the top one
+        let enemy = [(4.5, 1.5)];
+        for _ in 0..20 {
+            let mut spawns = SpawnSelector::new(&maze);
+            assert_eq!(spawns.pick(&maze, &enemy, Instant::now()), (1.5, 3.5));
+        }
+    }
+
+    #[test]
+    fn recently_used_spawn_waits_until_memory_expires() {
+        let maze = corridor();
+        let now = Instant::now();
+        let mut spawns = SpawnSelector::new(&maze);
+        let first = spawns.pick(&maze, &[], now);
+        let second = spawns.pick(&maze, &[], now + Duration::from_secs(1));
+        assert_ne!(first, second);
+
+        let used = spawns
+            .points
+            .iter()
+            .position(|&(x, _)| x as f32 + 0.5 == first.0)
+            .unwrap();
+        let later = now + MEMORY;
+        assert!(
+            spawns.score(&maze, used, first, &[], now + Duration::from_secs(5))
+                < MAX_USEFUL_DISTANCE
+        );
+        assert_eq!(
+            spawns.score(&maze, used, first, &[], later),
+            MAX_USEFUL_DISTANCE
+        );
+    }
+
+    #[test]
+    fn death_heat_steers_spawns_away_until_memory_expires() {
+        let maze = corridor();
+        let now = Instant::now();
+        for _ in 0..20 {
+            let mut spawns = SpawnSelector::new(&maze);
+            spawns.note_death((2.0, 1.5), now);
+            assert_eq!(spawns.pick(&maze, &[], now), (9.5, 1.5));
+        }
+
+        let mut spawns = SpawnSelector::new(&maze);
+        spawns.note_death((2.0, 1.5), now);
+        let cooled = spawns.score(&maze, 0, (1.5, 1.5), &[], now + MEMORY);
+        assert_eq!(cooled, MAX_USEFUL_DISTANCE);
+        spawns.pick(&maze, &[], now + MEMORY); // forgets the death
+        assert!(spawns.recent_deaths.is_empty());
+    }
+}
@@ -0,0 +1,386 @@
+use super::logic::{Cell, LEVEL_COUNT, Maze, UNREACHED};
+use std::collections::VecDeque;
+
+// Spawns closer than this (in walking steps) count as clustered.
+pub const MIN_SPAWN_SEPARATION: u32 = 4;
+
+type Tile = (usize, usize);
+
+/// Per-spawn fairness numbers, measured in walking steps.
+#[derive(Debug, Clone)]
+pub struct SpawnStats {
+    pub pos: Tile,
+    pub reachable: bool, // inside the largest walkable region
+    pub nearest_spawn: Option<u32>,
+    pub mean_spawn_distance: Option<f32>,
+}
+
+/// Result of a validation pass over a `Maze`.
+#[derive(Debug, Clone)]
+pub struct MazeReport {
+    pub level_id: u32,
+    pub name: String,
+    pub walkable_cells: usize,
+    pub region_sizes: Vec<usize>, // largest first
+    pub dead_ends: usize,
+    pub spawns: Vec<SpawnStats>,
+}
+
+impl MazeReport {
+    pub fn is_connected(&self) -> bool {
+        self.region_sizes.len() <= 1
+    }
+
+    pub fn min_spawn_separation(&self) -> Option<u32> {
+        self.spawns.iter().filter_map(|s| s.nearest_spawn).min()
+    }
+
+    /// Human readable list of everything that makes the level unfair or broken.
+    pub fn problems(&self) -> Vec<String> {
+        let mut out = Vec::new();
+        if self.walkable_cells == 0 {
+            out.push("no walkable cells".to_string());
+        }
+        if !self.is_connected() {
+            out.push(format!(
+                "walkable area split into {} regions (sizes {:?})",
+                self.region_sizes.len(),
+                self.region_sizes
+            ));
+        }
+        if self.spawns.is_empty() {
+            out.push("no spawn points".to_string());
+        }
+        for s in self.spawns.iter().filter(|s| !s.reachable) {
+            out.push(format!("spawn {:?} is outside the main region", s.pos));
+        }
+        if let Some(min) = self.min_spawn_separation()
+            && min < MIN_SPAWN_SEPARATION
+        {
+            out.push(format!(
+                "spawns are clustered (closest pair {} steps apart, want >= {})",
+                min, MIN_SPAWN_SEPARATION
+            ));
+        }
+        out
+    }
+
+    pub fn is_playable(&self) -> bool {
+        self.problems().is_empty()
+    }
+}
+
+impl std::fmt::Display for MazeReport {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        writeln!(f, "Level {}: '{}'", self.level_id, self.name)?;
+        writeln!(f, "  walkable cells : {}", self.walkable_cells)?;
+        writeln!(f, "  regions        : {:?}", self.region_sizes)?;
+        writeln!(f, "  dead ends      : {}", self.dead_ends)?;
+        writeln!(f, "  spawns         : {}", self.spawns.len())?;
+        for s in &self.spawns {
+            let nearest = s
+                .nearest_spawn
+                .map(|d| d.to_string())
+                .unwrap_or_else(|| "-".to_string());
+            let mean = s
+                .mean_spawn_distance
+                .map(|d| format!("{:.1}", d))
+                .unwrap_or_else(|| "-".to_string());
+            writeln!(
+                f,
+                "    ({:>2},{:>2}) reachable={:<5} nearest={:>3} mean={:>5}",
+                s.pos.0, s.pos.1, s.reachable, nearest, mean
+            )?;
+        }
+        let problems = self.problems();
+        if problems.is_empty() {
+            writeln!(f, "  status         : OK")
+        } else {
+            for p in problems {
+                writeln!(f, "  problem        : {}", p)?;
+            }
+            Ok(())
+        }
+    }
+}
+
+impl Maze {
+    /// Flood-fills the walkable cells into 4-connected regions, largest first.
+    pub fn regions(&self) -> Vec<Vec<Tile>> {
+        let mut seen = vec![false; self.width * self.height];
+        let mut regions = Vec::new();
+        for y in 0..self.height {
+            for x in 0..self.width {
+                if seen[y * self.width + x] || !self.is_walkable(x, y) {
+                    continue;
+                }
+                let mut region = Vec::new();
+                let mut queue = VecDeque::from([(x, y)]);
+                seen[y * self.width + x] = true;
+                while let Some((cx, cy)) = queue.pop_front() {
+                    region.push((cx, cy));
+                    for (nx, ny) in self.walkable_neighbors(cx, cy) {
+                        if !seen[ny * self.width + nx] {
+                            seen[ny * self.width + nx] = true;
+                            queue.push_back((nx, ny));
+                        }
+                    }
+                }
+                regions.push(region);
+            }
+        }
+        regions.sort_by_key(|r| std::cmp::Reverse(r.len()));
+        regions
+    }
+
+    /// Runs connectivity, dead-end and spawn-distance checks.
+    pub fn validate(&self) -> MazeReport {
+        let regions = self.regions();
+        let main_region: std::collections::HashSet<Tile> = regions
+            .first()
+            .map(|r| r.iter().copied().collect())
+            .unwrap_or_default();
+
+        let mut walkable_cells = 0;
+        let mut dead_ends = 0;
+        for y in 0..self.height {
+            for x in 0..self.width {
+                if self.is_walkable(x, y) {
+                    walkable_cells += 1;
+                    if self.walkable_neighbors(x, y).count() == 1 {
+                        dead_ends += 1;
+                    }
+                }
+            }
+        }
+
+        let spawn_cells = self.spawn_cells();
+        let spawns = spawn_cells
+            .iter()
+            .map(|&pos| {
+                let dist = self.distance_field(pos);
+                let others: Vec<u32> = spawn_cells
+                    .iter()
+                    .filter(|&&other| other != pos)
+                    .filter_map(|&(ox, oy)| dist.get(ox, oy))
+                    .collect();
+                SpawnStats {
+                    pos,
+                    reachable: main_region.contains(&pos),
+                    nearest_spawn: others.iter().copied().min(),
+                    mean_spawn_distance: (!others.is_empty())
+                        .then(|| others.iter().sum::<u32>() as f32 / others.len() as f32),
+                }
+            })
+            .collect();
+
+        MazeReport {
+            level_id: self.level_id,
+            name: self.name.clone(),
+            walkable_cells,
+            region_sizes: regions.iter().map(|r| r.len()).collect(),
+            dead_ends,
+            spawns,
+        }
+    }
+
+    /// Fixes what `validate` complains about: carves corridors joining isolated
+    /// pockets to the main region, then re-spreads spawns if they are clustered
+    /// or unreachable. Returns a description of each change made.
+    pub fn repair(&mut self) -> Vec<String> {
+        let mut actions = Vec::new();
+
+        loop {
+            let regions = self.regions();
+            if regions.len() <= 1 {
+                break;
+            }
+            // Join the closest pocket to the main region with an L-shaped corridor
+            let main = &regions[0];
+            let mut best: Option<(Tile, Tile, usize)> = None;
+            for region in &regions[1..] {
+                for &a in region {
+                    for &b in main {
+                        let d = a.0.abs_diff(b.0) + a.1.abs_diff(b.1);
+                        if best.is_none_or(|(_, _, bd)| d < bd) {
+                            best = Some((a, b, d));
+                        }
+                    }
+                }
+            }
+            let Some((from, to, _)) = best else { break };
+            self.carve_corridor(from, to);
+            actions.push(format!("carved corridor {:?} -> {:?}", from, to));
+        }
+
+        let report = self.validate();
+        let clustered = report
+            .min_spawn_separation()
+            .is_some_and(|d| d < MIN_SPAWN_SEPARATION);
+        let stranded = report.spawns.iter().any(|s| !s.reachable);
+        if report.spawns.is_empty() || clustered || stranded {
+            let count = report.spawns.len().max(1);
+            self.spread_spawns(count);
+            actions.push(format!("re-spread {} spawn points", count));
+        }
+
+        actions
+    }
+
+    fn carve_corridor(&mut self, from: Tile, to: Tile) {
+        let (mut x, y) = from;
+        while x != to.0 {
+            x = if x < to.0 { x + 1 } else { x - 1 };
+            if self.grid[y][x] == Cell::Wall {
+                self.set_path(x, y);
+            }
+        }
+        let mut y = y;
+        while y != to.1 {
+            y = if y < to.1 { y + 1 } else { y - 1 };
+            if self.grid[y][x] == Cell::Wall {
+                self.set_path(x, y);
+            }
+        }
+    }
+
+    // Farthest-point sampling over plain path cells of the main region.
+    fn spread_spawns(&mut self, count: usize) {
+        for (x, y) in self.spawn_cells() {
+            self.set_path(x, y);
+        }
+        let Some(main) = self.regions().into_iter().next() else {
+            return;
+        };
+        let candidates: Vec<Tile> = main
+            .into_iter()
+            .filter(|&(x, y)| self.grid[y][x] == Cell::Path)
+            .collect();
+        let Some(&origin) = candidates.first() else {
+            return;
+        };
+
+        // Start from the cell farthest from an arbitrary origin, then keep picking
+        // the candidate farthest from every spawn chosen so far.
+        let mut nearest = self.distance_field(origin).as_slice().to_vec();
+        let mut chosen = Vec::with_capacity(count);
+        while chosen.len() < count.min(candidates.len()) {
+            let &next = candidates
+                .iter()
+                .filter(|p| !chosen.contains(*p))
+                .max_by_key(|&&(x, y)| nearest[y * self.width + x])
+                .expect("candidates remain");
+            chosen.push(next);
+            if chosen.len() == 1 {
+                nearest = vec![UNREACHED; nearest.len()];
+            }
+            for (n, d) in nearest.iter_mut().zip(self.distance_field(next).as_slice()) {
+                *n = (*n).min(*d);
+            }
+        }
+
+        for (x, y) in chosen {
+            self.set_spawn_point(x, y);
+        }
+    }
+}
+
+/// CLI entry point: prints a validation report for every built-in level,
+/// before and after repair. Fails if any level is still unplayable.
+pub fn print_level_report() -> anyhow::Result<()> {
+    let mut broken = Vec::new();
+    for level in 1..=LEVEL_COUNT {
+        let mut maze = Maze::build_level(level);
+        println!("{}", maze.validate());
+        let actions = maze.repair();
+        if actions.is_empty() {
+            continue;
+        }
+        for action in &actions {
+            println!("  repair         : {}", action);
+        }
+        let repaired = maze.validate();
+        println!("-- after repair --\n{}", repaired);
+        if !repaired.is_playable() {
+            broken.push(level);
+        }
+    }
+    if !broken.is_empty() {
+        anyhow::bail!("levels {:?} are unplayable even after repair", broken);
+    }
+    Ok(())
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::game::logic::tests::maze_from;
+
+    #[test]
+    fn regions_are_split_by_walls_largest_first() {
+        let maze = maze_from(&[
+            "#######", //
+            "#...#.#", //
+            "#...#.#", //
+            "#######",
+        ]);
+        let regions = maze.regions();
+        assert_eq!(
+            regions.iter().map(|r| r.len()).collect::<Vec<_>>(),
+            vec![6, 2]
+        );
+        assert!(regions[1].contains(&(5, 1)));
+    }
+
+    #[test]
+    fn validate_reports_pockets_and_clustered_spawns() {
+        let maze = maze_from(&[
+            "#########", //
+            "#SS....##", //
+            "#......#.", //
+            "#########",
+        ]);
+        let report = maze.validate();
+        assert!(!report.is_connected());
+        assert_eq!(report.min_spawn_separation(), Some(1));
+        assert_eq!(report.problems().len(), 2);
+        assert!(!report.is_playable());
+
+        let fine = maze_from(&["#########", "#S.....S#", "#########"]).validate();
+        assert!(fine.is_playable(), "{:?}", fine.problems());
+        assert_eq!(fine.min_spawn_separation(), Some(6));
+    }
+
+    #[test]
+    fn repair_joins_pockets_and_spreads_spawns() {
+        let mut maze = maze_from(&[
+            "#########", //
+            "#SSS.#..#", //
+            "#....#..#", //
+            "#########",
+        ]);
+        let actions = maze.repair();
+        assert_eq!(actions.len(), 2, "{:?}", actions);
+        let report = maze.validate();
+        assert!(report.is_connected());
+        assert_eq!(report.spawns.len(), 3);
+        assert!(report.is_playable(), "{:?}", report.problems());
+        // A playable maze is left alone
+        assert!(maze.repair().is_empty());
+    }
+
+    #[test]
+    fn repair_fixes_level_5() {
+        let mut maze = Maze::build_level(5);
+        let before = maze.validate();
+        assert!(before.region_sizes.len() > 1);
+        assert_eq!(before.min_spawn_separation(), Some(1));
+
+        maze.repair();
+        let after = maze.validate();
+        assert_eq!(after.region_sizes.len(), 1);
+        assert_eq!(after.spawns.len(), before.spawns.len());
+        assert!(after.min_spawn_separation().unwrap() >= MIN_SPAWN_SEPARATION);
+        assert!(after.is_playable(), "{:?}", after.problems());
+    }
+}
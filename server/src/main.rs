@@ -1,18 +1,177 @@
 mod game {
+    pub mod bots;
+    pub mod ctf;
     pub mod logic;
+    pub mod mode;
+    pub mod pickups;
+    pub mod spawn;
+    pub mod validation;
 }
+use game::bots::{BotBrain, Difficulty, bot_name};
+use game::ctf::{Flags, Runner};
 use game::logic::{Cell, Maze};
+use game::mode::{Match, MatchSettings, Standing, TEAM_COUNT, Transition};
+use game::pickups::Pickups;
+use game::spawn::{SPAWN_PROTECTION, SpawnSelector};
+use protocol::Weapon;
+
+// Time after switching weapons before the new one can fire.
+const WEAPON_SWITCH_DELAY: f64 = 0.4;
+// Pickup effects
+const MAX_HEALTH: u8 = 100;
+const HEALTH_PACK: u8 = 25;
+const MAX_ARMOR: u8 = 100;
+const ARMOR_PACK: u8 = 50;
+const BOOST_DURATION: std::time::Duration = std::time::Duration::from_secs(10);
+// Bots go looking for a health pack below this much health
+const BOT_LOW_HEALTH: u8 = 40;
 
 #[tokio::main]
 async fn main() -> anyhow::Result<()> {
-    main_multiplayer().await
+    let args = clap::Command::new("server")
+        .about("Maze War FPS server")
+        .arg(
+            clap::Arg::new("validate-levels")
+                .long("validate-levels")
+                .help("Print a connectivity/spawn fairness report for every level and exit")
+                .action(clap::ArgAction::SetTrue),
+        )
+        .arg(
+            clap::Arg::new("fill-bots")
+                .long("fill-bots")
+                .value_name("N")
+                .help("Add server-driven bots until N players are in the game")
+                .value_parser(clap::value_parser!(usize))
+                .default_value("0"),
+        )
+        .arg(
+            clap::Arg::new("bot-difficulty")
+                .long("bot-difficulty")
+                .value_parser(["easy", "normal", "hard"])
+                .default_value("normal"),
+        )
+        .arg(
+            clap::Arg::new("mode")
+                .long("mode")
+                .help("Game mode: deathmatch, team deathmatch, last man standing or capture the flag")
+                .value_parser(["dm", "tdm", "lms", "ctf"])
+                .default_value("dm"),
+        )
+        .arg(
+            clap::Arg::new("frag-limit")
+                .long("frag-limit")
+                .value_name("KILLS")
+                .help("End the match when a player (or team) reaches this many kills (0 = none)")
+                .value_parser(clap::value_parser!(u32))
+                .default_value("20"),
+        )
+        .arg(
+            clap::Arg::new("capture-limit")
+                .long("capture-limit")
+                .value_name("CAPTURES")
+                .help("End a capture-the-flag match when a team reaches this many captures (0 = none)")
+                .value_parser(clap::value_parser!(u32))
+                .default_value("3"),
+        )
+        .arg(
+            clap::Arg::new("time-limit")
+                .long("time-limit")
+                .value_name("SECS")
+                .help("End the match after this many seconds (0 = none)")
+                .value_parser(clap::value_parser!(u64))
+                .default_value("600"),
+        )
+        .arg(
+            clap::Arg::new("warmup")
+                .long("warmup")
+                .value_name("SECS")
+                .help("Warmup countdown before each match")
+                .value_parser(clap::value_parser!(u64))
+                .default_value("15"),
+        )
+        .arg(
+            clap::Arg::new("respawn-delay")
+                .long("respawn-delay")
+                .value_name("SECS")
+                .help("How long killed players stay dead before respawning")
+                .value_parser(clap::value_parser!(u64))
+                .default_value("3"),
+        )
+        .arg(
+            clap::Arg::new("enemy-radar")
+                .long("enemy-radar")
+                .help("Which enemies the minimap shows: none, those in sight, or all")
+                .value_parser(["off", "visible", "all"])
+                .default_value("all"),
+        )
+        .arg(
+            clap::Arg::new("friendly-fire")
+                .long("friendly-fire")
+                .help("Let bullets hurt teammates in team modes")
+                .action(clap::ArgAction::SetTrue),
+        )
+        .get_matches();
+
+    if args.get_flag("validate-levels") {
+        return game::validation::print_level_report();
+    }
+
+    let config = ServerConfig {
+        bot_fill_target: *args.get_one::<usize>("fill-bots").unwrap_or(&0),
+        bot_difficulty: args
+            .get_one::<String>("bot-difficulty")
+            .and_then(|s| Difficulty::parse(s))
+            .unwrap_or(Difficulty::Normal),
+        match_settings: MatchSettings {
+            mode: args
+                .get_one::<String>("mode")
+                .and_then(|s| MatchSettings::parse_mode(s))
+                .unwrap_or(protocol::GameMode::Deathmatch),
+            frag_limit: args
+                .get_one::<u32>("frag-limit")
+                .copied()
+                .filter(|&n| n > 0),
+            capture_limit: args
+                .get_one::<u32>("capture-limit")
+                .copied()
+                .filter(|&n| n > 0),
+            time_limit: args
+                .get_one::<u64>("time-limit")
+                .copied()
+                .filter(|&n| n > 0)
+                .map(std::time::Duration::from_secs),
+            warmup: std::time::Duration::from_secs(*args.get_one::<u64>("warmup").unwrap_or(&15)),
+            enemy_radar: args
+                .get_one::<String>("enemy-radar")
+                .and_then(|s| MatchSettings::parse_radar(s))
+                .unwrap_or_default(),
+        },
+        friendly_fire: args.get_flag("friendly-fire"),
+        respawn_delay: std::time::Duration::from_secs(
+            *args.get_one::<u64>("respawn-delay").unwrap_or(&3),
+        ),
+    };
+    main_multiplayer(config).await
+}
+
+// Settings chosen on the command line.
+#[derive(Debug, Clone)]
+struct ServerConfig {
+    // Keep adding/removing bots so humans + bots == this (0 = no bots)
+    bot_fill_target: usize,
+    bot_difficulty: Difficulty,
+    match_settings: MatchSettings,
+    // Whether bullets hurt teammates (team modes only)
+    friendly_fire: bool,
+    // Time between dying and respawning
+    respawn_delay: std::time::Duration,
 }
 
 // Multiplayer server implementation
-async fn main_multiplayer() -> anyhow::Result<()> {
+async fn main_multiplayer(config: ServerConfig) -> anyhow::Result<()> {
     // ---- Networking setup ----
     let bind_addr = std::env::var("SERVER_BIND").unwrap_or_else(|_| "0.0.0.0:34254".to_string());
-    let broadcast_hz: u64 = 20;
+    let broadcast_hz = protocol::SNAPSHOT_HZ as u64;
     let socket = std::sync::Arc::new(tokio::net::UdpSocket::bind(&bind_addr).await?);
     println!("Maze War FPS Server listening on {}", socket.local_addr()?);
 
@@ -24,7 +183,9 @@ async fn main_multiplayer() -> anyhow::Result<()> {
     let state = std::sync::Arc::new(parking_lot::Mutex::new(ServerState::new(
         logic_maze,
         wire_level.clone(),
+        config,
     )));
+    state.lock().balance_bots(None);
 
     // ---- Outbound channel + sender task ----
     let (tx_out, mut rx_out) =
@@ -61,6 +222,37 @@ async fn main_multiplayer() -> anyhow::Result<()> {
                     st.update_bullets(&tx_out_broadcast);
                 }
 
+                // Bring back players whose respawn delay is over
+                {
+                    let mut st = state_for_broadcast.lock();
+                    st.update_respawns();
+                }
+
+                // Let bots move and shoot
+                {
+                    let mut st = state_for_broadcast.lock();
+                    st.update_bots(1.0 / broadcast_hz as f32, &tx_out_broadcast);
+                }
+
+                // Finish reloads and hand out items
+                {
+                    let mut st = state_for_broadcast.lock();
+                    st.update_reloads();
+                    st.update_pickups();
+                }
+
+                // Flag pickups, drops and captures
+                {
+                    let mut st = state_for_broadcast.lock();
+                    st.update_flags(&tx_out_broadcast);
+                }
+
+                // Advance the match lifecycle
+                {
+                    let mut st = state_for_broadcast.lock();
+                    st.update_match(&tx_out_broadcast);
+                }
+
                 let snapshot = {
                     let st = state_for_broadcast.lock();
                     let players = st
@@ -74,9 +266,27 @@ async fn main_multiplayer() -> anyhow::Result<()> {
                             angle: info.angle,
                             health: info.health,
                             score: info.score,
-                            ammo: info.ammo,
+                            ammo: info.magazines[info.weapon.index()],
                             kills: info.kills,
                             deaths: info.deaths,
+                            spawn_protected: std::time::Instant::now() < info.spawn_protected_until,
+                            team_id: info.team,
+                            weapon: info.weapon,
+                            reserve_ammo: info.reserves[info.weapon.index()],
+                            reloading: info.reload_done_at.is_some(),
+                            armor: info.armor,
+                            speed_boost_ms: ms_until(info.speed_boost_until),
+                            damage_boost_ms: ms_until(info.damage_boost_until),
+                            life: if info.health > 0 {
+                                protocol::LifeState::Alive
+                            } else {
+                                protocol::LifeState::Dead {
+                                    killer_id: info.killed_by,
+                                    respawn_in_ms: info.respawn_at.map(ms_until),
+                                }
+                            },
+                            ping_ms: info.ping_ms,
+                            is_bot: st.bots.contains_key(pid),
                         })
                         .collect::<Vec<_>>();
 
@@ -104,6 +314,8 @@ async fn main_multiplayer() -> anyhow::Result<()> {
                         server_time_ms: now_ms,
                         players,
                         bullets,
+                        flags: st.flags.as_ref().map(Flags::to_wire).unwrap_or_default(),
+                        pickups: st.pickups.to_wire(),
                     })
                 };
 
@@ -124,29 +336,71 @@ async fn main_multiplayer() -> anyhow::Result<()> {
         let (len, addr) = socket.recv_from(&mut buf).await?;
         let data = &buf[..len];
         match protocol::decode_client(data) {
+            Ok(protocol::ClientToServer::Join(join)) if join.username.trim().is_empty() => {
+                let _ = tx_out.send((
+                    addr,
+                    protocol::ServerToClient::Error {
+                        message: "join rejected: empty name".to_string(),
+                        fatal: true,
+                    },
+                ));
+            }
+
             Ok(protocol::ClientToServer::Join(join)) => {
                 // Register and send Accept with your maze
                 let (pid, info, accept_msg) = {
                     let mut st = state.lock();
-                    let (pid, info) = st.register_player(addr, join.username);
+                    let (pid, info) = st.register_player(addr, join.username, join.team);
                     let accept = protocol::ServerToClient::Accept(protocol::JoinAccept {
                         player_id: pid,
                         level: st.wire_level.clone(),
                     });
-                    (pid, info, accept)
+                    st.balance_bots(Some(&tx_out));
+                    let match_msg = protocol::ServerToClient::MatchState(
+                        st.current_match.to_wire(std::time::Instant::now()),
+                    );
+                    (pid, info, (accept, match_msg))
                 };
-                let _ = tx_out.send((addr, accept_msg));
+                let _ = tx_out.send((addr, accept_msg.0));
+                let _ = tx_out.send((addr, accept_msg.1));
                 println!("Player {} joined as {} from {}", pid, info.username, addr);
             }
 
             Ok(protocol::ClientToServer::Input(input)) => {
                 // Handle movement and shooting
                 let mut st = state.lock();
+                // Only the address that joined as a player may move it (bots have none)
+                let error = match st.player_by_addr.get(&addr) {
+                    Some(&pid) if pid == input.player_id => None,
+                    Some(_) => Some(("input for another player", false)),
+                    // Nobody joined from here (or the server restarted since)
+                    None => Some(("not in the game", true)),
+                };
+                if let Some((message, fatal)) = error {
+                    let _ = tx_out.send((
+                        addr,
+                        protocol::ServerToClient::Error {
+                            message: message.to_string(),
+                            fatal,
+                        },
+                    ));
+                    continue;
+                }
                 st.handle_input(input, &tx_out);
             }
 
             Ok(protocol::ClientToServer::SelectLevel(selection)) => {
                 // Handle level selection
+                if !(1..=game::logic::LEVEL_COUNT as u32).contains(&selection.level_id) {
+                    let _ = tx_out.send((
+                        addr,
+                        protocol::ServerToClient::Error {
+                            message: format!("no level {}", selection.level_id),
+                            fatal: false,
+                        },
+                    ));
+                    continue;
+                }
                 let mut st = state.lock();
                 st.change_level(selection.level_id, &tx_out);
             }
@@ -154,7 +408,7 @@ async fn main_multiplayer() -> anyhow::Result<()> {
             Ok(protocol::ClientToServer::Leave(leave)) => {
                 // Remove and inform others
                 let mut st = state.lock();
-                if let Some(addr) = st.addr_by_player.get(&leave.player_id).copied() {
+                if st.player_by_addr.get(&addr) == Some(&leave.player_id) {
                     st.addr_by_player.remove(&leave.player_id);
                     st.players.remove(&leave.player_id);
                     st.player_by_addr.remove(&addr);
@@ -165,10 +419,20 @@ async fn main_multiplayer() -> anyhow::Result<()> {
                     for dest in st.addr_by_player.values().copied().collect::<Vec<_>>() {
                         let _ = tx_out.send((dest, msg.clone()));
                     }
+                    st.balance_bots(Some(&tx_out));
                 }
             }
 
             Ok(protocol::ClientToServer::Ping(p)) => {
+                // Remember the client's measured RTT for the scoreboard
+                if let Some(rtt) = p.rtt_ms {
+                    let mut st = state.lock();
+                    if let Some(pid) = st.player_by_addr.get(&addr).copied()
+                        && let Some(player) = st.players.get_mut(&pid)
+                    {
+                        player.ping_ms = Some(rtt);
+                    }
+                }
                 let _ = tx_out.send((
                     addr,
                     protocol::ServerToClient::Pong(protocol::Pong { nonce: p.nonce }),
@@ -180,6 +444,7 @@ async fn main_multiplayer() -> anyhow::Result<()> {
                     addr,
                     protocol::ServerToClient::Error {
                         message: format!("bad request: {}", err),
+                        fatal: false,
                     },
                 ));
             }
@@ -196,11 +461,147 @@ struct PlayerInfo {
     angle: f32,
     health: u8,
     score: u32,
-    ammo: u8,
+    weapon: Weapon,
+    magazines: [u8; Weapon::ALL.len()], // rounds left per weapon, indexed by `Weapon::index`
+    reserves: [u16; Weapon::ALL.len()], // spare rounds per weapon
+    reload_done_at: Option<f64>,        // unix seconds the running reload finishes
     kills: u32,
     deaths: u32,
     last_seen: std::time::Instant,
-    last_shot_time: f64,
+    next_shot_time: f64, // unix seconds; fire rate and switch delay gate on this
+    spawn_protected_until: std::time::Instant,
+    team: Option<u8>, // only set in team modes
+    armor: u8,
+    speed_boost_until: std::time::Instant,
+    damage_boost_until: std::time::Instant,
+    respawn_at: Option<std::time::Instant>, // set while dead and waiting to respawn
+    killed_by: Option<u64>,                 // who got the last kill on us, while dead
+    ping_ms: Option<u32>,                   // last RTT the client reported
+}
+
+impl PlayerInfo {
+    /// A fresh, healthy player at `pos` with starting ammo and spawn protection.
+    fn new(username: String, pos: (f32, f32), team: Option<u8>, now: std::time::Instant) -> Self {
+        Self {
+            username,
+            pos_x: pos.0,
+            pos_y: pos.1,
+            angle: 0.0,
+            health: MAX_HEALTH,
+            score: 0,
+            weapon: Weapon::default(),
+            magazines: Weapon::ALL.map(|w| w.stats().magazine),
+            reserves: Weapon::ALL.map(|w| w.stats().starting_reserve),
+            reload_done_at: None,
+            kills: 0,
+            deaths: 0,
+            last_seen: now,
+            next_shot_time: 0.0,
+            spawn_protected_until: now + SPAWN_PROTECTION,
+            team,
+            armor: 0,
+            speed_boost_until: now,
+            damage_boost_until: now,
+            respawn_at: None,
+            killed_by: None,
+            ping_ms: None,
+        }
+    }
+
+    /// Full magazines and starting reserves for every weapon, no armor or
+    /// boosts, nothing pending from the last death (on spawn).
+    fn refill(&mut self) {
+        self.respawn_at = None;
+        self.killed_by = None;
+        self.magazines = Weapon::ALL.map(|w| w.stats().magazine);
+        self.reserves = Weapon::ALL.map(|w| w.stats().starting_reserve);
+        self.reload_done_at = None;
+        self.armor = 0;
+        let now = std::time::Instant::now();
+        self.speed_boost_until = now;
+        self.damage_boost_until = now;
+    }
+
+    fn damage_boosted(&self, now: std::time::Instant) -> bool {
+        now < self.damage_boost_until
+    }
+
+    /// Applies a hit: armor soaks up half the damage while it lasts.
+    fn take_damage(&mut self, damage: u8) {
+        let absorbed = (damage / 2).min(self.armor);
+        self.armor -= absorbed;
+        self.health = self.health.saturating_sub(damage - absorbed);
+    }
+
+    /// Applies a pickup's effect. Returns false if it would do nothing (full
+    /// health, full armor...), so the item stays on the map.
+    fn apply_pickup(&mut self, kind: protocol::PickupKind, now: std::time::Instant) -> bool {
+        match kind {
+            protocol::PickupKind::Ammo => self.add_ammo(),
+            protocol::PickupKind::Health => {
+                if self.health >= MAX_HEALTH {
+                    return false;
+                }
+                self.health = (self.health + HEALTH_PACK).min(MAX_HEALTH);
+                true
+            }
+            protocol::PickupKind::Armor => {
+                if self.armor >= MAX_ARMOR {
+                    return false;
+                }
+                self.armor = (self.armor + ARMOR_PACK).min(MAX_ARMOR);
+                true
+            }
+            protocol::PickupKind::SpeedBoost => {
+                self.speed_boost_until = now + BOOST_DURATION;
+                true
+            }
+            protocol::PickupKind::DamageBoost => {
+                self.damage_boost_until = now + BOOST_DURATION;
+                true
+            }
+        }
+    }
+
+    /// Starts reloading the equipped weapon if it isn't full and there are
+    /// spare rounds.
+    fn start_reload(&mut self, now: f64) {
+        let slot = self.weapon.index();
+        let stats = self.weapon.stats();
+        if self.reload_done_at.is_none()
+            && self.magazines[slot] < stats.magazine
+            && self.reserves[slot] > 0
+        {
+            self.reload_done_at = Some(now + stats.reload_time as f64);
+        }
+    }
+
+    /// Moves rounds from reserve into the magazine once the reload time is up.
+    fn finish_reload(&mut self, now: f64) {
+        if self.reload_done_at.is_none_or(|at| now < at) {
+            return;
+        }
+        self.reload_done_at = None;
+        let slot = self.weapon.index();
+        let missing = self.weapon.stats().magazine - self.magazines[slot];
+        let moved = (missing as u16).min(self.reserves[slot]);
+        self.magazines[slot] += moved as u8;
+        self.reserves[slot] -= moved;
+    }
+
+    /// Adds a magazine's worth of reserve to every weapon, up to the cap.
+    /// Returns false if nothing fit (so the pickup stays on the map).
+    fn add_ammo(&mut self) -> bool {
+        let mut gained = false;
+        for weapon in Weapon::ALL {
+            let stats = weapon.stats();
+            let slot = weapon.index();
+            let topped = (self.reserves[slot] + stats.magazine as u16).min(stats.max_reserve);
+            gained |= topped > self.reserves[slot];
+            self.reserves[slot] = topped;
+        }
+        gained
+    }
 }
 
 // Bullet information
@@ -215,6 +616,7 @@ struct BulletInfo {
     damage: u8,
     lifetime: f32,
     max_lifetime: f32,
+    weapon: Weapon,
 }
 
 // Shared server state.
@@ -234,22 +636,29 @@ struct ServerState {
     // Bullets in the world
     bullets: Vec<BulletInfo>,
 
-    // Precomputed spawn points from your maze logic
-    spawns: Vec<(usize, usize)>,
-    spawn_cursor: usize,
+    // Scores the maze's spawn points against enemy positions
+    spawns: SpawnSelector,
+
+    // Server-driven players; they live in `players` but have no address
+    bots: std::collections::HashMap<u64, BotBrain>,
+    config: ServerConfig,
+
+    // Mode rules and warmup/active/ended lifecycle
+    current_match: Match,
+    last_match_broadcast: std::time::Instant,
+    // Capture-the-flag flags; None in other modes
+    flags: Option<Flags>,
+    // Items at the maze's pickup spots
+    pickups: Pickups,
 }
 
 impl ServerState {
-    fn new(logic_maze: Maze, wire_level: protocol::MazeLevel) -> Self {
-        // Grab plenty of spawns; if fewer, we'll reuse cyclically.
-        let spawns = {
-            let mut s = logic_maze.spawn_points(128);
-            if s.is_empty() {
-                // fall back to a safe-ish default
-                s.push((1, 1));
-            }
-            s
-        };
+    fn new(logic_maze: Maze, wire_level: protocol::MazeLevel, config: ServerConfig) -> Self {
+        let spawns = SpawnSelector::new(&logic_maze);
+        let pickups = Pickups::new(&logic_maze);
+        let current_match = Match::new(config.match_settings.clone());
+        let flags = (current_match.settings.mode == protocol::GameMode::CaptureTheFlag)
+            .then(|| Flags::new(&logic_maze));
 
         Self {
             logic_maze,
@@ -261,16 +670,27 @@ impl ServerState {
             next_bullet_id: 1,
             bullets: Vec::new(),
             spawns,
-            spawn_cursor: 0,
+            bots: std::collections::HashMap::new(),
+            config,
+            current_match,
+            last_match_broadcast: std::time::Instant::now(),
+            flags,
+            pickups,
         }
     }
 
-    fn next_spawn(&mut self) -> (f32, f32) {
-        let (x, y) = self.spawns[self.spawn_cursor % self.spawns.len()];
-        self.spawn_cursor = (self.spawn_cursor + 1) % self.spawns.len();
-        // center of the tile
-        (x as f32 + 0.5, y as f32 + 0.5)
-        // NOTE: If your client uses a different scale, adjust here.
+    /// Picks the safest spawn for `player_id`, treating every other living
+    /// player not on its team as an enemy.
+    fn next_spawn(&mut self, player_id: u64) -> (f32, f32) {
+        let team = self.players.get(&player_id).and_then(|p| p.team);
+        let enemies: Vec<(f32, f32)> = self
+            .players
+            .iter()
+            .filter(|(pid, p)| **pid != player_id && p.health > 0 && hostile(team, p.team))
+            .map(|(_, p)| (p.pos_x, p.pos_y))
+            .collect();
+        self.spawns
+            .pick(&self.logic_maze, &enemies, std::time::Instant::now())
     }
 
     /// Registers a new player and returns (player_id, info).
@@ -278,31 +698,156 @@ impl ServerState {
         &mut self,
         addr: std::net::SocketAddr,
         username: String,
+        team: Option<u8>,
     ) -> (u64, PlayerInfo) {
+        // A resent Join (the client didn't get our Accept) keeps its player
+        if let Some(&player_id) = self.player_by_addr.get(&addr)
+            && let Some(info) = self.players.get(&player_id)
+        {
+            return (player_id, info.clone());
+        }
+        let (player_id, info) = self.add_player(username, team);
+        self.addr_by_player.insert(player_id, addr);
+        self.player_by_addr.insert(addr, player_id);
+        (player_id, info)
+    }
+
+    /// Creates a player at the best spawn; shared by humans and bots.
+    fn add_player(&mut self, username: String, team: Option<u8>) -> (u64, PlayerInfo) {
         let player_id = self.next_player_id;
         self.next_player_id += 1;
 
-        let (sx, sy) = self.next_spawn();
-        let info = PlayerInfo {
-            username,
-            pos_x: sx,
-            pos_y: sy,
-            angle: 0.0,
-            health: 100,
-            score: 0,
-            ammo: 30,
-            kills: 0,
-            deaths: 0,
-            last_seen: std::time::Instant::now(),
-            last_shot_time: 0.0,
-        };
+        let team = self.assign_team(team);
+        let spawn = self.next_spawn(player_id);
+        let mut info = PlayerInfo::new(username, spawn, team, std::time::Instant::now());
+        // Late joiners sit out a last-man-standing round in progress
+        if !self.current_match.respawns_allowed() {
+            info.health = 0;
+        }
 
         self.players.insert(player_id, info.clone());
-        self.addr_by_player.insert(player_id, addr);
-        self.player_by_addr.insert(addr, player_id);
         (player_id, info)
     }
 
+    /// Adds or removes bots so the player count matches `config.bot_fill_target`.
+    /// Departing bots are announced with `PlayerLeft` when `tx_out` is given.
+    fn balance_bots(
+        &mut self,
+        tx_out: Option<
+            &tokio::sync::mpsc::UnboundedSender<(std::net::SocketAddr, protocol::ServerToClient)>,
+        >,
+    ) {
+        let target = self.config.bot_fill_target;
+        if target == 0 {
+            return;
+        }
+
+        while self.players.len() < target {
+            let taken: Vec<&str> = self
+                .bots
+                .keys()
+                .filter_map(|id| self.players.get(id))
+                .map(|p| p.username.as_str())
+                .collect();
+            let name = bot_name(&taken);
+            let (pid, _) = self.add_player(name, None);
+            self.bots
+                .insert(pid, BotBrain::new(self.config.bot_difficulty));
+        }
+
+        while self.players.len() > target {
+            // Drop the newest bot from the biggest team first
+            let counts = self.team_counts();
+            let Some(pid) = self.bots.keys().copied().max_by_key(|pid| {
+                let team = self.players.get(pid).and_then(|p| p.team);
+                (team.map_or(0, |t| counts[t as usize % TEAM_COUNT]), *pid)
+            }) else {
+                break;
+            };
+            self.bots.remove(&pid);
+            self.players.remove(&pid);
+            if let Some(tx_out) = tx_out {
+                let msg =
+                    protocol::ServerToClient::PlayerLeft(protocol::LeaveNotice { player_id: pid });
+                for dest in self.addr_by_player.values() {
+                    let _ = tx_out.send((*dest, msg.clone()));
+                }
+            }
+        }
+    }
+
+    /// Runs every bot's brain and feeds the result through `handle_input`.
+    fn update_bots(
+        &mut self,
+        dt: f32,
+        tx_out: &tokio::sync::mpsc::UnboundedSender<(
+            std::net::SocketAddr,
+            protocol::ServerToClient,
+        )>,
+    ) {
+        let now = std::time::Instant::now();
+        let living: Vec<(u64, Option<u8>, (f32, f32))> = self
+            .players
+            .iter()
+            .filter(|(_, p)| p.health > 0)
+            .map(|(pid, p)| (*pid, p.team, (p.pos_x, p.pos_y)))
+            .collect();
+
+        let mut inputs = Vec::with_capacity(self.bots.len());
+        for (pid, brain) in self.bots.iter_mut() {
+            let Some(me) = self.players.get(pid) else {
+                continue;
+            };
+            if me.health == 0 {
+                continue; // eliminated until the round ends
+            }
+            let enemies: Vec<(u64, (f32, f32))> = living
+                .iter()
+                .filter(|(id, team, _)| id != pid && hostile(me.team, *team))
+                .map(|&(id, _, pos)| (id, pos))
+                .collect();
+            // Hurt or out of ammo for the current weapon: patch up first
+            let slot = me.weapon.index();
+            let dry = me.magazines[slot] == 0 && me.reserves[slot] == 0;
+            let objective = if me.health < BOT_LOW_HEALTH {
+                self.pickups
+                    .nearest(protocol::PickupKind::Health, (me.pos_x, me.pos_y))
+            } else {
+                None
+            };
+            let objective = objective.or_else(|| {
+                dry.then(|| {
+                    self.pickups
+                        .nearest(protocol::PickupKind::Ammo, (me.pos_x, me.pos_y))
+                })
+                .flatten()
+            });
+            let objective = objective.or_else(|| {
+                self.flags.as_ref().zip(me.team).and_then(|(flags, team)| {
+                    flags.objective(&Runner {
+                        player_id: *pid,
+                        team,
+                        pos: (me.pos_x, me.pos_y),
+                        alive: true,
+                    })
+                })
+            });
+            inputs.push(brain.think(
+                *pid,
+                (me.pos_x, me.pos_y, me.angle),
+                &self.logic_maze,
+                &enemies,
+                objective,
+                dt,
+                now,
+            ));
+        }
+
+        for input in inputs {
+            self.handle_input(input, tx_out);
+        }
+    }
+
     /// Handles player input including movement and shooting
     fn handle_input(
         &mut self,
@@ -313,6 +858,10 @@ impl ServerState {
         )>,
     ) {
         if let Some(p) = self.players.get_mut(&input.player_id) {
+            if p.health == 0 {
+                return; // dead players wait for a respawn
+            }
+
             // Handle movement
             let gx = if input.x >= 0.0 {
                 input.x.floor() as usize
@@ -331,31 +880,63 @@ impl ServerState {
                 p.last_seen = std::time::Instant::now();
             }
 
-            // Handle shooting
-            if input.action == protocol::Action::Shoot {
-                let current_time = std::time::SystemTime::now()
-                    .duration_since(std::time::UNIX_EPOCH)
-                    .unwrap()
-                    .as_secs_f64();
-
-                if p.ammo > 0 && (current_time - p.last_shot_time) > 0.5 {
-                    p.ammo -= 1;
-                    p.last_shot_time = current_time;
-
-                    // Create bullet
-                    let bullet = BulletInfo {
-                        bullet_id: self.next_bullet_id,
-                        shooter_id: input.player_id,
-                        x: input.x,
-                        y: input.y,
-                        angle: input.angle,
-                        speed: 25.0, // Increased bullet speed for better gameplay
-                        damage: 25,
-                        lifetime: 0.0,
-                        max_lifetime: 3.0, // 3 seconds max
+            let current_time = std::time::SystemTime::now()
+                .duration_since(std::time::UNIX_EPOCH)
+                .unwrap()
+                .as_secs_f64();
+
+            p.finish_reload(current_time);
+
+            // Handle weapon switching (cancels a running reload)
+            if let Some(weapon) = input.weapon
+                && weapon != p.weapon
+            {
+                p.weapon = weapon;
+                p.reload_done_at = None;
+                p.next_shot_time = p.next_shot_time.max(current_time + WEAPON_SWITCH_DELAY);
+            }
+
+            if input.action == protocol::Action::Reload {
+                p.start_reload(current_time);
+            }
+
+            // Handle shooting; pulling the trigger on an empty magazine reloads
+            if input.action == protocol::Action::Shoot && p.reload_done_at.is_none() {
+                let stats = p.weapon.stats();
+                let slot = p.weapon.index();
+                if p.magazines[slot] == 0 {
+                    p.start_reload(current_time);
+                } else if current_time >= p.next_shot_time {
+                    p.magazines[slot] -= 1;
+                    p.next_shot_time = current_time + stats.fire_interval as f64;
+                    // Shooting gives up spawn protection
+                    p.spawn_protected_until = std::time::Instant::now();
+
+                    let damage = if p.damage_boosted(std::time::Instant::now()) {
+                        (stats.damage as f32 * protocol::DAMAGE_BOOST_FACTOR).min(u8::MAX as f32)
+                            as u8
+                    } else {
+                        stats.damage
                     };
-                    self.next_bullet_id += 1;
-                    self.bullets.push(bullet);
+
+                    // One projectile per pellet, spread evenly-random across the cone
+                    for _ in 0..stats.pellets {
+                        let offset = (rand::random::<f32>() - 0.5) * stats.spread;
+                        let bullet = BulletInfo {
+                            bullet_id: self.next_bullet_id,
+                            shooter_id: input.player_id,
+                            x: input.x,
+                            y: input.y,
+                            angle: input.angle + offset,
+                            speed: stats.projectile_speed,
+                            damage,
+                            lifetime: 0.0,
+                            max_lifetime: stats.range / stats.projectile_speed,
+                            weapon: p.weapon,
+                        };
+                        self.next_bullet_id += 1;
+                        self.bullets.push(bullet);
+                    }
                 }
             }
         }
@@ -374,6 +955,11 @@ impl ServerState {
         let mut hit_events = Vec::new();
         let mut death_events = Vec::new();
         let mut respawn_events = Vec::new();
+        let now = std::time::Instant::now();
+        let counting = self.current_match.counts_kills();
+        let friendly_fire = self.config.friendly_fire;
+        let teams: std::collections::HashMap<u64, Option<u8>> =
+            self.players.iter().map(|(pid, p)| (*pid, p.team)).collect();
 
         for (i, bullet) in self.bullets.iter_mut().enumerate() {
             // Update bullet position
@@ -397,6 +983,16 @@ impl ServerState {
                 if *player_id == bullet.shooter_id {
                     continue; // Can't hit yourself
                 }
+                if now < player.spawn_protected_until {
+                    continue; // Bullets pass through freshly spawned players
+                }
+                if player.health == 0 {
+                    continue; // Eliminated players are out of play
+                }
+                let shooter_team = teams.get(&bullet.shooter_id).copied().flatten();
+                if !friendly_fire && !hostile(shooter_team, player.team) {
+                    continue; // Bullets pass through teammates
+                }
 
                 let dx = bullet.x - player.pos_x;
                 let dy = bullet.y - player.pos_y;
@@ -406,10 +1002,8 @@ impl ServerState {
                     // Hit radius
                     // Player hit!
                     let was_alive = player.health > 0;
-                    if player.health > bullet.damage {
-                        player.health -= bullet.damage;
-                    } else {
-                        player.health = 0;
+                    player.take_damage(bullet.damage);
+                    if player.health == 0 && counting {
                         player.deaths += 1;
                     }
 
@@ -424,11 +1018,11 @@ impl ServerState {
                         death_events.push(protocol::DeathEvent {
                             victim_id: *player_id,
                             killer_id: bullet.shooter_id,
-                            weapon: "Laser".to_string(),
+                            weapon: bullet.weapon,
                         });
 
-                        // Prepare respawn event
-                        respawn_events.push((*player_id, *player_id));
+                        // Prepare respawn event (victim, killer)
+                        respawn_events.push((*player_id, bullet.shooter_id));
                     }
 
                     bullets_to_remove.push(i);
@@ -444,25 +1038,27 @@ impl ServerState {
 
         // Award kills to shooters (separate loop to avoid borrowing issues)
         for event in &death_events {
-            if let Some(shooter) = self.players.get_mut(&event.killer_id) {
+            let victim_team = self.players.get(&event.victim_id).and_then(|p| p.team);
+            if let Some(shooter) = self.players.get_mut(&event.killer_id)
+                && counting
+                && hostile(shooter.team, victim_team)
+            {
                 shooter.kills += 1;
                 shooter.score += 100;
+                self.current_match.record_kill(shooter.team, victim_team);
             }
         }
 
-        // Handle respawns (separate loop to avoid borrowing issues)
-        let mut respawn_positions = Vec::new();
-        for _ in 0..respawn_events.len() {
-            respawn_positions.push(self.next_spawn());
-        }
-
-        for ((player_id, _), (sx, sy)) in respawn_events.iter().zip(respawn_positions.iter()) {
+        // Schedule respawns (separate loop to avoid borrowing issues)
+        let respawn_at = self
+            .current_match
+            .respawns_allowed()
+            .then(|| now + self.config.respawn_delay); // None: stays dead until the round ends
+        for (player_id, killer_id) in &respawn_events {
             if let Some(player) = self.players.get_mut(player_id) {
-                player.pos_x = *sx;
-                player.pos_y = *sy;
-                player.health = 100;
-                player.ammo = 30;
-                player.angle = 0.0;
+                self.spawns.note_death((player.pos_x, player.pos_y), now);
+                player.killed_by = Some(*killer_id);
+                player.respawn_at = respawn_at;
             }
         }
 
@@ -488,6 +1084,221 @@ impl ServerState {
         }
     }
 
+    /// Completes reloads whose time is up, even for players not sending input.
+    fn update_reloads(&mut self) {
+        let current_time = std::time::SystemTime::now()
+            .duration_since(std::time::UNIX_EPOCH)
+            .unwrap()
+            .as_secs_f64();
+        for player in self.players.values_mut() {
+            player.finish_reload(current_time);
+        }
+    }
+
+    /// Respawns dead players whose respawn delay has run out.
+    fn update_respawns(&mut self) {
+        let now = std::time::Instant::now();
+        let due: Vec<u64> = self
+            .players
+            .iter()
+            .filter(|(_, p)| p.health == 0 && p.respawn_at.is_some_and(|at| now >= at))
+            .map(|(pid, _)| *pid)
+            .collect();
+        for pid in due {
+            let (sx, sy) = self.next_spawn(pid);
+            if let Some(player) = self.players.get_mut(&pid) {
+                player.pos_x = sx;
+                player.pos_y = sy;
+                player.health = 100;
+                player.refill();
+                player.angle = 0.0;
+                player.spawn_protected_until = now + SPAWN_PROTECTION;
+            }
+        }
+    }
+
+    /// Restocks pickup spots and gives living players whatever they walk over.
+    fn update_pickups(&mut self) {
+        let now = std::time::Instant::now();
+        self.pickups.update(now);
+        for player in self.players.values_mut().filter(|p| p.health > 0) {
+            for (id, kind) in self.pickups.touching((player.pos_x, player.pos_y)) {
+                if player.apply_pickup(kind, now) {
+                    self.pickups.take(id, now);
+                }
+            }
+        }
+    }
+
+    /// Runs the capture-the-flag rules, scores captures and tells everyone
+    /// about flag pickups, drops, returns and captures.
+    fn update_flags(
+        &mut self,
+        tx_out: &tokio::sync::mpsc::UnboundedSender<(
+            std::net::SocketAddr,
+            protocol::ServerToClient,
+        )>,
+    ) {
+        let Some(flags) = self.flags.as_mut() else {
+            return;
+        };
+        let runners: Vec<Runner> = self
+            .players
+            .iter()
+            .filter_map(|(pid, p)| {
+                Some(Runner {
+                    player_id: *pid,
+                    team: p.team?,
+                    pos: (p.pos_x, p.pos_y),
+                    alive: p.health > 0,
+                })
+            })
+            .collect();
+
+        let events = flags.update(&runners, std::time::Instant::now());
+        let addrs: Vec<std::net::SocketAddr> = self.addr_by_player.values().copied().collect();
+        for event in events {
+            if event.kind == protocol::FlagEventKind::Captured
+                && let Some(pid) = event.player_id
+                && let Some(player) = self.players.get_mut(&pid)
+                && self.current_match.counts_kills()
+            {
+                player.score += 300;
+                if let Some(team) = player.team {
+                    self.current_match.record_capture(team);
+                }
+            }
+            for addr in &addrs {
+                let _ = tx_out.send((*addr, protocol::ServerToClient::Flag(event.clone())));
+            }
+        }
+    }
+
+    /// Respawns every player at once; old positions are meaningless after a
+    /// level change or round start, so each player only avoids the ones
+    /// already placed.
+    fn respawn_all(&mut self) {
+        let now = std::time::Instant::now();
+        let mut placed = Vec::with_capacity(self.players.len());
+        for player in self.players.values_mut() {
+            let (sx, sy) = self.spawns.pick(&self.logic_maze, &placed, now);
+            placed.push((sx, sy));
+            player.pos_x = sx;
+            player.pos_y = sy;
+            player.health = 100;
+            player.refill();
+            player.angle = 0.0;
+            player.spawn_protected_until = now + SPAWN_PROTECTION;
+        }
+    }
+
+    /// Number of players on each team.
+    fn team_counts(&self) -> [usize; TEAM_COUNT] {
+        let mut counts = [0usize; TEAM_COUNT];
+        for team in self.players.values().filter_map(|p| p.team) {
+            counts[team as usize % TEAM_COUNT] += 1;
+        }
+        counts
+    }
+
+    /// Honors the requested team if it is currently one of the smallest,
+    /// otherwise auto-balances onto the smallest team. `None` outside team modes.
+    fn assign_team(&self, requested: Option<u8>) -> Option<u8> {
+        if !self.current_match.settings.is_team_mode() {
+            return None;
+        }
+        let counts = self.team_counts();
+        let smallest = counts.iter().copied().min().unwrap_or(0);
+        match requested {
+            Some(t) if (t as usize) < TEAM_COUNT && counts[t as usize] == smallest => Some(t),
+            _ => (0..TEAM_COUNT).min_by_key(|&t| counts[t]).map(|t| t as u8),
+        }
+    }
+
+    /// Advances warmup/active/ended and tells clients about it: on every
+    /// phase change, and once a second so their timers stay in sync.
+    fn update_match(
+        &mut self,
+        tx_out: &tokio::sync::mpsc::UnboundedSender<(
+            std::net::SocketAddr,
+            protocol::ServerToClient,
+        )>,
+    ) {
+        let now = std::time::Instant::now();
+        let standings: Vec<Standing> = self
+            .players
+            .iter()
+            .map(|(pid, p)| Standing {
+                player_id: *pid,
+                username: p.username.clone(),
+                kills: p.kills,
+                alive: p.health > 0,
+            })
+            .collect();
+
+        let transition = self.current_match.tick(now, &standings);
+        let addrs: Vec<std::net::SocketAddr> = self.addr_by_player.values().copied().collect();
+        match &transition {
+            Some(Transition::Started) => {
+                println!("Match started ({:?})", self.current_match.settings.mode);
+                for player in self.players.values_mut() {
+                    player.score = 0;
+                    player.kills = 0;
+                    player.deaths = 0;
+                }
+                self.bullets.clear();
+                for brain in self.bots.values_mut() {
+                    brain.reset();
+                }
+                if let Some(flags) = self.flags.as_mut() {
+                    flags.reset();
+                }
+                self.respawn_all();
+            }
+            Some(Transition::Ended(winner, reason)) => {
+                println!("Match over: {:?} ({})", winner, reason);
+                let msg = protocol::ServerToClient::MatchOver(protocol::MatchResult {
+                    mode: self.current_match.settings.mode,
+                    winner: winner.clone(),
+                    reason: reason.clone(),
+                });
+                for addr in &addrs {
+                    let _ = tx_out.send((*addr, msg.clone()));
+                }
+            }
+            Some(Transition::Warmup) => {
+                // Bring back anyone eliminated during the round
+                let dead: Vec<u64> = self
+                    .players
+                    .iter()
+                    .filter(|(_, p)| p.health == 0)
+                    .map(|(pid, _)| *pid)
+                    .collect();
+                for pid in dead {
+                    let (sx, sy) = self.next_spawn(pid);
+                    if let Some(player) = self.players.get_mut(&pid) {
+                        player.pos_x = sx;
+                        player.pos_y = sy;
+                        player.health = 100;
+                        player.refill();
+                        player.spawn_protected_until = now + SPAWN_PROTECTION;
+                    }
+                }
+            }
+            None => {}
+        }
+
+        if transition.is_some()
+            || now.duration_since(self.last_match_broadcast) >= std::time::Duration::from_secs(1)
+        {
+            self.last_match_broadcast = now;
+            let msg = protocol::ServerToClient::MatchState(self.current_match.to_wire(now));
+            for addr in &addrs {
+                let _ = tx_out.send((*addr, msg.clone()));
+            }
+        }
+    }
+
     /// Changes the level and respawns all players
     fn change_level(
         &mut self,
@@ -502,8 +1313,10 @@ impl ServerState {
             level_id, self.logic_maze.name, self.logic_maze.level_id
         );
 
-        // Load new maze
+        // Load new maze; a level that fails validation falls back to level 1,
+        // so report the one we actually loaded
         self.logic_maze = Maze::load_level(level_id as u8);
+        let level_id = self.logic_maze.level_id;
         self.wire_level = maze_to_protocol(level_id, &self.logic_maze);
 
         println!(
@@ -511,27 +1324,20 @@ impl ServerState {
             level_id, self.logic_maze.name, self.logic_maze.width, self.logic_maze.height
         );
 
-        // Update spawn points
-        self.spawns = self.logic_maze.spawn_points(128);
-        self.spawn_cursor = 0;
+        // Update spawn points and flag bases
+        self.spawns = SpawnSelector::new(&self.logic_maze);
+        if self.flags.is_some() {
+            self.flags = Some(Flags::new(&self.logic_maze));
+        }
+        self.pickups = Pickups::new(&self.logic_maze);
 
-        // Clear bullets
+        // Clear bullets and stale bot routes
         self.bullets.clear();
-
-        // Collect spawn positions first to avoid borrowing issues
-        let mut spawn_positions = Vec::new();
-        for _ in 0..self.players.len() {
-            spawn_positions.push(self.next_spawn());
+        for brain in self.bots.values_mut() {
+            brain.reset();
         }
 
-        // Respawn all players
-        for ((player_id, player), (sx, sy)) in self.players.iter_mut().zip(spawn_positions.iter()) {
-            player.pos_x = *sx;
-            player.pos_y = *sy;
-            player.health = 100;
-            player.ammo = 30;
-            player.angle = 0.0;
-        }
+        self.respawn_all();
 
         // Send new level to all clients
         let level_msg = protocol::ServerToClient::Accept(protocol::JoinAccept {
@@ -550,8 +1356,23 @@ impl ServerState {
     }
 }
 
+// Milliseconds left until `until`, 0 once it has passed.
+fn ms_until(until: std::time::Instant) -> u32 {
+    ms_left(until, std::time::Instant::now())
+}
+
+fn ms_left(until: std::time::Instant, now: std::time::Instant) -> u32 {
+    until.saturating_duration_since(now).as_millis() as u32
+}
+
+/// Whether players on these teams may score off each other (always outside team modes).
+fn hostile(a: Option<u8>, b: Option<u8>) -> bool {
+    a.is_none() || a != b
+}
+
 /// Convert your logical maze to the protocol's wire format.
 /// Simplest mapping: Cell::Wall => all edges = true; Path/SpawnPoint/Cover => all edges = false.
+/// The cell type itself goes along as `kind`.
 fn maze_to_protocol(level_id: u32, m: &Maze) -> protocol::MazeLevel {
     let mut cells = Vec::with_capacity(m.width * m.height);
     for y in 0..m.height {
@@ -562,6 +1383,12 @@ fn maze_to_protocol(level_id: u32, m: &Maze) -> protocol::MazeLevel {
                 wall_south: is_wall,
                 wall_east: is_wall,
                 wall_west: is_wall,
+                kind: match m.grid[y][x] {
+                    Cell::Wall => protocol::CellKind::Wall,
+                    Cell::Path => protocol::CellKind::Path,
+                    Cell::SpawnPoint => protocol::CellKind::SpawnPoint,
+                    Cell::Cover => protocol::CellKind::Cover,
+                },
             });
         }
     }
@@ -575,3 +1402,112 @@ fn maze_to_protocol(level_id: u32, m: &Maze) -> protocol::MazeLevel {
     }
 }
 
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use std::time::{Duration, Instant};
+
+    fn player() -> PlayerInfo {
+        PlayerInfo::new("test".to_string(), (1.5, 1.5), None, Instant::now())
+    }
+
+    #[test]
+    fn armor_soaks_half_the_damage_while_it_lasts() {
+        let mut p = player();
+        p.armor = 50;
+        p.take_damage(40);
+        assert_eq!((p.health, p.armor), (80, 30));
+
+        p.armor = 5; // runs out partway through the hit
+        p.take_damage(40);
+        assert_eq!((p.health, p.armor), (45, 0));
+
+        p.take_damage(200);
+        assert_eq!(p.health, 0);
+    }
+
+    #[test]
+    fn health_and_armor_pickups_are_refused_when_full() {
+        let now = Instant::now();
+        let mut p = player();
+        assert!(!p.apply_pickup(protocol::PickupKind::Health, now));
+        p.health = MAX_HEALTH - 10;
+        assert!(p.apply_pickup(protocol::PickupKind::Health, now));
+        assert_eq!(p.health, MAX_HEALTH); // capped, not +HEALTH_PACK
+
+        assert!(p.apply_pickup(protocol::PickupKind::Armor, now));
+        assert!(p.apply_pickup(protocol::PickupKind::Armor, now));
+        assert_eq!(p.armor, MAX_ARMOR);
+        assert!(!p.apply_pickup(protocol::PickupKind::Armor, now));
+    }
+
+    #[test]
+    fn boosts_run_out_after_their_duration() {
+        let t0 = Instant::now();
+        let mut p = PlayerInfo::new("test".to_string(), (1.5, 1.5), None, t0);
+        assert!(!p.damage_boosted(t0));
+        assert!(p.apply_pickup(protocol::PickupKind::DamageBoost, t0));
+        assert!(p.apply_pickup(protocol::PickupKind::SpeedBoost, t0));
+        let later = t0 + Duration::from_secs(4);
+        assert!(p.damage_boosted(later));
+        assert_eq!(ms_left(p.speed_boost_until, later), 6000);
+
+        // Another pickup restarts the timer rather than stacking
+        assert!(p.apply_pickup(protocol::PickupKind::SpeedBoost, later));
+        assert_eq!(
+            ms_left(p.speed_boost_until, later),
+            BOOST_DURATION.as_millis() as u32
+        );
+
+        let expired = t0 + BOOST_DURATION;
+        assert!(!p.damage_boosted(expired));
+        assert_eq!(ms_left(p.damage_boost_until, expired), 0);
+    }
+
+    #[test]
+    fn reload_moves_what_the_reserve_has_once_the_timer_is_up() {
+        let mut p = player();
+        let slot = p.weapon.index();
+        let stats = p.weapon.stats();
+        p.magazines[slot] = 0;
+        p.reserves[slot] = 3; // less than a magazine
+        p.start_reload(100.0);
+        let done = 100.0 + stats.reload_time as f64;
+        assert_eq!(p.reload_done_at, Some(done));
+
+        // Pressing reload again mid-reload doesn't restart the timer
+        p.start_reload(100.5);
+        assert_eq!(p.reload_done_at, Some(done));
+
+        p.finish_reload(done - 0.01);
+        assert_eq!((p.magazines[slot], p.reserves[slot]), (0, 3));
+        p.finish_reload(done);
+        assert_eq!((p.magazines[slot], p.reserves[slot]), (3, 0));
+        assert_eq!(p.reload_done_at, None);
+
+        // Nothing left to load
+        p.start_reload(200.0);
+        assert_eq!(p.reload_done_at, None);
+    }
+
+    #[test]
+    fn full_magazine_does_not_reload() {
+        let mut p = player();
+        p.start_reload(0.0);
+        assert_eq!(p.reload_done_at, None);
+    }
+
+    #[test]
+    fn ammo_pickup_tops_up_reserves_to_the_cap() {
+        let mut p = player();
+        for weapon in Weapon::ALL {
+            p.reserves[weapon.index()] = weapon.stats().max_reserve;
+        }
+        assert!(!p.add_ammo());
+
+        let weapon = Weapon::default();
+        p.reserves[weapon.index()] = weapon.stats().max_reserve - 1;
+        assert!(p.add_ammo());
+        assert_eq!(p.reserves[weapon.index()], weapon.stats().max_reserve);
+    }
+}